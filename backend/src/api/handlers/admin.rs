@@ -8,6 +8,7 @@ use uuid::Uuid;
 use crate::api::middleware::AuthUser;
 use crate::errors::{ApiResult, AppError};
 use crate::models::{User, UserResponse};
+use crate::services::routing;
 
 /// User list response
 #[derive(Debug, Serialize)]
@@ -34,6 +35,14 @@ pub struct UpdateUserRequest {
     pub is_operator: Option<bool>,
     pub is_admin: Option<bool>,
     pub is_active: Option<bool>,
+    /// Alias shown to customers; an empty string clears it
+    pub operator_alias: Option<String>,
+    /// Routing skills (languages and topics)
+    pub skills: Option<Vec<String>>,
+    /// Teams (conversation visibility between teammates)
+    pub teams: Option<Vec<String>>,
+    /// Concurrent conversation limit; 0 falls back to the system default
+    pub max_concurrent_chats: Option<u32>,
 }
 
 /// GET /api/admin/users - List all users (admin only)
@@ -144,6 +153,25 @@ pub async fn update_user(
     if let Some(is_active) = req.is_active {
         user.is_active = is_active;
     }
+    if req.operator_alias.is_some() || req.skills.is_some() || req.teams.is_some() || req.max_concurrent_chats.is_some() {
+        let mut settings = user.parsed_settings();
+        if let Some(alias) = req.operator_alias {
+            let alias = alias.trim().to_string();
+            settings.operator_alias = if alias.is_empty() { None } else { Some(alias) };
+        }
+        if let Some(skills) = req.skills {
+            settings.skills = routing::normalize_skills(skills);
+        }
+        if let Some(teams) = req.teams {
+            settings.teams = routing::normalize_skills(teams);
+        }
+        if let Some(max_chats) = req.max_concurrent_chats {
+            settings.max_concurrent_chats = if max_chats == 0 { None } else { Some(max_chats) };
+        }
+        let settings_string = serde_json::to_string(&settings)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize settings: {}", e)))?;
+        user.settings = Some(settings_string);
+    }
 
     // Save
     let user = user_store
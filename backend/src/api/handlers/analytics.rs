@@ -4,15 +4,17 @@
 //! messages, users, and response times.
 
 use axum::{extract::{Query, State}, Extension, Json};
-use chrono::Timelike;
+use chrono::{DateTime, NaiveDate, Timelike, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use storehaus::prelude::*;
 use uuid::Uuid;
 
 use crate::api::middleware::AuthUser;
 use crate::errors::{ApiResult, AppError};
-use crate::models::{Conversation, ConversationStatus, Message};
+use crate::models::{Conversation, ConversationStatus, Message, MessageKind, SlaEventKind, SlaTimer, User};
+use crate::services::{assignments, dispositions, queue};
 
 /// Query parameters for analytics endpoints.
 ///
@@ -26,6 +28,28 @@ pub struct AnalyticsQuery {
     pub end_date: Option<String>,
 }
 
+impl AnalyticsQuery {
+    /// Parse the optional date range (RFC 3339 timestamps or `YYYY-MM-DD` dates).
+    /// A plain end date includes the whole day.
+    pub fn date_range(&self) -> Result<(Option<DateTime<Utc>>, Option<DateTime<Utc>>), AppError> {
+        let start = self.start_date.as_deref().map(|d| parse_date(d, false)).transpose()?;
+        let end = self.end_date.as_deref().map(|d| parse_date(d, true)).transpose()?;
+        Ok((start, end))
+    }
+}
+
+fn parse_date(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, AppError> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| AppError::BadRequest(format!("Invalid date: {}", value)))?;
+    let date = if end_of_day { date.succ_opt().unwrap_or(date) } else { date };
+
+    Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+}
+
 /// Overall system statistics response.
 ///
 /// # Fields
@@ -124,9 +148,10 @@ pub async fn get_overall_stats(
         .map_err(|e| AppError::Database(e.to_string()))?
         .len() as i64;
 
-    // Count total messages
+    // Count total messages (internal notes excluded)
     let total_messages = message_store
-        .find(QueryBuilder::new())
+        .find(QueryBuilder::new()
+            .filter(QueryFilter::ne("kind", serde_json::json!(MessageKind::Note.as_str()))))
         .await
         .map_err(|e| AppError::Database(e.to_string()))?
         .len() as i64;
@@ -175,6 +200,7 @@ pub async fn get_overall_stats(
         let first_msg_query = QueryBuilder::new()
             .filter(QueryFilter::eq("conversation_id", serde_json::json!(conversation.id)))
             .filter(QueryFilter::eq("from_user", serde_json::json!(true)))
+            .filter(QueryFilter::ne("kind", serde_json::json!(MessageKind::Note.as_str())))
             .limit(1);
 
         if let Ok(messages) = message_store.find(first_msg_query).await {
@@ -205,8 +231,9 @@ pub async fn get_overall_stats(
 
 /// Get statistics for all operators.
 ///
-/// Returns performance metrics for each operator including conversations handled
-/// and messages sent.
+/// Returns performance metrics for each operator including conversations handled,
+/// messages sent and average response time. Work done before a transfer stays
+/// with the operator who did it (see `services::assignments`).
 ///
 /// # Endpoint
 ///
@@ -229,95 +256,35 @@ pub async fn get_users_stats(
     Query(_query): Query<AnalyticsQuery>,
     State(storehaus): State<Arc<StoreHaus>>,
 ) -> ApiResult<Json<Vec<UserStats>>> {
-    // Build aggregation query with JOINs using StoreHaus
-    // We need to use conditional COUNT for messages_sent
-    // SELECT
-    //   users.id,
-    //   users.email,
-    //   COUNT(DISTINCT conversations.id) as conversations_handled,
-    //   COUNT(CASE WHEN messages.from_user = true THEN 1 END) as messages_sent
-    // FROM users
-    // LEFT JOIN conversations ON users.id = conversations.user_id
-    // LEFT JOIN messages ON conversations.id = messages.conversation_id
-    // WHERE users.is_operator = true OR users.is_admin = true
-    // GROUP BY users.id, users.email
-
-    let query = QueryBuilder::new()
-        .select_fields(vec![
-            SelectField::field("users.id"),
-            SelectField::field("users.email"),
-            SelectField::count_distinct("conversations.id").with_alias("conversations_handled"),
-        ])
-        .join(JoinClause::new_on(
-            JoinType::Left,
-            "conversations",
-            "users.id",
-            "conversations.user_id",
-        ))
-        .join(JoinClause::new_on(
-            JoinType::Left,
-            "messages",
-            "conversations.id",
-            "messages.conversation_id",
-        ))
-        .filter(QueryFilter::or(vec![
-            QueryFilter::eq("users.is_operator", serde_json::json!(true)),
-            QueryFilter::eq("users.is_admin", serde_json::json!(true)),
-        ]))
-        .group_by(GroupBy::new(vec![
-            "users.id".to_string(),
-            "users.email".to_string(),
-        ]));
-
-    // Build SQL from query
-    let (select_clause, join_clause, where_clause, group_by_clause, _, _, _, where_values, _) =
-        query.build_full();
-
-    // We need to add COUNT(CASE...) manually since StoreHaus doesn't have this yet
-    let custom_select = format!(
-        "users.id, users.email, COUNT(DISTINCT conversations.id) as conversations_handled, \
-         COUNT(CASE WHEN messages.from_user = true THEN 1 END) as messages_sent"
-    );
-
-    let sql = format!(
-        "SELECT {} FROM users {} {} {}",
-        custom_select,
-        join_clause,
-        where_clause,
-        group_by_clause
-    );
-
-    // Execute query and get raw rows
-    let mut query_builder = sqlx::query(&sql);
-    for value in where_values {
-        query_builder = query_builder.bind(value);
-    }
+    let user_store = storehaus
+        .get_store::<GenericStore<User>>("users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    let rows = query_builder
-        .fetch_all(storehaus.pool())
+    let operators = user_store
+        .find(QueryBuilder::new().filter(QueryFilter::or(vec![
+            QueryFilter::eq("is_operator", serde_json::json!(true)),
+            QueryFilter::eq("is_admin", serde_json::json!(true)),
+        ])))
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
 
-    // Extract results from rows
-    let mut user_stats = Vec::new();
-    for row in rows {
-        let user_id: Uuid = row.try_get("id")
-            .map_err(|e| AppError::Database(e.to_string()))?;
-        let user_email: String = row.try_get("email")
-            .map_err(|e| AppError::Database(e.to_string()))?;
-        let conversations_handled: i64 = row.try_get("conversations_handled")
-            .unwrap_or(0);
-        let messages_sent: i64 = row.try_get("messages_sent")
-            .unwrap_or(0);
-
-        user_stats.push(UserStats {
-            user_id,
-            user_email,
-            conversations_handled,
-            messages_sent,
-            average_response_time_seconds: None,
-        });
-    }
+    // Replies and response times are attributed to the operator who handled
+    // the conversation at the time, so transfers don't move past work
+    let mut activity = assignments::operator_activity(&storehaus, None).await?;
+
+    let user_stats = operators
+        .into_iter()
+        .map(|user| {
+            let activity = activity.remove(&user.id).unwrap_or_default();
+            UserStats {
+                user_id: user.id,
+                user_email: user.email,
+                conversations_handled: activity.conversations,
+                messages_sent: activity.messages_sent,
+                average_response_time_seconds: activity.average_response_time,
+            }
+        })
+        .collect();
 
     Ok(Json(user_stats))
 }
@@ -399,20 +366,16 @@ pub async fn get_response_time_stats(
             continue;
         }
 
-        // Find first operator message (from_user = true)
-        if let Some(first_operator_msg) = messages.iter().find(|m| m.from_user) {
-            // Calculate time from conversation start to first operator response
-            let response_time = (first_operator_msg.__created_at__ - conversation.__created_at__).num_seconds();
-            if response_time > 0 {
-                first_response_times.push(response_time as f64);
-            }
+        // Time from conversation start to the first operator reply (notes don't count)
+        if let Some(response_time) = queue::first_response_seconds(&conversation, &messages) {
+            first_response_times.push(response_time);
         }
 
         // Calculate response times between user messages and operator replies
         for i in 0..messages.len() {
             if !messages[i].from_user {
                 // This is a user message, find next operator message
-                if let Some(operator_msg) = messages.iter().skip(i + 1).find(|m| m.from_user) {
+                if let Some(operator_msg) = messages.iter().skip(i + 1).find(|m| m.is_operator_reply()) {
                     let response_time = (operator_msg.__created_at__ - messages[i].__created_at__).num_seconds();
                     if response_time > 0 {
                         all_response_times.push(response_time as f64);
@@ -507,7 +470,7 @@ pub async fn get_message_volume(
     // Group messages by hour of day (0-23)
     let mut hour_counts: std::collections::HashMap<u32, i64> = std::collections::HashMap::new();
 
-    for message in all_messages {
+    for message in all_messages.iter().filter(|m| !m.is_note()) {
         let hour = message.__created_at__.hour();
         *hour_counts.entry(hour).or_insert(0) += 1;
     }
@@ -524,4 +487,502 @@ pub async fn get_message_volume(
     results.sort_by_key(|v| v.hour);
 
     Ok(Json(results))
-}
\ No newline at end of file
+}
+
+/// Conversations per deep link source.
+///
+/// # Fields
+///
+/// * `source` - Deep link source (`None` for conversations without one)
+/// * `conversations` - Number of conversations from this source
+/// * `customers` - Number of distinct Telegram users
+/// * `closed_conversations` - Number of closed conversations
+#[derive(Debug, Serialize)]
+pub struct SourceStats {
+    pub source: Option<String>,
+    pub conversations: i64,
+    pub customers: i64,
+    pub closed_conversations: i64,
+}
+
+/// Customers per first-touch source.
+#[derive(Debug, Serialize)]
+pub struct FirstTouchStats {
+    pub source: Option<String>,
+    pub customers: i64,
+}
+
+/// Source attribution breakdown.
+///
+/// # Fields
+///
+/// * `conversations` - Per-conversation (last touch) attribution
+/// * `first_touch` - Customers by the source of their first `/start`
+#[derive(Debug, Serialize)]
+pub struct SourceBreakdownResponse {
+    pub conversations: Vec<SourceStats>,
+    pub first_touch: Vec<FirstTouchStats>,
+}
+
+/// Get conversation counts broken down by deep link source.
+///
+/// # Endpoint
+///
+/// `GET /api/analytics/sources`
+///
+/// # Query Parameters
+///
+/// * `start_date` - Optional start date (conversation / customer creation time)
+/// * `end_date` - Optional end date
+///
+/// # Returns
+///
+/// * `SourceBreakdownResponse` - Conversations per source and first-touch customers
+///
+/// # Errors
+///
+/// Returns `AppError::BadRequest` for invalid dates and `AppError::Database`
+/// if database operations fail.
+pub async fn get_source_stats(
+    Extension(_auth_user): Extension<AuthUser>,
+    Query(query): Query<AnalyticsQuery>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<SourceBreakdownResponse>> {
+    let (start, end) = query.date_range()?;
+
+    let conversations_sql = format!(
+        "SELECT source, COUNT(*) AS conversations, \
+         COUNT(DISTINCT telegram_user_id) AS customers, \
+         COUNT(*) FILTER (WHERE status = '{}') AS closed_conversations \
+         FROM conversations \
+         WHERE __deleted_at__ IS NULL \
+         AND ($1::timestamptz IS NULL OR __created_at__ >= $1) \
+         AND ($2::timestamptz IS NULL OR __created_at__ < $2) \
+         GROUP BY source \
+         ORDER BY conversations DESC",
+        ConversationStatus::Closed.as_str()
+    );
+
+    let rows = sqlx::query(&conversations_sql)
+        .bind(start)
+        .bind(end)
+        .fetch_all(storehaus.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let mut conversations = Vec::new();
+    for row in rows {
+        conversations.push(SourceStats {
+            source: row.try_get("source").map_err(|e| AppError::Database(e.to_string()))?,
+            conversations: row.try_get("conversations").unwrap_or(0),
+            customers: row.try_get("customers").unwrap_or(0),
+            closed_conversations: row.try_get("closed_conversations").unwrap_or(0),
+        });
+    }
+
+    let first_touch_sql = "SELECT first_source AS source, COUNT(*) AS customers \
+         FROM telegram_users \
+         WHERE __deleted_at__ IS NULL \
+         AND ($1::timestamptz IS NULL OR __created_at__ >= $1) \
+         AND ($2::timestamptz IS NULL OR __created_at__ < $2) \
+         GROUP BY first_source \
+         ORDER BY customers DESC";
+
+    let rows = sqlx::query(first_touch_sql)
+        .bind(start)
+        .bind(end)
+        .fetch_all(storehaus.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let mut first_touch = Vec::new();
+    for row in rows {
+        first_touch.push(FirstTouchStats {
+            source: row.try_get("source").map_err(|e| AppError::Database(e.to_string()))?,
+            customers: row.try_get("customers").unwrap_or(0),
+        });
+    }
+
+    Ok(Json(SourceBreakdownResponse {
+        conversations,
+        first_touch,
+    }))
+}
+
+/// Conversation and customer counts for a single tag.
+#[derive(Debug, Serialize)]
+pub struct TagStats {
+    pub tag_id: Uuid,
+    pub name: String,
+    pub color: String,
+    pub conversations: i64,
+    pub closed_conversations: i64,
+    pub customers: i64,
+}
+
+/// Get conversation counts broken down by tag.
+///
+/// # Endpoint
+///
+/// `GET /api/analytics/tags`
+///
+/// # Query Parameters
+///
+/// * `start_date` - Optional start date (conversation creation time)
+/// * `end_date` - Optional end date
+///
+/// # Returns
+///
+/// * `Vec<TagStats>` - One entry per tag, including unused tags, ordered by
+///   conversation count. `customers` counts tagged telegram users regardless
+///   of the date range.
+///
+/// # Errors
+///
+/// Returns `AppError::BadRequest` for invalid dates and `AppError::Database`
+/// if database operations fail.
+pub async fn get_tag_stats(
+    Extension(_auth_user): Extension<AuthUser>,
+    Query(query): Query<AnalyticsQuery>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Vec<TagStats>>> {
+    let (start, end) = query.date_range()?;
+
+    let sql = format!(
+        "SELECT t.id, t.name, t.color, \
+         COUNT(DISTINCT c.id) AS conversations, \
+         COUNT(DISTINCT c.id) FILTER (WHERE c.status = '{}') AS closed_conversations, \
+         (SELECT COUNT(*) FROM telegram_user_tags tut \
+          WHERE tut.tag_id = t.id AND tut.__deleted_at__ IS NULL) AS customers \
+         FROM tags t \
+         LEFT JOIN conversation_tags ct ON ct.tag_id = t.id AND ct.__deleted_at__ IS NULL \
+         LEFT JOIN conversations c ON c.id = ct.conversation_id AND c.__deleted_at__ IS NULL \
+         AND ($1::timestamptz IS NULL OR c.__created_at__ >= $1) \
+         AND ($2::timestamptz IS NULL OR c.__created_at__ < $2) \
+         WHERE t.__deleted_at__ IS NULL \
+         GROUP BY t.id, t.name, t.color \
+         ORDER BY conversations DESC, t.name ASC",
+        ConversationStatus::Closed.as_str()
+    );
+
+    let rows = sqlx::query(&sql)
+        .bind(start)
+        .bind(end)
+        .fetch_all(storehaus.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let mut stats = Vec::new();
+    for row in rows {
+        stats.push(TagStats {
+            tag_id: row.try_get("id").map_err(|e| AppError::Database(e.to_string()))?,
+            name: row.try_get("name").map_err(|e| AppError::Database(e.to_string()))?,
+            color: row.try_get("color").map_err(|e| AppError::Database(e.to_string()))?,
+            conversations: row.try_get("conversations").unwrap_or(0),
+            closed_conversations: row.try_get("closed_conversations").unwrap_or(0),
+            customers: row.try_get("customers").unwrap_or(0),
+        });
+    }
+
+    Ok(Json(stats))
+}
+
+/// Breach counts for one SLA timer.
+#[derive(Debug, Serialize)]
+pub struct SlaTimerStats {
+    pub timer: String,
+    /// Conversations that ran this timer
+    pub conversations: i64,
+    /// Conversations where the timer was breached at least once
+    pub breached: i64,
+    /// `breached / conversations` as a percentage
+    pub breach_rate: f64,
+}
+
+impl SlaTimerStats {
+    fn new(timer: SlaTimer, conversations: i64, breached: i64) -> Self {
+        let breach_rate = if conversations > 0 {
+            breached as f64 / conversations as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        Self {
+            timer: timer.as_str().to_string(),
+            conversations,
+            breached,
+            breach_rate,
+        }
+    }
+}
+
+/// SLA breach counts for a single policy.
+#[derive(Debug, Serialize)]
+pub struct SlaPolicyStats {
+    pub policy_id: Uuid,
+    pub name: String,
+    pub timers: Vec<SlaTimerStats>,
+}
+
+/// SLA breach statistics response.
+#[derive(Debug, Serialize)]
+pub struct SlaStatsResponse {
+    /// Totals over all policies, one entry per timer
+    pub timers: Vec<SlaTimerStats>,
+    pub policies: Vec<SlaPolicyStats>,
+}
+
+/// Get SLA breach rates per timer and per policy.
+///
+/// # Endpoint
+///
+/// `GET /api/analytics/sla`
+///
+/// # Query Parameters
+///
+/// * `start_date` - Optional start date (conversation creation time)
+/// * `end_date` - Optional end date
+///
+/// # Returns
+///
+/// * `SlaStatsResponse` - Breach counts for conversations that had an SLA
+///   policy. A timer only counts for policies that define its target.
+///
+/// # Errors
+///
+/// Returns `AppError::BadRequest` for invalid dates and `AppError::Database`
+/// if database operations fail.
+pub async fn get_sla_stats(
+    Extension(_auth_user): Extension<AuthUser>,
+    Query(query): Query<AnalyticsQuery>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<SlaStatsResponse>> {
+    let (start, end) = query.date_range()?;
+
+    let mut columns = Vec::new();
+    for timer in SlaTimer::ALL {
+        let name = timer.as_str();
+        columns.push(format!(
+            "COUNT(DISTINCT c.id) FILTER (WHERE p.{name}_minutes IS NOT NULL) AS {name}_total, \
+             COUNT(DISTINCT e.conversation_id) FILTER (WHERE e.timer = '{name}') AS {name}_breached"
+        ));
+    }
+
+    let sql = format!(
+        "SELECT p.id, p.name, {} \
+         FROM sla_policies p \
+         LEFT JOIN conversations c ON c.sla_policy_id = p.id AND c.__deleted_at__ IS NULL \
+         AND ($1::timestamptz IS NULL OR c.__created_at__ >= $1) \
+         AND ($2::timestamptz IS NULL OR c.__created_at__ < $2) \
+         LEFT JOIN sla_events e ON e.conversation_id = c.id AND e.policy_id = p.id \
+         AND e.kind = '{}' AND e.__deleted_at__ IS NULL \
+         WHERE p.__deleted_at__ IS NULL \
+         GROUP BY p.id, p.name \
+         ORDER BY p.name ASC",
+        columns.join(", "),
+        SlaEventKind::Breach.as_str()
+    );
+
+    let rows = sqlx::query(&sql)
+        .bind(start)
+        .bind(end)
+        .fetch_all(storehaus.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let mut totals = [(0i64, 0i64); 3];
+    let mut policies = Vec::new();
+    for row in rows {
+        let mut timers = Vec::new();
+        for (i, timer) in SlaTimer::ALL.into_iter().enumerate() {
+            let conversations: i64 = row.try_get(format!("{}_total", timer.as_str()).as_str()).unwrap_or(0);
+            let breached: i64 = row.try_get(format!("{}_breached", timer.as_str()).as_str()).unwrap_or(0);
+            totals[i].0 += conversations;
+            totals[i].1 += breached;
+            timers.push(SlaTimerStats::new(timer, conversations, breached));
+        }
+
+        policies.push(SlaPolicyStats {
+            policy_id: row.try_get("id").map_err(|e| AppError::Database(e.to_string()))?,
+            name: row.try_get("name").map_err(|e| AppError::Database(e.to_string()))?,
+            timers,
+        });
+    }
+
+    let timers = SlaTimer::ALL
+        .into_iter()
+        .zip(totals)
+        .map(|(timer, (conversations, breached))| SlaTimerStats::new(timer, conversations, breached))
+        .collect();
+
+    Ok(Json(SlaStatsResponse { timers, policies }))
+}
+
+/// Query parameters for disposition statistics.
+///
+/// # Fields
+///
+/// * `start_date` / `end_date` - Optional range of close times
+/// * `period` - Bucket size: `day`, `week` (default) or `month`
+#[derive(Debug, Deserialize)]
+pub struct DispositionStatsQuery {
+    #[serde(flatten)]
+    pub range: AnalyticsQuery,
+    pub period: Option<String>,
+}
+
+/// Number of conversations closed with one disposition code.
+#[derive(Debug, Serialize)]
+pub struct DispositionCount {
+    pub disposition: String,
+    /// Current label of the code (the code itself when it was deleted)
+    pub label: String,
+    pub conversations: i64,
+}
+
+/// Dispositions of the conversations closed by one operator.
+#[derive(Debug, Serialize)]
+pub struct OperatorDispositionStats {
+    pub user_id: Uuid,
+    pub user_email: Option<String>,
+    pub total: i64,
+    pub dispositions: Vec<DispositionCount>,
+}
+
+/// Dispositions of the conversations closed in one period.
+#[derive(Debug, Serialize)]
+pub struct PeriodDispositionStats {
+    pub period_start: DateTime<Utc>,
+    pub total: i64,
+    pub dispositions: Vec<DispositionCount>,
+}
+
+/// Disposition statistics response.
+#[derive(Debug, Serialize)]
+pub struct DispositionStatsResponse {
+    pub period: String,
+    /// Totals over the whole range
+    pub dispositions: Vec<DispositionCount>,
+    pub operators: Vec<OperatorDispositionStats>,
+    /// Periods with at least one disposition, oldest first
+    pub periods: Vec<PeriodDispositionStats>,
+}
+
+/// Get disposition code counts in total, per operator and per period.
+///
+/// # Endpoint
+///
+/// `GET /api/analytics/dispositions`
+///
+/// # Query Parameters
+///
+/// * `start_date` - Optional start date (close time)
+/// * `end_date` - Optional end date
+/// * `period` - `day`, `week` (default) or `month`
+///
+/// # Returns
+///
+/// * `DispositionStatsResponse` - Counts for conversations closed with a
+///   disposition code, attributed to the operator who closed them. Periods
+///   are UTC calendar days, ISO weeks or months.
+///
+/// # Errors
+///
+/// Returns `AppError::BadRequest` for invalid dates or periods and
+/// `AppError::Database` if database operations fail.
+pub async fn get_disposition_stats(
+    Extension(_auth_user): Extension<AuthUser>,
+    Query(query): Query<DispositionStatsQuery>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<DispositionStatsResponse>> {
+    let (start, end) = query.range.date_range()?;
+
+    let period = query.period.as_deref().unwrap_or("week");
+    if !matches!(period, "day" | "week" | "month") {
+        return Err(AppError::BadRequest("Invalid period. Must be 'day', 'week' or 'month'".to_string()));
+    }
+
+    let sql = format!(
+        "SELECT c.disposition, c.closed_by, u.email, \
+         date_trunc('{}', c.closed_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' AS period_start, \
+         COUNT(*) AS conversations \
+         FROM conversations c \
+         LEFT JOIN users u ON u.id = c.closed_by \
+         WHERE c.__deleted_at__ IS NULL AND c.status = '{}' AND c.disposition IS NOT NULL \
+         AND ($1::timestamptz IS NULL OR c.closed_at >= $1) \
+         AND ($2::timestamptz IS NULL OR c.closed_at < $2) \
+         GROUP BY c.disposition, c.closed_by, u.email, period_start",
+        period,
+        ConversationStatus::Closed.as_str()
+    );
+
+    let rows = sqlx::query(&sql)
+        .bind(start)
+        .bind(end)
+        .fetch_all(storehaus.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let labels: HashMap<String, String> = dispositions::list_codes(&storehaus)
+        .await?
+        .into_iter()
+        .map(|c| (c.code, c.label))
+        .collect();
+
+    let mut totals: BTreeMap<String, i64> = BTreeMap::new();
+    let mut operators: BTreeMap<Uuid, (Option<String>, BTreeMap<String, i64>)> = BTreeMap::new();
+    let mut periods: BTreeMap<DateTime<Utc>, BTreeMap<String, i64>> = BTreeMap::new();
+    for row in rows {
+        let disposition: String = row.try_get("disposition").map_err(|e| AppError::Database(e.to_string()))?;
+        let closed_by: Option<Uuid> = row.try_get("closed_by").map_err(|e| AppError::Database(e.to_string()))?;
+        let period_start: DateTime<Utc> =
+            row.try_get("period_start").map_err(|e| AppError::Database(e.to_string()))?;
+        let conversations: i64 = row.try_get("conversations").unwrap_or(0);
+
+        *totals.entry(disposition.clone()).or_default() += conversations;
+        *periods.entry(period_start).or_default().entry(disposition.clone()).or_default() += conversations;
+        if let Some(user_id) = closed_by {
+            let (_, by_code) = operators
+                .entry(user_id)
+                .or_insert_with(|| (row.try_get("email").ok().flatten(), BTreeMap::new()));
+            *by_code.entry(disposition).or_default() += conversations;
+        }
+    }
+
+    let counts = |by_code: BTreeMap<String, i64>| -> (i64, Vec<DispositionCount>) {
+        let mut dispositions: Vec<DispositionCount> = by_code
+            .into_iter()
+            .map(|(disposition, conversations)| DispositionCount {
+                label: labels.get(&disposition).cloned().unwrap_or_else(|| disposition.clone()),
+                disposition,
+                conversations,
+            })
+            .collect();
+        dispositions.sort_by_key(|d| std::cmp::Reverse(d.conversations));
+        (dispositions.iter().map(|d| d.conversations).sum(), dispositions)
+    };
+
+    let mut operators: Vec<OperatorDispositionStats> = operators
+        .into_iter()
+        .map(|(user_id, (user_email, dispositions))| {
+            let (total, dispositions) = counts(dispositions);
+            OperatorDispositionStats { user_id, user_email, total, dispositions }
+        })
+        .collect();
+    operators.sort_by_key(|o| std::cmp::Reverse(o.total));
+
+    let periods = periods
+        .into_iter()
+        .map(|(period_start, dispositions)| {
+            let (total, dispositions) = counts(dispositions);
+            PeriodDispositionStats { period_start, total, dispositions }
+        })
+        .collect();
+
+    Ok(Json(DispositionStatsResponse {
+        period: period.to_string(),
+        dispositions: counts(totals).1,
+        operators,
+        periods,
+    }))
+}
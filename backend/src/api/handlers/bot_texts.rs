@@ -0,0 +1,171 @@
+use axum::{extract::{Path, Query, State}, Extension, Json};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use storehaus::prelude::*;
+
+use crate::api::middleware::AuthUser;
+use crate::errors::{ApiResult, AppError};
+use crate::l10n::{self, BotMessages};
+use crate::services::bot_texts;
+
+/// Bot text list query parameters
+#[derive(Debug, Deserialize)]
+pub struct BotTextListQuery {
+    pub locale: Option<String>,
+}
+
+/// Bot text with its file default and admin override
+#[derive(Debug, Serialize)]
+pub struct BotTextResponse {
+    pub locale: String,
+    pub key: String,
+    pub default_value: String,
+    pub override_value: Option<String>,
+    pub value: String,
+}
+
+/// Update bot text request
+#[derive(Debug, Deserialize)]
+pub struct UpdateBotTextRequest {
+    pub value: String,
+}
+
+/// Preview bot text request
+#[derive(Debug, Deserialize)]
+pub struct PreviewBotTextRequest {
+    pub locale: String,
+    /// Message key to render (uses the effective text)
+    pub key: Option<String>,
+    /// Unsaved text to render instead of the stored one
+    pub template: Option<String>,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+/// Preview bot text response
+#[derive(Debug, Serialize)]
+pub struct PreviewBotTextResponse {
+    pub template: String,
+    pub rendered: String,
+}
+
+fn validate_locale(locale: &str) -> ApiResult<()> {
+    if !l10n::available_locales().contains(&locale) {
+        return Err(AppError::NotFound(format!("Unknown locale '{}'", locale)));
+    }
+    Ok(())
+}
+
+fn validate_key(key: &str) -> ApiResult<()> {
+    if !BotMessages::KEYS.contains(&key) {
+        return Err(AppError::NotFound(format!("Unknown bot message key '{}'", key)));
+    }
+    Ok(())
+}
+
+fn bot_text_response(locale: &str, key: &str) -> BotTextResponse {
+    let defaults = &l10n::get_locale_by_key(locale).bot;
+    let overrides = l10n::overrides_for_locale(locale);
+    let default_value = defaults.get(key).unwrap_or_default().to_string();
+    let override_value = overrides.get(key).cloned();
+
+    BotTextResponse {
+        locale: locale.to_string(),
+        key: key.to_string(),
+        value: override_value.clone().unwrap_or_else(|| default_value.clone()),
+        default_value,
+        override_value,
+    }
+}
+
+/// GET /api/admin/bot-texts - List bot texts per locale (admin only)
+pub async fn get_bot_texts(
+    Extension(_auth_user): Extension<AuthUser>,
+    Query(query): Query<BotTextListQuery>,
+) -> ApiResult<Json<Vec<BotTextResponse>>> {
+    let locales = match query.locale {
+        Some(locale) => {
+            validate_locale(&locale)?;
+            vec![locale]
+        }
+        None => l10n::available_locales().into_iter().map(String::from).collect(),
+    };
+
+    let results = locales
+        .iter()
+        .flat_map(|locale| {
+            BotMessages::KEYS
+                .iter()
+                .map(move |key| bot_text_response(locale, key))
+        })
+        .collect();
+
+    Ok(Json(results))
+}
+
+/// PUT /api/admin/bot-texts/:locale/:key - Override bot text (admin only)
+pub async fn update_bot_text(
+    Extension(auth_user): Extension<AuthUser>,
+    Path((locale, key)): Path<(String, String)>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    Json(req): Json<UpdateBotTextRequest>,
+) -> ApiResult<Json<BotTextResponse>> {
+    validate_locale(&locale)?;
+    validate_key(&key)?;
+
+    if req.value.trim().is_empty() {
+        return Err(AppError::Validation("Bot text cannot be empty".to_string()));
+    }
+
+    bot_texts::save_override(&storehaus, &locale, &key, req.value, Some(auth_user.user_id)).await?;
+
+    tracing::info!("[BOT_TEXTS] '{}' ({}) updated by admin user {}", key, locale, auth_user.user_id);
+
+    Ok(Json(bot_text_response(&locale, &key)))
+}
+
+/// DELETE /api/admin/bot-texts/:locale/:key - Revert bot text to default (admin only)
+pub async fn reset_bot_text(
+    Extension(auth_user): Extension<AuthUser>,
+    Path((locale, key)): Path<(String, String)>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<BotTextResponse>> {
+    validate_locale(&locale)?;
+    validate_key(&key)?;
+
+    bot_texts::delete_override(&storehaus, &locale, &key).await?;
+
+    tracing::info!("[BOT_TEXTS] '{}' ({}) reset by admin user {}", key, locale, auth_user.user_id);
+
+    Ok(Json(bot_text_response(&locale, &key)))
+}
+
+/// POST /api/admin/bot-texts/preview - Render bot text with variables (admin only)
+pub async fn preview_bot_text(
+    Extension(_auth_user): Extension<AuthUser>,
+    Json(req): Json<PreviewBotTextRequest>,
+) -> ApiResult<Json<PreviewBotTextResponse>> {
+    validate_locale(&req.locale)?;
+
+    let template = match (req.template, req.key) {
+        (Some(template), _) => template,
+        (None, Some(key)) => {
+            validate_key(&key)?;
+            bot_text_response(&req.locale, &key).value
+        }
+        (None, None) => {
+            return Err(AppError::BadRequest("Either 'key' or 'template' is required".to_string()));
+        }
+    };
+
+    let vars: HashMap<&str, &str> = req
+        .variables
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+
+    let rendered = l10n::format_message(&template, &vars);
+
+    Ok(Json(PreviewBotTextResponse { template, rendered }))
+}
@@ -2,14 +2,32 @@ use axum::{extract::{Path, Query, State}, Extension, Json};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use storehaus::prelude::*;
-use tracing::{info, warn, error};
+use tracing::{info, warn};
 use uuid::Uuid;
 
-use crate::api::middleware::AuthUser;
+use crate::api::middleware::{authorize_conversation, AuthUser};
 use crate::errors::{ApiResult, AppError};
-use crate::models::{Conversation, ConversationStatus, TelegramUser, User};
+use crate::models::{
+    Conversation, ConversationAssignment, ConversationMerge, ConversationPriority, ConversationStatus, Tag,
+    TelegramUser, User,
+};
+use crate::services::access::{self, Access};
+use crate::services::conversation_list::{self, Cursor, ListFilter, ListSort, PageStart};
+use crate::services::queue::{self, QueueSnapshot};
+use crate::services::read_state::{self, InboxBadge};
+use crate::services::presence::{self, ConversationPresence, PresenceState};
+use crate::services::trash::{self, DeletedConversation, PurgeSummary};
+use crate::services::dispositions::{self, WrapUp};
+use crate::services::settings::wrap_up_settings;
+use crate::services::{
+    assignments, audit, conversations, custom_fields, customer_notifications, customer_profiles, priority, routing,
+    snooze, tags,
+};
+use crate::telegram::BotManager;
 use crate::websocket::{WebSocketEvent, WebSocketManager};
 
 /// Conversation list query parameters
@@ -18,8 +36,24 @@ pub struct ConversationListQuery {
     pub status: Option<String>,
     pub user_id: Option<Uuid>,
     pub search: Option<String>,
+    pub source: Option<String>,
+    /// Comma-separated tag IDs; matches conversations with any of them
+    pub tags: Option<String>,
+    /// "low", "normal", "high" or "urgent"
+    pub priority: Option<String>,
+    pub category: Option<String>,
+    /// "priority" (default: highest priority, then longest waiting), "last_message"
+    /// (default with `cursor`) or "time_to_breach" (nearest SLA deadline first)
+    pub sort: Option<String>,
+    /// Include snoozed conversations when no status is given (default: false)
+    pub include_snoozed: Option<bool>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// `next_cursor` of the previous page; takes precedence over `offset`
+    pub cursor: Option<String>,
+    /// Custom field key; with `custom_value`, matches customers with that value
+    pub custom_field: Option<String>,
+    pub custom_value: Option<String>,
 }
 
 /// Conversation with telegram user info
@@ -29,32 +63,122 @@ pub struct ConversationResponse {
     pub telegram_user: TelegramUser,
     pub user_id: Option<Uuid>,
     pub status: String,
+    pub priority: ConversationPriority,
+    pub category: Option<String>,
     pub last_message_at: Option<DateTime<Utc>>,
-    pub unread_count: i32,
+    /// Unread customer messages for the requesting operator
+    pub unread_count: i64,
+    pub source: Option<String>,
+    pub tags: Vec<Tag>,
+    pub sla_policy_id: Option<Uuid>,
+    pub first_response_due_at: Option<DateTime<Utc>>,
+    pub next_response_due_at: Option<DateTime<Utc>>,
+    pub resolution_due_at: Option<DateTime<Utc>>,
+    /// Earliest running SLA deadline
+    pub sla_due_at: Option<DateTime<Utc>>,
+    pub closed_reason: Option<String>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub closed_by: Option<Uuid>,
+    pub disposition: Option<String>,
+    pub resolution_summary: Option<String>,
+    pub previous_conversation_id: Option<Uuid>,
+    pub snoozed_until: Option<DateTime<Utc>>,
+    pub snoozed_by: Option<Uuid>,
     pub created_at: DateTime<Utc>,
 }
 
+impl ConversationResponse {
+    /// Build response from a conversation and its telegram user. The unread
+    /// count is per operator; use `for_user` or `with_unread_count` to set it.
+    pub fn new(conv: Conversation, telegram_user: TelegramUser) -> Self {
+        let sla_due_at = conv.sla_due_at();
+
+        Self {
+            id: conv.id,
+            telegram_user,
+            user_id: conv.user_id,
+            status: conv.status.to_string(),
+            priority: conv.priority,
+            category: conv.category,
+            last_message_at: conv.last_message_at,
+            unread_count: 0,
+            source: conv.source,
+            tags: Vec::new(),
+            sla_policy_id: conv.sla_policy_id,
+            first_response_due_at: conv.first_response_due_at,
+            next_response_due_at: conv.next_response_due_at,
+            resolution_due_at: conv.resolution_due_at,
+            sla_due_at,
+            closed_reason: conv.closed_reason,
+            closed_at: conv.closed_at,
+            closed_by: conv.closed_by,
+            disposition: conv.disposition,
+            resolution_summary: conv.resolution_summary,
+            previous_conversation_id: conv.previous_conversation_id,
+            snoozed_until: conv.snoozed_until,
+            snoozed_by: conv.snoozed_by,
+            created_at: conv.__created_at__,
+        }
+    }
+
+    /// Attach the conversation's tags
+    pub fn with_tags(mut self, tags: Vec<Tag>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Report the requesting operator's unread count
+    pub fn with_unread_count(mut self, unread_count: i64) -> Self {
+        self.unread_count = unread_count;
+        self
+    }
+
+    /// Build response with tags and `user_id`'s unread count, like `get_conversation`
+    pub async fn for_user(
+        storehaus: &StoreHaus,
+        user_id: Uuid,
+        conv: Conversation,
+        telegram_user: TelegramUser,
+    ) -> ApiResult<Self> {
+        let unread_count = read_state::unread_count(storehaus, user_id, conv.id).await?;
+        Ok(Self::with_conversation_tags(storehaus, conv, telegram_user)
+            .await?
+            .with_unread_count(unread_count))
+    }
+
+    /// Build response with the conversation's tags
+    async fn with_conversation_tags(
+        storehaus: &StoreHaus,
+        conv: Conversation,
+        telegram_user: TelegramUser,
+    ) -> ApiResult<Self> {
+        let tags = tags::conversation_tags(storehaus, &[conv.id])
+            .await?
+            .remove(&conv.id)
+            .unwrap_or_default();
+        Ok(Self::new(conv, telegram_user).with_tags(tags))
+    }
+}
+
 /// Response for conversation list
 #[derive(Debug, Serialize)]
 pub struct ConversationListResponse {
     pub conversations: Vec<ConversationResponse>,
     pub total: usize,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    pub next_cursor: Option<String>,
 }
 
+/// Page size of the conversation list
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 200;
+
 /// GET /api/conversations
 pub async fn get_conversations(
     Extension(auth_user): Extension<AuthUser>,
     Query(query): Query<ConversationListQuery>,
     State(storehaus): State<Arc<StoreHaus>>,
 ) -> ApiResult<Json<ConversationListResponse>> {
-    let conversation_store = storehaus
-        .get_store::<GenericStore<Conversation>>("conversations")
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    let telegram_user_store = storehaus
-        .get_store::<GenericStore<TelegramUser>>("telegram_users")
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-
     let system_user_store = storehaus
         .get_store::<GenericStore<User>>("users")
         .map_err(|e| AppError::Internal(e.to_string()))?;
@@ -66,18 +190,24 @@ pub async fn get_conversations(
         .map_err(|_| AppError::NotFound("User not found".to_string()))?
         .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
-    // Build query
-    let mut query_builder = QueryBuilder::new()
-        .order_by("last_message_at", SortOrder::Desc);
-
     // Handle status filter:
     // - If status is explicitly provided, filter by that status
-    // - If status is NOT provided, exclude closed conversations by default (show waiting + active)
-    if let Some(status) = query.status {
-        query_builder = query_builder.filter(QueryFilter::eq("status", json!(status)));
-    } else {
-        // When no status filter is provided, exclude closed conversations
-        query_builder = query_builder.filter(QueryFilter::ne("status", json!(ConversationStatus::Closed.as_str())));
+    // - If status is NOT provided, exclude closed conversations by default (show waiting + active),
+    //   and snoozed ones unless include_snoozed is set
+    let mut filter = ListFilter {
+        status: query.status,
+        include_snoozed: query.include_snoozed.unwrap_or(false),
+        source: query.source.filter(|s| !s.is_empty()),
+        search: query.search.filter(|s| !s.trim().is_empty()),
+        category: query.category.filter(|c| !c.is_empty()),
+        ..Default::default()
+    };
+
+    if let Some(value) = query.priority.as_deref().filter(|p| !p.is_empty()) {
+        let priority = ConversationPriority::parse(value).ok_or_else(|| {
+            AppError::BadRequest("Invalid priority. Must be 'low', 'normal', 'high' or 'urgent'".to_string())
+        })?;
+        filter.priority = Some(priority);
     }
 
     // Apply user_id filter based on permissions:
@@ -85,88 +215,132 @@ pub async fn get_conversations(
     // - If user_id is NOT provided and user is NOT admin, filter by current user's ID
     // - If user_id is NOT provided and user IS admin, show all conversations
     if let Some(user_id) = query.user_id {
-        query_builder = query_builder.filter(QueryFilter::eq("user_id", json!(user_id)));
-    } else if !current_user.is_admin {
+        // Non-admins can list their own and their teammates' conversations
+        if !current_user.has_admin_access()
+            && !access::teammate_ids(&storehaus, &current_user).await?.contains(&user_id)
+        {
+            return Err(AppError::Forbidden("You cannot view this operator's conversations".to_string()));
+        }
+        filter.user_id = Some(user_id);
+    } else if !current_user.has_admin_access() {
         // Non-admin users can only see their own conversations
-        query_builder = query_builder.filter(QueryFilter::eq("user_id", json!(auth_user.user_id)));
+        filter.user_id = Some(auth_user.user_id);
     }
     // Admin users with no user_id filter see ALL conversations
 
-    // Don't apply limit/offset when searching, as we need to filter results after joining with users
-    if query.search.is_none() {
-        if let Some(limit) = query.limit {
-            query_builder = query_builder.limit(limit);
-        }
+    // Filter by tags (any of the given tags)
+    if let Some(value) = query.tags.as_deref().filter(|t| !t.trim().is_empty()) {
+        let tag_ids = tags::parse_tag_ids(value)
+            .ok_or_else(|| AppError::BadRequest("Invalid tag ID in tags filter".to_string()))?;
+        filter.tag_ids = Some(tag_ids);
+    }
 
-        if let Some(offset) = query.offset {
-            query_builder = query_builder.offset(offset);
+    // Filter by a custom field value
+    match (query.custom_field.as_deref(), query.custom_value.as_deref()) {
+        (Some(key), Some(value)) => {
+            let field = custom_fields::find_by_key(&storehaus, key)
+                .await?
+                .ok_or_else(|| AppError::BadRequest(format!("Unknown custom field '{}'", key)))?;
+            let value = custom_fields::normalize(&field, &json!(value)).map_err(AppError::Validation)?;
+            filter.custom_field = Some((field.id, value));
+        }
+        (None, None) => {}
+        _ => {
+            return Err(AppError::BadRequest(
+                "'custom_field' and 'custom_value' must be given together".to_string(),
+            ));
         }
     }
 
-    // Get conversations
-    let conversations = conversation_store
-        .find(query_builder)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
-
-    // Get user info for each conversation
-    let mut results = Vec::new();
-    for conv in conversations {
-        let telegram_user = telegram_user_store
-            .get_by_id(&conv.telegram_user_id)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?
-            .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
-
-        // Apply search filter if provided
-        if let Some(ref search_query) = query.search {
-            let search_lower = search_query.to_lowercase();
-            let matches = telegram_user.first_name.to_lowercase().contains(&search_lower)
-                || telegram_user.last_name.as_ref().map(|l| l.to_lowercase().contains(&search_lower)).unwrap_or(false)
-                || telegram_user.username.as_ref().map(|u| u.to_lowercase().contains(&search_lower)).unwrap_or(false);
+    // Cursors page through the last message order, so it is the default for them
+    let sort = match query.sort.as_deref() {
+        None if query.cursor.is_some() => ListSort::LastMessage,
+        None => ListSort::Priority,
+        Some(value) => ListSort::parse(value).ok_or_else(|| {
+            AppError::BadRequest(
+                "Invalid sort. Must be 'priority', 'last_message' or 'time_to_breach'".to_string(),
+            )
+        })?,
+    };
 
-            if !matches {
-                continue; // Skip this conversation if it doesn't match search
+    let start = match query.cursor.as_deref() {
+        Some(value) => {
+            let cursor = Cursor::parse(value).ok_or_else(|| AppError::BadRequest("Invalid cursor".to_string()))?;
+            if sort != ListSort::LastMessage {
+                return Err(AppError::BadRequest(
+                    "Cursor pagination is only supported with the 'last_message' sort".to_string(),
+                ));
             }
+            PageStart::After(cursor)
         }
+        None => PageStart::Offset(query.offset.unwrap_or(0)),
+    };
 
-        results.push(ConversationResponse {
-            id: conv.id,
-            telegram_user,
-            user_id: conv.user_id,
-            status: conv.status.to_string(),
-            last_message_at: conv.last_message_at,
-            unread_count: conv.unread_count,
-            created_at: conv.__created_at__,
-        });
+    let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT);
+    let page = conversation_list::page(&storehaus, &filter, sort, start, limit).await?;
+
+    // Load the page's conversations and customers, then restore the page order
+    let mut results = Vec::with_capacity(page.ids.len());
+    if !page.ids.is_empty() {
+        let mut conversations: HashMap<Uuid, Conversation> = sqlx::query_as::<_, Conversation>(
+            "SELECT * FROM conversations WHERE id = ANY($1) AND __deleted_at__ IS NULL",
+        )
+        .bind(&page.ids)
+        .fetch_all(storehaus.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .into_iter()
+        .map(|c| (c.id, c))
+        .collect();
+
+        let telegram_user_ids: Vec<i64> = conversations
+            .values()
+            .map(|c| c.telegram_user_id)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let telegram_users: HashMap<i64, TelegramUser> = sqlx::query_as::<_, TelegramUser>(
+            "SELECT * FROM telegram_users WHERE id = ANY($1) AND __deleted_at__ IS NULL",
+        )
+        .bind(&telegram_user_ids)
+        .fetch_all(storehaus.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .into_iter()
+        .map(|u| (u.id, u))
+        .collect();
+
+        for id in &page.ids {
+            let Some(conv) = conversations.remove(id) else {
+                continue;
+            };
+            let telegram_user = telegram_users
+                .get(&conv.telegram_user_id)
+                .cloned()
+                .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+            results.push(ConversationResponse::new(conv, telegram_user));
+        }
     }
 
-    // Store total before applying limit/offset
-    let total = results.len();
-
-    // Apply limit/offset after filtering when search is present
-    if query.search.is_some() {
-        let offset = query.offset.unwrap_or(0) as usize;
-        let limit = query.limit.unwrap_or(20) as usize;
-
-        let end = (offset + limit).min(total);
-
-        results = if offset < total {
-            results[offset..end].to_vec()
-        } else {
-            vec![]
-        };
+    // Attach tags and the caller's unread counts to the returned page
+    let conversation_ids: Vec<Uuid> = results.iter().map(|c| c.id).collect();
+    let mut tags_by_conversation = tags::conversation_tags(&storehaus, &conversation_ids).await?;
+    let unread = read_state::unread_counts(&storehaus, auth_user.user_id, &conversation_ids).await?;
+    for result in &mut results {
+        result.tags = tags_by_conversation.remove(&result.id).unwrap_or_default();
+        result.unread_count = unread.get(&result.id).copied().unwrap_or(0);
     }
 
     Ok(Json(ConversationListResponse {
         conversations: results,
-        total,
+        total: page.total as usize,
+        next_cursor: page.next_cursor.map(|c| c.encode()),
     }))
 }
 
 /// GET /api/conversations/:id
 pub async fn get_conversation(
-    Extension(_auth_user): Extension<AuthUser>,
+    Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
     State(storehaus): State<Arc<StoreHaus>>,
 ) -> ApiResult<Json<ConversationResponse>> {
@@ -184,21 +358,26 @@ pub async fn get_conversation(
         .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
         .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
 
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Read).await?;
+
     let telegram_user = telegram_user_store
         .get_by_id(&conv.telegram_user_id)
         .await
         .map_err(|e| AppError::Database(e.to_string()))?
         .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
 
-    Ok(Json(ConversationResponse {
-        id: conv.id,
-        telegram_user,
-        user_id: conv.user_id,
-        status: conv.status.to_string(),
-        last_message_at: conv.last_message_at,
-        unread_count: conv.unread_count,
-        created_at: conv.__created_at__,
-    }))
+    let tags = tags::conversation_tags(&storehaus, &[conv.id])
+        .await?
+        .remove(&conv.id)
+        .unwrap_or_default();
+
+    let unread_count = read_state::unread_count(&storehaus, auth_user.user_id, conv.id).await?;
+
+    Ok(Json(
+        ConversationResponse::new(conv, telegram_user)
+            .with_tags(tags)
+            .with_unread_count(unread_count),
+    ))
 }
 
 /// PATCH /api/conversations/:id/assign
@@ -212,6 +391,7 @@ pub async fn assign_conversation(
     Path(id): Path<Uuid>,
     State(storehaus): State<Arc<StoreHaus>>,
     State(ws_manager): State<Arc<WebSocketManager>>,
+    State(bot_manager): State<Arc<BotManager>>,
     Json(req): Json<AssignRequest>,
 ) -> ApiResult<Json<ConversationResponse>> {
     let conversation_store = storehaus
@@ -229,9 +409,16 @@ pub async fn assign_conversation(
         .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
         .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
 
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Write).await?;
+
+    let previous_user_id = conv.user_id;
+    let assignee_changed = previous_user_id != Some(req.user_id);
+
     // Update conversation
     conv.user_id = Some(req.user_id);
     conv.status = ConversationStatus::Active;
+    conv.snoozed_until = None;
+    conv.snoozed_by = None;
 
     let conv = conversation_store
         .update(&id, conv, Some(vec!["assigned".to_string()]))
@@ -255,23 +442,47 @@ pub async fn assign_conversation(
         warn!("Failed to broadcast ConversationAssigned event: {}", e);
     }
 
-    Ok(Json(ConversationResponse {
-        id: conv.id,
-        telegram_user,
-        user_id: conv.user_id,
-        status: conv.status.to_string(),
-        last_message_at: conv.last_message_at,
-        unread_count: conv.unread_count,
-        created_at: conv.__created_at__,
-    }))
+    if assignee_changed {
+        if let Err(e) = assignments::record_assignment(
+            &storehaus,
+            conv.id,
+            previous_user_id,
+            Some(req.user_id),
+            Some(auth_user.user_id),
+            None,
+        )
+        .await
+        {
+            warn!("Failed to record assignment of conversation {}: {}", conv.id, e);
+        }
+    }
+
+    // The conversation left the queue or the previous operator has a free slot
+    routing::spawn_drain_queue(storehaus.clone(), ws_manager.clone(), bot_manager.clone());
+
+    // Let the customer know an operator joined
+    if assignee_changed {
+        spawn_operator_assigned_notification(storehaus.clone(), bot_manager, conv.clone(), req.user_id);
+    }
+
+    Ok(Json(ConversationResponse::for_user(&storehaus, auth_user.user_id, conv, telegram_user).await?))
 }
 
-/// PATCH /api/conversations/:id/close
-pub async fn close_conversation(
-    Extension(_auth_user): Extension<AuthUser>,
+/// POST /api/conversations/:id/transfer
+#[derive(Debug, Deserialize)]
+pub struct TransferRequest {
+    pub user_id: Uuid,
+    /// Handoff note for the receiving operator
+    pub note: Option<String>,
+}
+
+pub async fn transfer_conversation(
+    Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
     State(storehaus): State<Arc<StoreHaus>>,
     State(ws_manager): State<Arc<WebSocketManager>>,
+    State(bot_manager): State<Arc<BotManager>>,
+    Json(req): Json<TransferRequest>,
 ) -> ApiResult<Json<ConversationResponse>> {
     let conversation_store = storehaus
         .get_store::<GenericStore<Conversation>>("conversations")
@@ -281,60 +492,154 @@ pub async fn close_conversation(
         .get_store::<GenericStore<TelegramUser>>("telegram_users")
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Get current conversation
+    let system_user_store = storehaus
+        .get_store::<GenericStore<User>>("users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
     let mut conv = conversation_store
         .get_by_id(&id)
         .await
         .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
         .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
 
-    // Update status
-    conv.status = ConversationStatus::Closed;
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Write).await?;
+
+    if conv.is_closed() {
+        return Err(AppError::BadRequest("Cannot transfer a closed conversation".to_string()));
+    }
+
+    if conv.user_id == Some(req.user_id) {
+        return Err(AppError::BadRequest("Conversation is already assigned to this operator".to_string()));
+    }
+
+    let target = system_user_store
+        .get_by_id(&req.user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    if !target.is_active || !(target.is_operator || target.is_admin) {
+        return Err(AppError::BadRequest("Target user is not an active operator".to_string()));
+    }
+
+    let note = req.note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    let previous_user_id = conv.user_id;
+
+    conv.user_id = Some(req.user_id);
+    conv.status = ConversationStatus::Active;
+    conv.snoozed_until = None;
+    conv.snoozed_by = None;
 
     let conv = conversation_store
-        .update(&id, conv, Some(vec!["closed".to_string()]))
+        .update(&id, conv, Some(vec!["transferred".to_string()]))
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
 
+    assignments::record_assignment(
+        &storehaus,
+        conv.id,
+        previous_user_id,
+        Some(req.user_id),
+        Some(auth_user.user_id),
+        note.clone(),
+    )
+    .await?;
+
     let telegram_user = telegram_user_store
         .get_by_id(&conv.telegram_user_id)
         .await
         .map_err(|e| AppError::Database(e.to_string()))?
         .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
 
-    // Broadcast ConversationClosed event
-    let ws_event = WebSocketEvent::ConversationClosed {
+    // Notify the receiving and previous operators directly
+    let ws_event = WebSocketEvent::ConversationTransferred {
         conversation_id: conv.id,
+        from_user_id: previous_user_id,
+        to_user_id: req.user_id,
+        transferred_by: auth_user.user_id,
+        transferred_by_name: auth_user.email.clone(),
+        note,
     };
 
-    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
-        warn!("Failed to broadcast ConversationClosed event: {}", e);
+    let recipients = std::iter::once(req.user_id).chain(previous_user_id.filter(|p| *p != auth_user.user_id));
+    for user_id in recipients {
+        if let Err(e) = ws_manager.send_to_user(&user_id, ws_event.clone()).await {
+            warn!("Failed to send ConversationTransferred event to user {}: {}", user_id, e);
+        }
     }
 
-    Ok(Json(ConversationResponse {
-        id: conv.id,
-        telegram_user,
-        user_id: conv.user_id,
-        status: conv.status.to_string(),
-        last_message_at: conv.last_message_at,
-        unread_count: conv.unread_count,
-        created_at: conv.__created_at__,
-    }))
+    // The previous operator has a free slot now
+    if previous_user_id.is_some() {
+        routing::spawn_drain_queue(storehaus.clone(), ws_manager.clone(), bot_manager.clone());
+    }
+
+    spawn_operator_assigned_notification(storehaus.clone(), bot_manager, conv.clone(), req.user_id);
+
+    Ok(Json(ConversationResponse::for_user(&storehaus, auth_user.user_id, conv, telegram_user).await?))
 }
 
-/// PATCH /api/conversations/:id/status
-#[derive(Debug, Deserialize)]
-pub struct UpdateStatusRequest {
-    pub status: String,
+/// GET /api/conversations/queue
+pub async fn get_queue(
+    Extension(auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<QueueSnapshot>> {
+    let user_store = storehaus
+        .get_store::<GenericStore<User>>("users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let current_user = user_store
+        .get_by_id(&auth_user.user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let scope = access::read_scope(&storehaus, &current_user).await?;
+
+    Ok(Json(queue::snapshot(&storehaus, &scope).await?))
 }
 
-pub async fn update_conversation_status(
-    Extension(_auth_user): Extension<AuthUser>,
+/// GET /api/conversations/:id/assignments
+pub async fn get_assignment_history(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Vec<ConversationAssignment>>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let conv = conversation_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Read).await?;
+
+    let history = assignments::history(&storehaus, id).await?;
+
+    Ok(Json(history))
+}
+
+/// Close request; the body may be omitted unless wrap-up is required
+#[derive(Debug, Default, Deserialize)]
+pub struct CloseConversationRequest {
+    /// Disposition code, e.g. "resolved"
+    pub disposition: Option<String>,
+    pub resolution_summary: Option<String>,
+}
+
+/// PATCH /api/conversations/:id/close
+pub async fn close_conversation(
+    Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
     State(storehaus): State<Arc<StoreHaus>>,
     State(ws_manager): State<Arc<WebSocketManager>>,
-    Json(req): Json<UpdateStatusRequest>,
+    State(bot_manager): State<Arc<BotManager>>,
+    req: Option<Json<CloseConversationRequest>>,
 ) -> ApiResult<Json<ConversationResponse>> {
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+
     let conversation_store = storehaus
         .get_store::<GenericStore<Conversation>>("conversations")
         .map_err(|e| AppError::Internal(e.to_string()))?;
@@ -343,30 +648,24 @@ pub async fn update_conversation_status(
         .get_store::<GenericStore<TelegramUser>>("telegram_users")
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Validate and parse status
-    let new_status = match req.status.as_str() {
-        "waiting" => ConversationStatus::Waiting,
-        "active" => ConversationStatus::Active,
-        "closed" => ConversationStatus::Closed,
-        _ => {
-            return Err(AppError::BadRequest(
-                "Invalid status. Must be 'waiting', 'active', or 'closed'".to_string(),
-            ));
-        }
-    };
-
     // Get current conversation
-    let mut conv = conversation_store
+    let conv = conversation_store
         .get_by_id(&id)
         .await
         .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
         .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
 
-    // Update status
-    conv.status = new_status;
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Write).await?;
 
-    let conv = conversation_store
-        .update(&id, conv, Some(vec!["status_updated".to_string()]))
+    let wrap_up = validate_wrap_up(
+        &storehaus,
+        &auth_user,
+        req.disposition.as_deref(),
+        req.resolution_summary.as_deref(),
+    )
+    .await?;
+
+    let conv = conversations::close(&storehaus, &ws_manager, &bot_manager, conv, None, Some(wrap_up))
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -376,39 +675,45 @@ pub async fn update_conversation_status(
         .map_err(|e| AppError::Database(e.to_string()))?
         .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
 
-    // Broadcast status change event
-    let ws_event = if req.status == "closed" {
-        WebSocketEvent::ConversationClosed {
-            conversation_id: conv.id,
-        }
-    } else {
-        WebSocketEvent::ConversationStatusChanged {
-            conversation_id: conv.id,
-            status: req.status.clone(),
-            user_id: conv.user_id,
-        }
-    };
+    Ok(Json(ConversationResponse::for_user(&storehaus, auth_user.user_id, conv, telegram_user).await?))
+}
 
-    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
-        warn!("Failed to broadcast status change event: {}", e);
-    }
+/// Validate an operator's wrap-up against the wrap-up settings and the
+/// active disposition codes
+async fn validate_wrap_up(
+    storehaus: &StoreHaus,
+    auth_user: &AuthUser,
+    disposition: Option<&str>,
+    resolution_summary: Option<&str>,
+) -> ApiResult<WrapUp> {
+    let settings = wrap_up_settings(storehaus).await?;
+    let codes = dispositions::list_codes(storehaus).await?;
+
+    let (disposition, resolution_summary) =
+        dispositions::validate(&settings, &codes, disposition, resolution_summary).map_err(AppError::Validation)?;
+
+    Ok(WrapUp {
+        closed_by: auth_user.user_id,
+        disposition,
+        resolution_summary,
+    })
+}
 
-    Ok(Json(ConversationResponse {
-        id: conv.id,
-        telegram_user,
-        user_id: conv.user_id,
-        status: conv.status.to_string(),
-        last_message_at: conv.last_message_at,
-        unread_count: conv.unread_count,
-        created_at: conv.__created_at__,
-    }))
+/// Merge request
+#[derive(Debug, Deserialize)]
+pub struct MergeConversationRequest {
+    /// Conversation folded into the one in the path
+    pub source_conversation_id: Uuid,
 }
 
-/// PATCH /api/conversations/:id/mark-read
-pub async fn mark_conversation_read(
-    Extension(_auth_user): Extension<AuthUser>,
+/// POST /api/conversations/:id/merge
+pub async fn merge_conversation(
+    Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
     State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    State(bot_manager): State<Arc<BotManager>>,
+    Json(req): Json<MergeConversationRequest>,
 ) -> ApiResult<Json<ConversationResponse>> {
     let conversation_store = storehaus
         .get_store::<GenericStore<Conversation>>("conversations")
@@ -418,72 +723,661 @@ pub async fn mark_conversation_read(
         .get_store::<GenericStore<TelegramUser>>("telegram_users")
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Get current conversation
-    let mut conv = conversation_store
+    if req.source_conversation_id == id {
+        return Err(AppError::BadRequest("Cannot merge a conversation into itself".to_string()));
+    }
+
+    let target = conversation_store
         .get_by_id(&id)
         .await
-        .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
+        .map_err(|e| AppError::Database(e.to_string()))?
         .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
 
-    // Reset unread count
-    conv.unread_count = 0;
+    authorize_conversation(&storehaus, &auth_user, &target, Access::Write).await?;
 
-    let conv = conversation_store
-        .update(&id, conv, None)
+    let source = conversation_store
+        .get_by_id(&req.source_conversation_id)
         .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Source conversation not found".to_string()))?;
+
+    authorize_conversation(&storehaus, &auth_user, &source, Access::Write).await?;
+
+    if target.is_closed() {
+        return Err(AppError::BadRequest("Cannot merge into a closed conversation".to_string()));
+    }
+    if source.closed_reason.as_deref() == Some(Conversation::MERGED_REASON) {
+        return Err(AppError::BadRequest("Source conversation was already merged".to_string()));
+    }
 
     let telegram_user = telegram_user_store
-        .get_by_id(&conv.telegram_user_id)
+        .get_by_id(&target.telegram_user_id)
         .await
         .map_err(|e| AppError::Database(e.to_string()))?
         .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
 
-    Ok(Json(ConversationResponse {
-        id: conv.id,
-        telegram_user,
-        user_id: conv.user_id,
-        status: conv.status.to_string(),
-        last_message_at: conv.last_message_at,
-        unread_count: conv.unread_count,
-        created_at: conv.__created_at__,
-    }))
+    let source_telegram_user = telegram_user_store
+        .get_by_id(&source.telegram_user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+
+    if !customer_profiles::same_customer(&telegram_user, &source_telegram_user) {
+        return Err(AppError::BadRequest(
+            "Conversations belong to different customers; link their Telegram accounts first".to_string(),
+        ));
+    }
+
+    // The source's operator loses a conversation
+    let frees_slot = !source.is_closed() && source.user_id.is_some();
+
+    let (target, _) = conversations::merge(&storehaus, &ws_manager, source, target, Some(auth_user.user_id))
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    if frees_slot {
+        routing::spawn_drain_queue(storehaus.clone(), ws_manager.clone(), bot_manager);
+    }
+
+    Ok(Json(ConversationResponse::for_user(&storehaus, auth_user.user_id, target, telegram_user).await?))
 }
 
-/// Delete conversation
-pub async fn delete_conversation(
-    Extension(_auth_user): Extension<AuthUser>,
+/// GET /api/conversations/:id/merges
+pub async fn get_merge_history(
+    Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
     State(storehaus): State<Arc<StoreHaus>>,
-) -> ApiResult<Json<serde_json::Value>> {
-    info!("DELETE /conversations/{} called", id);
-
+) -> ApiResult<Json<Vec<ConversationMerge>>> {
     let conversation_store = storehaus
         .get_store::<GenericStore<Conversation>>("conversations")
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Check if conversation exists
+    let merge_store = storehaus
+        .get_store::<GenericStore<ConversationMerge>>("conversation_merges")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
     let conv = conversation_store
         .get_by_id(&id)
         .await
         .map_err(|e| AppError::Database(e.to_string()))?
         .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
 
-    info!("Found conversation to delete: {:?}", conv.id);
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Read).await?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::or(vec![
+            QueryFilter::eq("source_conversation_id", json!(id)),
+            QueryFilter::eq("target_conversation_id", json!(id)),
+        ]))
+        .order_by("__created_at__", SortOrder::Asc);
 
-    // Delete the conversation
-    conversation_store
-        .delete(&id)
+    let merges = merge_store
+        .find(query)
         .await
-        .map_err(|e| {
-            error!("Failed to delete conversation {}: {}", id, e);
-            AppError::Database(e.to_string())
-        })?;
+        .map_err(|e| AppError::Database(e.to_string()))?;
 
-    info!("Successfully deleted conversation {}", id);
+    Ok(Json(merges))
+}
 
-    Ok(Json(json!({
-        "success": true,
-        "message": "Conversation deleted successfully"
-    })))
+/// Snooze request
+#[derive(Debug, Deserialize)]
+pub struct SnoozeRequest {
+    pub until: DateTime<Utc>,
+}
+
+/// PATCH /api/conversations/:id/snooze
+pub async fn snooze_conversation(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    Json(req): Json<SnoozeRequest>,
+) -> ApiResult<Json<ConversationResponse>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let telegram_user_store = storehaus
+        .get_store::<GenericStore<TelegramUser>>("telegram_users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    if req.until <= Utc::now() {
+        return Err(AppError::Validation("Snooze time must be in the future".to_string()));
+    }
+
+    let conv = conversation_store
+        .get_by_id(&id)
+        .await
+        .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Write).await?;
+
+    if conv.is_closed() {
+        return Err(AppError::BadRequest("Closed conversations cannot be snoozed".to_string()));
+    }
+
+    let conv = snooze::snooze(&storehaus, &ws_manager, conv, req.until, auth_user.user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let telegram_user = telegram_user_store
+        .get_by_id(&conv.telegram_user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+
+    Ok(Json(ConversationResponse::for_user(&storehaus, auth_user.user_id, conv, telegram_user).await?))
+}
+
+/// PATCH /api/conversations/:id/wake
+pub async fn wake_conversation(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+) -> ApiResult<Json<ConversationResponse>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let telegram_user_store = storehaus
+        .get_store::<GenericStore<TelegramUser>>("telegram_users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let conv = conversation_store
+        .get_by_id(&id)
+        .await
+        .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Write).await?;
+
+    if !conv.is_snoozed() {
+        return Err(AppError::BadRequest("Conversation is not snoozed".to_string()));
+    }
+
+    // The operator waking it is at the console, no Telegram notification needed
+    let conv = snooze::wake(&storehaus, &ws_manager, None, conv, snooze::WakeReason::Manual)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let telegram_user = telegram_user_store
+        .get_by_id(&conv.telegram_user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+
+    Ok(Json(ConversationResponse::for_user(&storehaus, auth_user.user_id, conv, telegram_user).await?))
+}
+
+/// Priority / category update; an empty category clears it
+#[derive(Debug, Deserialize)]
+pub struct UpdatePriorityRequest {
+    pub priority: Option<ConversationPriority>,
+    pub category: Option<String>,
+}
+
+/// PATCH /api/conversations/:id/priority
+pub async fn update_priority(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    State(bot_manager): State<Arc<BotManager>>,
+    Json(req): Json<UpdatePriorityRequest>,
+) -> ApiResult<Json<ConversationResponse>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let telegram_user_store = storehaus
+        .get_store::<GenericStore<TelegramUser>>("telegram_users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut conv = conversation_store
+        .get_by_id(&id)
+        .await
+        .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Write).await?;
+
+    let category = match req.category.as_deref() {
+        Some(category) if category.trim().chars().count() > priority::MAX_CATEGORY_LENGTH => {
+            return Err(AppError::Validation(format!(
+                "Category must be at most {} characters",
+                priority::MAX_CATEGORY_LENGTH
+            )));
+        }
+        Some(category) => priority::normalize_category(category),
+        None => conv.category.clone(),
+    };
+    let new_priority = req.priority.unwrap_or(conv.priority);
+    let priority_changed = new_priority != conv.priority;
+
+    if priority_changed || category != conv.category {
+        conv.priority = new_priority;
+        conv.category = category;
+        conv = conversation_store
+            .update(&id, conv, None)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        priority::broadcast_change(&ws_manager, &conv, Some(auth_user.user_id)).await;
+
+        // A new priority reorders the queue
+        if priority_changed && conv.is_waiting() && conv.user_id.is_none() {
+            routing::spawn_drain_queue(storehaus.clone(), ws_manager.clone(), bot_manager);
+        }
+    }
+
+    let telegram_user = telegram_user_store
+        .get_by_id(&conv.telegram_user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+
+    Ok(Json(ConversationResponse::for_user(&storehaus, auth_user.user_id, conv, telegram_user).await?))
+}
+
+/// Presence report
+#[derive(Debug, Deserialize)]
+pub struct PresenceRequest {
+    pub state: PresenceState,
+}
+
+/// PUT /api/conversations/:id/presence - Report viewing/typing, repeated while it holds
+pub async fn report_presence(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    Json(req): Json<PresenceRequest>,
+) -> ApiResult<Json<ConversationPresence>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let user_store = storehaus
+        .get_store::<GenericStore<User>>("users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let conv = conversation_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Read).await?;
+
+    let user_name = user_store
+        .get_by_id(&auth_user.user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .map(|user| user.name)
+        .unwrap_or_else(|| auth_user.email.clone());
+
+    let presence = presence::report(&ws_manager, id, auth_user.user_id, &user_name, req.state).await;
+
+    Ok(Json(presence))
+}
+
+/// GET /api/conversations/:id/presence
+pub async fn get_presence(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<ConversationPresence>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let conv = conversation_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Read).await?;
+
+    Ok(Json(presence::presence(id).await))
+}
+
+/// PATCH /api/conversations/:id/status
+#[derive(Debug, Deserialize)]
+pub struct UpdateStatusRequest {
+    pub status: String,
+    /// Wrap-up when closing, as for `close_conversation`
+    pub disposition: Option<String>,
+    pub resolution_summary: Option<String>,
+}
+
+pub async fn update_conversation_status(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    State(bot_manager): State<Arc<BotManager>>,
+    Json(req): Json<UpdateStatusRequest>,
+) -> ApiResult<Json<ConversationResponse>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let telegram_user_store = storehaus
+        .get_store::<GenericStore<TelegramUser>>("telegram_users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    // Validate and parse status
+    let new_status = match req.status.as_str() {
+        "waiting" => ConversationStatus::Waiting,
+        "active" => ConversationStatus::Active,
+        "closed" => ConversationStatus::Closed,
+        _ => {
+            return Err(AppError::BadRequest(
+                "Invalid status. Must be 'waiting', 'active', or 'closed'".to_string(),
+            ));
+        }
+    };
+
+    // Get current conversation
+    let mut conv = conversation_store
+        .get_by_id(&id)
+        .await
+        .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Write).await?;
+
+    if new_status == ConversationStatus::Closed {
+        let wrap_up = validate_wrap_up(
+            &storehaus,
+            &auth_user,
+            req.disposition.as_deref(),
+            req.resolution_summary.as_deref(),
+        )
+        .await?;
+        let conv = conversations::close(&storehaus, &ws_manager, &bot_manager, conv, None, Some(wrap_up))
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let telegram_user = telegram_user_store
+            .get_by_id(&conv.telegram_user_id)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+
+        return Ok(Json(ConversationResponse::for_user(&storehaus, auth_user.user_id, conv, telegram_user).await?));
+    }
+
+    // Update status
+    conv.status = new_status;
+    conv.clear_close();
+    conv.snoozed_until = None;
+    conv.snoozed_by = None;
+
+    let conv = conversation_store
+        .update(&id, conv, Some(vec!["status_updated".to_string()]))
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let telegram_user = telegram_user_store
+        .get_by_id(&conv.telegram_user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+
+    // Broadcast status change event
+    let ws_event = WebSocketEvent::ConversationStatusChanged {
+        conversation_id: conv.id,
+        status: req.status.clone(),
+        user_id: conv.user_id,
+    };
+
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast status change event: {}", e);
+    }
+
+    Ok(Json(ConversationResponse::for_user(&storehaus, auth_user.user_id, conv, telegram_user).await?))
+}
+
+/// Send the operator_assigned message to the customer in the background
+fn spawn_operator_assigned_notification(
+    storehaus: Arc<StoreHaus>,
+    bot_manager: Arc<BotManager>,
+    conv: Conversation,
+    operator_id: Uuid,
+) {
+    tokio::spawn(async move {
+        if let Err(e) = customer_notifications::notify_operator_assigned(&storehaus, &bot_manager, &conv, operator_id).await {
+            warn!("Failed to notify customer about assignment of conversation {}: {}", conv.id, e);
+        }
+    });
+}
+
+/// PATCH /api/conversations/:id/mark-read - Move the caller's read cursor to the latest message
+pub async fn mark_conversation_read(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+) -> ApiResult<Json<ConversationResponse>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let telegram_user_store = storehaus
+        .get_store::<GenericStore<TelegramUser>>("telegram_users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    // Get current conversation
+    let conv = conversation_store
+        .get_by_id(&id)
+        .await
+        .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Read).await?;
+
+    if let Some(message) = read_state::mark_conversation_read(&storehaus, auth_user.user_id, id).await? {
+        // Tell the reader's other sessions; read state is per operator
+        let ws_event = WebSocketEvent::MessageRead {
+            message_id: message.id,
+            conversation_id: id,
+            unread_count: 0,
+        };
+
+        if let Err(e) = ws_manager.send_to_user(&auth_user.user_id, ws_event).await {
+            warn!("Failed to send MessageRead event: {}", e);
+        }
+    }
+
+    let telegram_user = telegram_user_store
+        .get_by_id(&conv.telegram_user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+
+    let response = ConversationResponse::with_conversation_tags(&storehaus, conv, telegram_user).await?;
+
+    Ok(Json(response.with_unread_count(0)))
+}
+
+/// Unread count of one conversation
+#[derive(Debug, Serialize)]
+pub struct ConversationUnread {
+    pub conversation_id: Uuid,
+    pub unread_count: i64,
+}
+
+/// Response for the caller's unread state
+#[derive(Debug, Serialize)]
+pub struct UnreadResponse {
+    /// Conversations in the caller's inbox with unread messages
+    pub conversations: Vec<ConversationUnread>,
+    #[serde(flatten)]
+    pub badge: InboxBadge,
+}
+
+/// GET /api/conversations/unread - The caller's unread counts and inbox badge totals
+pub async fn get_unread(
+    Extension(auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<UnreadResponse>> {
+    let (counts, badge) = read_state::inbox_unread(&storehaus, auth_user.user_id).await?;
+
+    let mut conversations: Vec<ConversationUnread> = counts
+        .into_iter()
+        .map(|(conversation_id, unread_count)| ConversationUnread { conversation_id, unread_count })
+        .collect();
+    conversations.sort_by_key(|c| (Reverse(c.unread_count), c.conversation_id));
+
+    Ok(Json(UnreadResponse { conversations, badge }))
+}
+
+/// DELETE /api/conversations/:id - Move the conversation to the trash
+pub async fn delete_conversation(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    State(bot_manager): State<Arc<BotManager>>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let conv = conversation_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(&storehaus, &auth_user, &conv, Access::Delete).await?;
+
+    if !trash::soft_delete(&storehaus, id, auth_user.user_id).await? {
+        return Err(AppError::NotFound("Conversation not found".to_string()));
+    }
+
+    info!("Conversation {} moved to trash by {}", id, auth_user.user_id);
+
+    let details = json!({ "telegram_user_id": conv.telegram_user_id, "status": conv.status.as_str() });
+    let recorded =
+        audit::record_conversation(&storehaus, audit::CONVERSATION_DELETED, id, Some(auth_user.user_id), Some(details)).await;
+    if let Err(e) = recorded {
+        warn!("Failed to record deletion of conversation {}: {}", id, e);
+    }
+
+    let ws_event = WebSocketEvent::ConversationDeleted {
+        conversation_id: id,
+        deleted_by: auth_user.user_id,
+    };
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast ConversationDeleted event: {}", e);
+    }
+
+    // The operator has a free slot now
+    if conv.user_id.is_some() && !conv.is_closed() {
+        routing::spawn_drain_queue(storehaus.clone(), ws_manager.clone(), bot_manager);
+    }
+
+    Ok(Json(json!({
+        "success": true,
+        "message": "Conversation moved to trash"
+    })))
+}
+
+/// Trash list query
+#[derive(Debug, Deserialize)]
+pub struct TrashQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Trash list response
+#[derive(Debug, Serialize)]
+pub struct TrashResponse {
+    pub conversations: Vec<DeletedConversation>,
+    pub total: i64,
+}
+
+/// GET /api/admin/conversations/trash - List deleted conversations (admin only)
+pub async fn get_trash(
+    Extension(_auth_user): Extension<AuthUser>,
+    Query(query): Query<TrashQuery>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<TrashResponse>> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let (conversations, total) = trash::list(&storehaus, limit, offset).await?;
+
+    Ok(Json(TrashResponse { conversations, total }))
+}
+
+/// POST /api/admin/conversations/:id/restore - Restore a deleted conversation (admin only)
+pub async fn restore_conversation(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+) -> ApiResult<Json<ConversationResponse>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let telegram_user_store = storehaus
+        .get_store::<GenericStore<TelegramUser>>("telegram_users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    if !trash::restore(&storehaus, id).await? {
+        return Err(AppError::NotFound("Conversation is not in the trash".to_string()));
+    }
+
+    let recorded = audit::record_conversation(&storehaus, audit::CONVERSATION_RESTORED, id, Some(auth_user.user_id), None).await;
+    if let Err(e) = recorded {
+        warn!("Failed to record restore of conversation {}: {}", id, e);
+    }
+
+    let conv = conversation_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    let telegram_user = telegram_user_store
+        .get_by_id(&conv.telegram_user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+
+    let ws_event = WebSocketEvent::ConversationRestored { conversation_id: id };
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast ConversationRestored event: {}", e);
+    }
+
+    Ok(Json(ConversationResponse::for_user(&storehaus, auth_user.user_id, conv, telegram_user).await?))
+}
+
+/// DELETE /api/admin/conversations/:id/purge - Irreversibly remove a deleted
+/// conversation and its history (admin only)
+pub async fn purge_conversation(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<PurgeSummary>> {
+    // Only trashed conversations can be purged
+    trash::find(&storehaus, id)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Move the conversation to the trash before purging it".to_string()))?;
+
+    // Writes the audit entry in the purge transaction
+    let summary = trash::purge(&storehaus, id, auth_user.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Conversation is not in the trash".to_string()))?;
+
+    info!("Conversation {} purged by {}: {:?}", id, auth_user.user_id, summary);
+
+    Ok(Json(summary))
 }
@@ -0,0 +1,206 @@
+use axum::{extract::{Path, State}, Extension, Json};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::api::middleware::AuthUser;
+use crate::errors::{ApiResult, AppError};
+use crate::models::{CustomField, CustomFieldType, TelegramUser};
+use crate::services::custom_fields;
+
+/// Create custom field request
+#[derive(Debug, Deserialize)]
+pub struct CreateCustomFieldRequest {
+    pub key: String,
+    pub label: String,
+    pub field_type: CustomFieldType,
+    /// Allowed values (enum fields only)
+    pub options: Option<Vec<String>>,
+    pub required: Option<bool>,
+    pub position: Option<i32>,
+}
+
+/// Update custom field request. The key and type are fixed once created.
+#[derive(Debug, Deserialize)]
+pub struct UpdateCustomFieldRequest {
+    pub label: Option<String>,
+    pub options: Option<Vec<String>>,
+    pub required: Option<bool>,
+    pub position: Option<i32>,
+}
+
+/// Set custom field values request: field key to value, `null` clears
+#[derive(Debug, Deserialize)]
+pub struct SetCustomFieldValuesRequest {
+    pub values: HashMap<String, Value>,
+}
+
+/// GET /api/custom-fields
+pub async fn get_custom_fields(
+    Extension(_auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Vec<CustomField>>> {
+    Ok(Json(custom_fields::list_fields(&storehaus).await?))
+}
+
+/// POST /api/admin/custom-fields
+pub async fn create_custom_field(
+    Extension(_auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    Json(req): Json<CreateCustomFieldRequest>,
+) -> ApiResult<Json<CustomField>> {
+    let field_store = storehaus
+        .get_store::<GenericStore<CustomField>>("custom_fields")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let key = req.key.trim().to_string();
+    if !custom_fields::is_valid_key(&key) {
+        return Err(AppError::Validation(
+            "Field key must start with a lowercase letter and contain only lowercase letters, digits and underscores"
+                .to_string(),
+        ));
+    }
+    if custom_fields::find_by_key(&storehaus, &key).await?.is_some() {
+        return Err(AppError::Conflict("Custom field already exists".to_string()));
+    }
+
+    let options = validate_options(req.field_type, req.options.unwrap_or_default())?;
+
+    let field = CustomField::create(
+        key,
+        validate_label(&req.label)?,
+        req.field_type,
+        options,
+        req.required.unwrap_or(false),
+        req.position.unwrap_or(0),
+    );
+
+    let field = field_store
+        .create(field, None)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(field))
+}
+
+/// PATCH /api/admin/custom-fields/:id
+pub async fn update_custom_field(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    Json(req): Json<UpdateCustomFieldRequest>,
+) -> ApiResult<Json<CustomField>> {
+    let field_store = storehaus
+        .get_store::<GenericStore<CustomField>>("custom_fields")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut field = field_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Custom field not found".to_string()))?;
+
+    if let Some(label) = req.label {
+        field.label = validate_label(&label)?;
+    }
+
+    if let Some(options) = req.options {
+        let options = validate_options(field.field_type, options)?;
+        field.options = (!options.is_empty()).then(|| json!(options).to_string());
+    }
+
+    if let Some(required) = req.required {
+        field.required = required;
+    }
+
+    if let Some(position) = req.position {
+        field.position = position;
+    }
+
+    let field = field_store
+        .update(&id, field, None)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(field))
+}
+
+/// DELETE /api/admin/custom-fields/:id - Delete a field and all its values
+pub async fn delete_custom_field(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Value>> {
+    let field_store = storehaus
+        .get_store::<GenericStore<CustomField>>("custom_fields")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    field_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Custom field not found".to_string()))?;
+
+    custom_fields::remove_field_values(&storehaus, id).await?;
+
+    field_store
+        .delete(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(json!({ "message": "Custom field deleted successfully" })))
+}
+
+/// PATCH /api/telegram-users/:id/custom-fields
+pub async fn set_custom_field_values(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    Json(req): Json<SetCustomFieldValuesRequest>,
+) -> ApiResult<Json<BTreeMap<String, Value>>> {
+    let telegram_user_store = storehaus
+        .get_store::<GenericStore<TelegramUser>>("telegram_users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    telegram_user_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+
+    let fields = custom_fields::list_fields(&storehaus).await?;
+    let changes = custom_fields::validate_changes(&fields, &req.values).map_err(AppError::Validation)?;
+
+    custom_fields::apply_changes(&storehaus, id, changes, auth_user.user_id).await?;
+
+    Ok(Json(custom_fields::values_for(&storehaus, id).await?))
+}
+
+fn validate_label(label: &str) -> ApiResult<String> {
+    let label = label.trim();
+    if label.is_empty() || label.chars().count() > 100 {
+        return Err(AppError::Validation("Field label must be 1-100 characters".to_string()));
+    }
+    Ok(label.to_string())
+}
+
+fn validate_options(field_type: CustomFieldType, options: Vec<String>) -> ApiResult<Vec<String>> {
+    let mut cleaned: Vec<String> = Vec::with_capacity(options.len());
+    for option in options.iter().map(|o| o.trim()).filter(|o| !o.is_empty()) {
+        if !cleaned.iter().any(|c| c.eq_ignore_ascii_case(option)) {
+            cleaned.push(option.to_string());
+        }
+    }
+
+    match field_type {
+        CustomFieldType::Enum if cleaned.is_empty() => {
+            Err(AppError::Validation("Enum fields need at least one option".to_string()))
+        }
+        CustomFieldType::Enum => Ok(cleaned),
+        _ if !cleaned.is_empty() => Err(AppError::Validation("Only enum fields have options".to_string())),
+        _ => Ok(cleaned),
+    }
+}
@@ -0,0 +1,57 @@
+use axum::{extract::State, Extension, Json};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::api::middleware::AuthUser;
+use crate::config::AppConfig;
+use crate::errors::{ApiResult, AppError};
+use crate::telegram::BotManager;
+use crate::utils::deep_link;
+
+/// Deep link request
+#[derive(Debug, Deserialize)]
+pub struct CreateDeepLinkRequest {
+    pub source: String,
+    /// Our own customer ID; requires DEEP_LINK_SECRET
+    pub customer_id: Option<String>,
+}
+
+/// Deep link response
+#[derive(Debug, Serialize)]
+pub struct DeepLinkResponse {
+    pub payload: String,
+    /// Full t.me link (only when the bot is connected)
+    pub url: Option<String>,
+}
+
+/// POST /api/admin/deep-links - Build a (signed) /start payload
+pub async fn create_deep_link(
+    Extension(_auth_user): Extension<AuthUser>,
+    State(config): State<AppConfig>,
+    State(bot_manager): State<Arc<BotManager>>,
+    Json(req): Json<CreateDeepLinkRequest>,
+) -> ApiResult<Json<DeepLinkResponse>> {
+    let source = req.source.trim();
+
+    let payload = match req.customer_id.as_deref().map(str::trim).filter(|id| !id.is_empty()) {
+        Some(customer_id) => {
+            let secret = config.deep_link_secret.as_deref().ok_or_else(|| {
+                AppError::BadRequest("DEEP_LINK_SECRET is not configured".to_string())
+            })?;
+            deep_link::sign_payload(source, customer_id, secret)?
+        }
+        None => {
+            let link = deep_link::parse_payload(source, None)
+                .ok_or_else(|| AppError::Validation("Invalid source".to_string()))?;
+            link.source
+        }
+    };
+
+    let url = bot_manager
+        .health()
+        .await
+        .bot_username
+        .map(|username| format!("https://t.me/{}?start={}", username, payload));
+
+    Ok(Json(DeepLinkResponse { payload, url }))
+}
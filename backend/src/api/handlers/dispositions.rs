@@ -0,0 +1,135 @@
+use axum::{extract::{Path, State}, Extension, Json};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::api::middleware::AuthUser;
+use crate::errors::{ApiResult, AppError};
+use crate::models::DispositionCode;
+use crate::services::dispositions;
+
+/// Create disposition code request
+#[derive(Debug, Deserialize)]
+pub struct CreateDispositionCodeRequest {
+    pub code: String,
+    pub label: String,
+    pub position: Option<i32>,
+}
+
+/// Update disposition code request. The code is fixed once created.
+#[derive(Debug, Deserialize)]
+pub struct UpdateDispositionCodeRequest {
+    pub label: Option<String>,
+    pub position: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+/// GET /api/disposition-codes
+pub async fn get_disposition_codes(
+    Extension(_auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Vec<DispositionCode>>> {
+    Ok(Json(dispositions::list_codes(&storehaus).await?))
+}
+
+/// POST /api/admin/disposition-codes
+pub async fn create_disposition_code(
+    Extension(_auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    Json(req): Json<CreateDispositionCodeRequest>,
+) -> ApiResult<Json<DispositionCode>> {
+    let code_store = storehaus
+        .get_store::<GenericStore<DispositionCode>>("disposition_codes")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let code = req.code.trim().to_string();
+    if !dispositions::is_valid_code(&code) {
+        return Err(AppError::Validation(
+            "Disposition code must use lowercase letters, digits and underscores".to_string(),
+        ));
+    }
+    if dispositions::find_by_code(&storehaus, &code).await?.is_some() {
+        return Err(AppError::Conflict("Disposition code already exists".to_string()));
+    }
+
+    let code = DispositionCode::create(code, validate_label(&req.label)?, req.position.unwrap_or(0));
+
+    let code = code_store
+        .create(code, None)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(code))
+}
+
+/// PATCH /api/admin/disposition-codes/:id
+pub async fn update_disposition_code(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    Json(req): Json<UpdateDispositionCodeRequest>,
+) -> ApiResult<Json<DispositionCode>> {
+    let code_store = storehaus
+        .get_store::<GenericStore<DispositionCode>>("disposition_codes")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut code = code_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Disposition code not found".to_string()))?;
+
+    if let Some(label) = req.label {
+        code.label = validate_label(&label)?;
+    }
+
+    if let Some(position) = req.position {
+        code.position = position;
+    }
+
+    if let Some(is_active) = req.is_active {
+        code.is_active = is_active;
+    }
+
+    let code = code_store
+        .update(&id, code, None)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(code))
+}
+
+/// DELETE /api/admin/disposition-codes/:id
+/// Closed conversations keep the code; it just can no longer be picked
+pub async fn delete_disposition_code(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Value>> {
+    let code_store = storehaus
+        .get_store::<GenericStore<DispositionCode>>("disposition_codes")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    code_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Disposition code not found".to_string()))?;
+
+    code_store
+        .delete(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(json!({ "message": "Disposition code deleted successfully" })))
+}
+
+fn validate_label(label: &str) -> ApiResult<String> {
+    let label = label.trim();
+    if label.is_empty() || label.chars().count() > 100 {
+        return Err(AppError::Validation("Disposition label must be 1-100 characters".to_string()));
+    }
+    Ok(label.to_string())
+}
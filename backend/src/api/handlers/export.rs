@@ -1,19 +1,24 @@
 use axum::{extract::{Path, Query, State}, Extension, response::{IntoResponse, Response}, http::{header, StatusCode}};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use storehaus::prelude::*;
 use uuid::Uuid;
 
-use crate::api::middleware::AuthUser;
+use crate::api::middleware::{authorize_conversation, AuthUser};
 use crate::errors::AppError;
-use crate::models::{Conversation, Message, TelegramUser};
+use crate::models::{Conversation, CustomField, Message, MessageKind, TelegramUser};
+use crate::services::access::Access;
+use crate::services::{custom_fields, dispositions};
 
 /// Export format
 #[derive(Debug, Deserialize)]
 pub struct ExportQuery {
     pub format: Option<String>, // json, csv, txt
+    /// Include internal notes (default: false)
+    pub include_notes: Option<bool>,
 }
 
 /// Export conversation messages
@@ -25,11 +30,13 @@ pub struct ExportMessage {
     pub created_at: String,
     pub media_type: Option<String>,
     pub file_name: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_note: bool,
 }
 
 /// GET /api/conversations/:id/export
 pub async fn export_conversation(
-    Extension(_auth_user): Extension<AuthUser>,
+    Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
     Query(query): Query<ExportQuery>,
     State(storehaus): State<Arc<StoreHaus>>,
@@ -53,6 +60,8 @@ pub async fn export_conversation(
         .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
         .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
 
+    authorize_conversation(&storehaus, &auth_user, &conversation, Access::Read).await?;
+
     // Get user info
     let telegram_user = telegram_user_store
         .get_by_id(&conversation.telegram_user_id)
@@ -61,10 +70,15 @@ pub async fn export_conversation(
         .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
     // Get all messages
-    let query_builder = QueryBuilder::new()
+    let mut query_builder = QueryBuilder::new()
         .filter(QueryFilter::eq("conversation_id", json!(id)))
         .order_by("__created_at__", SortOrder::Asc);
 
+    // Internal notes are only exported when explicitly requested
+    if !query.include_notes.unwrap_or(false) {
+        query_builder = query_builder.filter(QueryFilter::ne("kind", json!(MessageKind::Note.as_str())));
+    }
+
     let messages = message_store
         .find(query_builder)
         .await
@@ -73,6 +87,8 @@ pub async fn export_conversation(
     let export_messages: Vec<ExportMessage> = messages
         .into_iter()
         .map(|msg| ExportMessage {
+            // Evaluated before the fields below move out of `msg`
+            is_note: msg.is_note(),
             id: msg.id,
             from_user: msg.from_user,
             content: msg.content,
@@ -82,27 +98,55 @@ pub async fn export_conversation(
         })
         .collect();
 
+    let custom_fields = custom_fields::field_values(&storehaus, telegram_user.id).await?;
+
+    // Label of the disposition code; the code itself when it was deleted since
+    let disposition = match conversation.disposition.as_deref() {
+        Some(code) => Some(
+            dispositions::find_by_code(&storehaus, code)
+                .await?
+                .map(|c| c.label)
+                .unwrap_or_else(|| code.to_string()),
+        ),
+        None => None,
+    };
+
+    let disposition = disposition.as_deref();
+
     let format = query.format.as_deref().unwrap_or("json");
 
     match format {
-        "json" => export_as_json(&telegram_user, &conversation, &export_messages),
+        "json" => export_as_json(&telegram_user, &custom_fields, &conversation, disposition, &export_messages),
         "csv" => export_as_csv(&telegram_user, &conversation, &export_messages),
-        "txt" => export_as_txt(&telegram_user, &conversation, &export_messages),
+        "txt" => export_as_txt(&telegram_user, &custom_fields, &conversation, disposition, &export_messages),
         _ => Err(AppError::BadRequest("Unsupported format. Use json, csv, or txt".to_string())),
     }
 }
 
 fn export_as_json(
     user: &TelegramUser,
+    custom_fields: &[(CustomField, Value)],
     conversation: &Conversation,
+    disposition_label: Option<&str>,
     messages: &[ExportMessage],
 ) -> Result<Response, AppError> {
+    let custom_fields: BTreeMap<&str, &Value> = custom_fields
+        .iter()
+        .map(|(field, value)| (field.key.as_str(), value))
+        .collect();
+
     let export_data = json!({
         "conversation_id": conversation.id,
+        "status": conversation.status.as_str(),
+        "closed_at": conversation.closed_at.map(|at| at.to_rfc3339()),
+        "disposition": conversation.disposition,
+        "disposition_label": disposition_label,
+        "resolution_summary": conversation.resolution_summary,
         "user": {
             "id": user.id,
             "username": user.username,
             "first_name": user.first_name,
+            "custom_fields": custom_fields,
         },
         "messages": messages,
         "exported_at": Utc::now().to_rfc3339(),
@@ -127,7 +171,9 @@ fn export_as_csv(
     let mut csv = String::from("Timestamp,From,Content,Media Type,File Name\n");
 
     for msg in messages {
-        let from = if msg.from_user {
+        let from = if msg.is_note {
+            "Internal note"
+        } else if msg.from_user {
             "Operator"
         } else {
             user.username.as_deref().unwrap_or("User")
@@ -153,26 +199,45 @@ fn export_as_csv(
 
 fn export_as_txt(
     user: &TelegramUser,
+    custom_fields: &[(CustomField, Value)],
     conversation: &Conversation,
+    disposition_label: Option<&str>,
     messages: &[ExportMessage],
 ) -> Result<Response, AppError> {
     let mut txt = format!(
         "Conversation Export\n\
          ==================\n\
          Conversation ID: {}\n\
-         User: {} ({})\n\
-         Exported: {}\n\
-         \n\
-         Messages:\n\
-         =========\n\n",
+         User: {} ({})\n",
         conversation.id,
         user.first_name,
         user.username.as_deref().unwrap_or("no username"),
-        Utc::now().to_rfc3339()
     );
 
+    for (field, value) in custom_fields {
+        let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+        txt.push_str(&format!("{}: {}\n", field.label, value));
+    }
+
+    if let Some(label) = disposition_label {
+        txt.push_str(&format!("Disposition: {}\n", label));
+    }
+    if let Some(summary) = &conversation.resolution_summary {
+        txt.push_str(&format!("Resolution: {}\n", summary));
+    }
+
+    txt.push_str(&format!(
+        "Exported: {}\n\
+         \n\
+         Messages:\n\
+         =========\n\n",
+        Utc::now().to_rfc3339()
+    ));
+
     for msg in messages {
-        let from = if msg.from_user {
+        let from = if msg.is_note {
+            "Internal note"
+        } else if msg.from_user {
             "Operator"
         } else {
             user.username.as_deref().unwrap_or(&user.first_name)
@@ -2,14 +2,20 @@ use axum::{extract::{Path, Query, State}, Extension, Json};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
 use std::sync::Arc;
 use storehaus::prelude::*;
 use tracing::warn;
 use uuid::Uuid;
 
-use crate::api::middleware::AuthUser;
+use crate::api::middleware::{authorize_conversation, AuthUser};
 use crate::errors::{ApiResult, AppError};
-use crate::models::{Conversation, Message, MessageEdit, TelegramUser};
+use crate::models::{Conversation, Message, MessageEdit, MessageKind, TelegramUser, User};
+use crate::services::access::{self, Access, ReadScope};
+use crate::services::message_history::{self, Anchor, HistoryWindow};
+use crate::services::message_search::{self, MediaFilter, SearchCursor, SearchFilter, SenderSide, Snippet};
+use crate::services::settings::collision_settings;
+use crate::services::{notes, presence, read_state, sla};
 use crate::telegram::{send_message_to_telegram_user, SendMessageResult};
 use crate::websocket::{WebSocketEvent, WebSocketManager};
 
@@ -17,8 +23,16 @@ use crate::websocket::{WebSocketEvent, WebSocketManager};
 #[derive(Debug, Deserialize)]
 pub struct MessageListQuery {
     pub conversation_id: Uuid,
+    /// Include internal notes (default: true)
+    pub include_notes: Option<bool>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// Messages older than this message ID or RFC 3339 timestamp
+    pub before: Option<String>,
+    /// Messages newer than this message ID or RFC 3339 timestamp
+    pub after: Option<String>,
+    /// This message with older and newer messages around it
+    pub around: Option<Uuid>,
 }
 
 /// Message response
@@ -40,23 +54,111 @@ pub struct MessageResponse {
     pub mime_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration: Option<i32>,
+    pub kind: MessageKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
 }
 
+impl From<Message> for MessageResponse {
+    fn from(msg: Message) -> Self {
+        Self {
+            id: msg.id,
+            conversation_id: msg.conversation_id,
+            from_user: msg.from_user,
+            content: msg.content,
+            read: msg.read,
+            telegram_message_id: msg.telegram_message_id,
+            media_type: msg.media_type,
+            media_url: msg.media_url,
+            file_name: msg.file_name,
+            file_size: msg.file_size,
+            mime_type: msg.mime_type,
+            duration: msg.duration,
+            kind: msg.kind,
+            user_id: msg.user_id,
+            created_at: msg.__created_at__,
+        }
+    }
+}
+
+/// Messages per page when paging with a cursor
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+const MAX_HISTORY_LIMIT: i64 = 200;
+
 /// GET /api/messages
 pub async fn get_messages(
-    Extension(_auth_user): Extension<AuthUser>,
+    Extension(auth_user): Extension<AuthUser>,
     Query(query): Query<MessageListQuery>,
     State(storehaus): State<std::sync::Arc<StoreHaus>>,
 ) -> ApiResult<Json<Vec<MessageResponse>>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
     let message_store = storehaus
         .get_store::<GenericStore<Message>>("messages")
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
+    let conversation = conversation_store
+        .get_by_id(&query.conversation_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(&storehaus, &auth_user, &conversation, Access::Read).await?;
+
+    let include_notes = query.include_notes.unwrap_or(true);
+
+    let window = match (query.before.as_deref(), query.after.as_deref(), query.around) {
+        (None, None, None) => None,
+        (Some(before), None, None) => Some(HistoryWindow::Before(parse_anchor(before)?)),
+        (None, Some(after), None) => Some(HistoryWindow::After(parse_anchor(after)?)),
+        (None, None, Some(around)) => Some(HistoryWindow::Around(around)),
+        _ => {
+            return Err(AppError::BadRequest(
+                "Only one of 'before', 'after' and 'around' can be given".to_string(),
+            ));
+        }
+    };
+
+    if let Some(window) = window {
+        let limit = query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT).clamp(1, MAX_HISTORY_LIMIT);
+        let ids = message_history::window(&storehaus, query.conversation_id, window, include_notes, limit)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Message not found in this conversation".to_string()))?;
+
+        if ids.is_empty() {
+            return Ok(Json(Vec::new()));
+        }
+
+        let query_builder = QueryBuilder::new()
+            .filter(QueryFilter::or(ids.iter().map(|id| QueryFilter::eq("id", json!(id))).collect()));
+        let mut messages: HashMap<Uuid, Message> = message_store
+            .find(query_builder)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .into_iter()
+            .map(|m| (m.id, m))
+            .collect();
+
+        let mut results: Vec<MessageResponse> = ids
+            .iter()
+            .filter_map(|id| messages.remove(id))
+            .map(MessageResponse::from)
+            .collect();
+        apply_read_state(&storehaus, auth_user.user_id, query.conversation_id, &mut results).await?;
+        return Ok(Json(results));
+    }
+
     let mut query_builder = QueryBuilder::new()
         .filter(QueryFilter::eq("conversation_id", json!(query.conversation_id)))
         .order_by("__created_at__", SortOrder::Asc);
 
+    if !include_notes {
+        query_builder = query_builder.filter(QueryFilter::ne("kind", json!(MessageKind::Note.as_str())));
+    }
+
     if let Some(limit) = query.limit {
         query_builder = query_builder.limit(limit);
     }
@@ -70,33 +172,37 @@ pub async fn get_messages(
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
 
-    let results = messages
+    let mut results: Vec<MessageResponse> = messages
         .into_iter()
-        .map(|msg| MessageResponse {
-            id: msg.id,
-            conversation_id: msg.conversation_id,
-            from_user: msg.from_user,
-            content: msg.content,
-            read: msg.read,
-            telegram_message_id: msg.telegram_message_id,
-            media_type: msg.media_type,
-            media_url: msg.media_url,
-            file_name: msg.file_name,
-            file_size: msg.file_size,
-            mime_type: msg.mime_type,
-            duration: msg.duration,
-            created_at: msg.__created_at__,
-        })
+        .map(MessageResponse::from)
         .collect();
+    apply_read_state(&storehaus, auth_user.user_id, query.conversation_id, &mut results).await?;
 
     Ok(Json(results))
 }
 
+/// Report customer messages as read or unread for `user_id` according to
+/// their read cursor; operator messages keep their stored flag
+async fn apply_read_state(
+    storehaus: &StoreHaus,
+    user_id: Uuid,
+    conversation_id: Uuid,
+    messages: &mut [MessageResponse],
+) -> ApiResult<()> {
+    let cursor = read_state::cursor(storehaus, user_id, conversation_id).await?;
+    for message in messages.iter_mut().filter(|m| !m.from_user && m.kind == MessageKind::Chat) {
+        message.read = cursor.as_ref().is_some_and(|c| !c.is_before(message.created_at, message.id));
+    }
+    Ok(())
+}
+
 /// Send message request
 #[derive(Debug, Deserialize)]
 pub struct SendMessageRequest {
     pub conversation_id: Uuid,
     pub content: String,
+    /// Reply despite another operator holding the reply lock ("warn" mode)
+    pub force: Option<bool>,
 }
 
 /// POST /api/messages/send
@@ -122,8 +228,20 @@ pub async fn send_message(
         .map_err(|_| AppError::NotFound("Conversation not found".to_string()))?
         .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
 
+    authorize_conversation(&storehaus, &auth_user, &conversation, Access::Write).await?;
+
+    // Another operator may be composing a reply to this conversation
+    let reply_lock_mode = collision_settings(&storehaus).await?.reply_lock_mode;
+    let lock = presence::lock_holder(conversation.id).await;
+    let force = req.force.unwrap_or(false);
+    if presence::reply_collides(reply_lock_mode, &conversation, auth_user.user_id, lock.as_ref(), force) {
+        let holder = lock.map(|l| l.user_name).unwrap_or_default();
+        return Err(AppError::Conflict(format!("{} is already replying to this conversation", holder)));
+    }
+
     // Create message
     let mut message = Message::from_user_message(req.conversation_id, req.content.clone());
+    message.user_id = Some(auth_user.user_id);
 
     // Get bot from bot manager
     let bot = bot_manager.bot().await
@@ -176,13 +294,20 @@ pub async fn send_message(
     // Update conversation
     let conversation_id = conversation.id;
     conversation.last_message_at = Some(Utc::now());
-    conversation.unread_count = 0;
+    sla::on_operator_reply(&mut conversation, Utc::now());
 
     conversation_store
         .update(&conversation_id, conversation, None)
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
 
+    presence::release(conversation_id, auth_user.user_id).await;
+
+    // Replying means the operator has read the conversation
+    if let Err(e) = read_state::mark_read(&storehaus, auth_user.user_id, &message).await {
+        warn!("Failed to move read cursor of user {}: {}", auth_user.user_id, e);
+    }
+
     // Broadcast MessageSent event to all connected users
     let ws_event = WebSocketEvent::MessageSent {
         conversation_id: message.conversation_id,
@@ -202,74 +327,122 @@ pub async fn send_message(
         warn!("Failed to broadcast MessageSent event: {}", e);
     }
 
-    Ok(Json(MessageResponse {
-        id: message.id,
-        conversation_id: message.conversation_id,
-        from_user: message.from_user,
-        content: message.content,
-        read: message.read,
-        telegram_message_id: message.telegram_message_id,
-        media_type: message.media_type,
-        media_url: message.media_url,
-        file_name: message.file_name,
-        file_size: message.file_size,
-        mime_type: message.mime_type,
-        duration: message.duration,
-        created_at: message.__created_at__,
-    }))
+    Ok(Json(MessageResponse::from(message)))
 }
 
-/// PATCH /api/messages/:id/read
-pub async fn mark_as_read(
-    Extension(_auth_user): Extension<AuthUser>,
+/// Create note request
+#[derive(Debug, Deserialize)]
+pub struct CreateNoteRequest {
+    pub content: String,
+}
+
+/// POST /api/conversations/:id/notes
+pub async fn create_note(
+    Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
     State(storehaus): State<Arc<StoreHaus>>,
     State(ws_manager): State<Arc<WebSocketManager>>,
+    State(bot_manager): State<Arc<crate::telegram::BotManager>>,
+    Json(req): Json<CreateNoteRequest>,
 ) -> ApiResult<Json<MessageResponse>> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
     let message_store = storehaus
         .get_store::<GenericStore<Message>>("messages")
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Get message
-    let mut message = message_store
+    let content = req.content.trim();
+    if content.is_empty() {
+        return Err(AppError::Validation("Note cannot be empty".to_string()));
+    }
+
+    let conversation = conversation_store
         .get_by_id(&id)
         .await
-        .map_err(|_| AppError::NotFound("Message not found".to_string()))?
-        .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
 
-    // Update read status
-    message.read = true;
+    // Notes never reach the customer, so whoever can see the conversation can add one
+    authorize_conversation(&storehaus, &auth_user, &conversation, Access::Read).await?;
 
-    let message = message_store
-        .update(&id, message, None)
+    // Notes stay in the timeline only; they are never sent to Telegram and
+    // don't touch last_message_at or unread counters
+    let note = Message::internal_note(id, content.to_string(), auth_user.user_id);
+
+    let note = message_store
+        .create(note, Some(vec!["internal_note".to_string()]))
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
 
-    // Broadcast MessageRead event
-    let ws_event = WebSocketEvent::MessageRead {
-        message_id: message.id,
-        conversation_id: message.conversation_id,
+    let handles = notes::extract_mentions(&note.content);
+    let mentioned: Vec<_> = notes::resolve_mentions(&storehaus, &handles)
+        .await?
+        .into_iter()
+        .filter(|u| u.id != auth_user.user_id)
+        .collect();
+
+    let ws_event = WebSocketEvent::NoteAdded {
+        conversation_id: note.conversation_id,
+        message_id: note.id,
+        content: note.content.clone(),
+        user_id: auth_user.user_id,
+        user_name: auth_user.email.clone(),
+        mentioned_user_ids: mentioned.iter().map(|u| u.id).collect(),
     };
 
     if let Err(e) = ws_manager.broadcast_event(ws_event).await {
-        warn!("Failed to broadcast MessageRead event: {}", e);
+        warn!("Failed to broadcast NoteAdded event: {}", e);
     }
 
-    Ok(Json(MessageResponse {
-        id: message.id,
-        conversation_id: message.conversation_id,
-        from_user: message.from_user,
-        content: message.content,
-        read: message.read,
-        telegram_message_id: message.telegram_message_id,
-        media_type: message.media_type,
-        media_url: message.media_url,
-        file_name: message.file_name,
-        file_size: message.file_size,
-        mime_type: message.mime_type,
-        duration: message.duration,
-        created_at: message.__created_at__,
-    }))
+    if !mentioned.is_empty() {
+        let note = note.clone();
+        let author_name = auth_user.email.clone();
+        tokio::spawn(async move {
+            notes::notify_mentions(&ws_manager, &bot_manager, &note, &author_name, &mentioned).await;
+        });
+    }
+
+    Ok(Json(MessageResponse::from(note)))
+}
+
+/// PATCH /api/messages/:id/read - Move the caller's read cursor up to the message
+pub async fn mark_as_read(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+) -> ApiResult<Json<MessageResponse>> {
+    let message_store = storehaus
+        .get_store::<GenericStore<Message>>("messages")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    // Get message
+    let message = message_store
+        .get_by_id(&id)
+        .await
+        .map_err(|_| AppError::NotFound("Message not found".to_string()))?
+        .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
+
+    authorize_message(&storehaus, &auth_user, &message, Access::Read).await?;
+
+    if read_state::mark_read(&storehaus, auth_user.user_id, &message).await? {
+        // Tell the reader's other sessions; read state is per operator
+        let ws_event = WebSocketEvent::MessageRead {
+            message_id: message.id,
+            conversation_id: message.conversation_id,
+            unread_count: read_state::unread_count(&storehaus, auth_user.user_id, message.conversation_id).await?,
+        };
+
+        if let Err(e) = ws_manager.send_to_user(&auth_user.user_id, ws_event).await {
+            warn!("Failed to send MessageRead event: {}", e);
+        }
+    }
+
+    let mut response = MessageResponse::from(message);
+    response.read = true;
+    Ok(Json(response))
 }
 
 /// Edit message request
@@ -302,11 +475,20 @@ pub async fn edit_message(
         .map_err(|_| AppError::NotFound("Message not found".to_string()))?
         .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
 
+    // Notes follow the note rules below; replies need write access
+    let access = if message.is_note() { Access::Read } else { Access::Write };
+    authorize_message(&storehaus, &auth_user, &message, access).await?;
+
     // Only users can edit messages, and only their own messages
     if !message.from_user {
         return Err(AppError::Forbidden("Cannot edit user messages".to_string()));
     }
 
+    // Notes can only be edited by their author
+    if message.is_note() && message.user_id != Some(auth_user.user_id) {
+        return Err(AppError::Forbidden("Only the author can edit a note".to_string()));
+    }
+
     // Save edit history
     let edit_record = MessageEdit::new_edit(
         message.id,
@@ -329,39 +511,34 @@ pub async fn edit_message(
         .map_err(|e| AppError::Database(e.to_string()))?;
 
     // Broadcast MessageEdited event
-    let ws_event = WebSocketEvent::MessageSent {
-        conversation_id: message.conversation_id,
-        message_id: message.id,
-        content: message.content.clone(),
-        user_id: auth_user.user_id,
-        user_name: auth_user.email.clone(),
-        media_type: message.media_type.clone(),
-        media_url: message.media_url.clone(),
-        file_name: message.file_name.clone(),
-        file_size: message.file_size,
-        mime_type: message.mime_type.clone(),
-        duration: message.duration,
+    let ws_event = if message.is_note() {
+        WebSocketEvent::NoteEdited {
+            conversation_id: message.conversation_id,
+            message_id: message.id,
+            content: message.content.clone(),
+            user_id: auth_user.user_id,
+        }
+    } else {
+        WebSocketEvent::MessageSent {
+            conversation_id: message.conversation_id,
+            message_id: message.id,
+            content: message.content.clone(),
+            user_id: auth_user.user_id,
+            user_name: auth_user.email.clone(),
+            media_type: message.media_type.clone(),
+            media_url: message.media_url.clone(),
+            file_name: message.file_name.clone(),
+            file_size: message.file_size,
+            mime_type: message.mime_type.clone(),
+            duration: message.duration,
+        }
     };
 
     if let Err(e) = ws_manager.broadcast_event(ws_event).await {
         warn!("Failed to broadcast MessageEdited event: {}", e);
     }
 
-    Ok(Json(MessageResponse {
-        id: message.id,
-        conversation_id: message.conversation_id,
-        from_user: message.from_user,
-        content: message.content,
-        read: message.read,
-        telegram_message_id: message.telegram_message_id,
-        media_type: message.media_type,
-        media_url: message.media_url,
-        file_name: message.file_name,
-        file_size: message.file_size,
-        mime_type: message.mime_type,
-        duration: message.duration,
-        created_at: message.__created_at__,
-    }))
+    Ok(Json(MessageResponse::from(message)))
 }
 
 /// Message edit history response
@@ -377,14 +554,26 @@ pub struct MessageEditResponse {
 
 /// GET /api/messages/:id/history
 pub async fn get_message_history(
-    Extension(_auth_user): Extension<AuthUser>,
+    Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
     State(storehaus): State<Arc<StoreHaus>>,
 ) -> ApiResult<Json<Vec<MessageEditResponse>>> {
+    let message_store = storehaus
+        .get_store::<GenericStore<Message>>("messages")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
     let edit_store = storehaus
         .get_store::<GenericStore<MessageEdit>>("message_edits")
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
+    let message = message_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
+
+    authorize_message(&storehaus, &auth_user, &message, Access::Read).await?;
+
     let query = QueryBuilder::new()
         .filter(QueryFilter::eq("message_id", json!(id)))
         .order_by("__created_at__", SortOrder::Desc);
@@ -414,60 +603,205 @@ pub async fn get_message_history(
 pub struct SearchMessagesQuery {
     pub query: String,
     pub conversation_id: Option<Uuid>,
+    /// Messages created at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Messages created before this time
+    pub to: Option<DateTime<Utc>>,
+    /// "customer", "operator" or "note"
+    pub sender: Option<String>,
+    /// Operator who wrote the message or note
+    pub operator_id: Option<Uuid>,
+    /// "none", "any" or a media type ("photo", "document", ...)
+    pub media_type: Option<String>,
+    pub tag_id: Option<Uuid>,
+    /// Conversation status
+    pub status: Option<String>,
     pub limit: Option<i64>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+/// Customer of a search result's conversation
+#[derive(Debug, Serialize)]
+pub struct SearchCustomer {
+    pub id: i64,
+    pub first_name: String,
+    pub last_name: Option<String>,
+    pub username: Option<String>,
+}
+
+/// Conversation of a search result
+#[derive(Debug, Serialize)]
+pub struct SearchConversation {
+    pub id: Uuid,
+    pub status: String,
+    pub user_id: Option<Uuid>,
+    pub customer: SearchCustomer,
+}
+
+/// Message search result
+#[derive(Debug, Serialize)]
+pub struct MessageSearchResult {
+    pub message: MessageResponse,
+    pub rank: f32,
+    pub snippet: Snippet,
+    pub conversation: SearchConversation,
+}
+
+/// Response for message search
+#[derive(Debug, Serialize)]
+pub struct SearchMessagesResponse {
+    pub results: Vec<MessageSearchResult>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    pub next_cursor: Option<String>,
 }
 
 /// GET /api/messages/search
 pub async fn search_messages(
-    Extension(_auth_user): Extension<AuthUser>,
+    Extension(auth_user): Extension<AuthUser>,
     Query(search_query): Query<SearchMessagesQuery>,
     State(storehaus): State<Arc<StoreHaus>>,
-) -> ApiResult<Json<Vec<MessageResponse>>> {
+) -> ApiResult<Json<SearchMessagesResponse>> {
     let message_store = storehaus
         .get_store::<GenericStore<Message>>("messages")
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Build query with text search filter
-    let search_pattern = format!("%{}%", search_query.query);
-    let mut query_builder = QueryBuilder::new()
-        .filter(QueryFilter::like("content", search_pattern.as_str()))
-        .order_by("__created_at__", SortOrder::Desc);
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Filter by conversation if specified
-    if let Some(conversation_id) = search_query.conversation_id {
-        query_builder = query_builder.filter(QueryFilter::eq("conversation_id", json!(conversation_id)));
-    }
+    let user_store = storehaus
+        .get_store::<GenericStore<User>>("users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    // Apply limit
-    if let Some(limit) = search_query.limit {
-        query_builder = query_builder.limit(limit);
-    } else {
-        query_builder = query_builder.limit(50); // Default limit
+    if search_query.query.trim().is_empty() {
+        return Err(AppError::BadRequest("Search query cannot be empty".to_string()));
     }
 
-    let messages = message_store
-        .find(query_builder)
+    let sender = match search_query.sender.as_deref() {
+        Some(value) => Some(SenderSide::parse(value).ok_or_else(|| {
+            AppError::BadRequest("Invalid sender. Must be 'customer', 'operator' or 'note'".to_string())
+        })?),
+        None => None,
+    };
+
+    let after = match search_query.cursor.as_deref() {
+        Some(value) => {
+            Some(SearchCursor::parse(value).ok_or_else(|| AppError::BadRequest("Invalid cursor".to_string()))?)
+        }
+        None => None,
+    };
+
+    let current_user = user_store
+        .get_by_id(&auth_user.user_id)
         .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    // Only conversations the user can read
+    let assignees = match access::read_scope(&storehaus, &current_user).await? {
+        ReadScope::All => None,
+        ReadScope::Assignees(ids) => Some(ids),
+        ReadScope::Nothing => {
+            return Ok(Json(SearchMessagesResponse {
+                results: Vec::new(),
+                next_cursor: None,
+            }));
+        }
+    };
+    if let Some(conversation_id) = search_query.conversation_id {
+        let conversation = conversation_store
+            .get_by_id(&conversation_id)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+        authorize_conversation(&storehaus, &auth_user, &conversation, Access::Read).await?;
+    }
+
+    let filter = SearchFilter {
+        query: search_query.query,
+        conversation_id: search_query.conversation_id,
+        from: search_query.from,
+        to: search_query.to,
+        sender,
+        operator_id: search_query.operator_id,
+        media: search_query.media_type.as_deref().map(MediaFilter::parse),
+        tag_id: search_query.tag_id,
+        status: search_query.status,
+        assignees,
+    };
+    let limit = search_query.limit.unwrap_or(50).clamp(1, 200);
+
+    let page = message_search::search(&storehaus, &filter, after, limit).await?;
+
+    // Load the full messages of the page
+    let mut messages: HashMap<Uuid, Message> = HashMap::new();
+    if !page.hits.is_empty() {
+        let query = QueryBuilder::new().filter(QueryFilter::or(
+            page.hits.iter().map(|hit| QueryFilter::eq("id", json!(hit.message_id))).collect(),
+        ));
+        messages = message_store
+            .find(query)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .into_iter()
+            .map(|m| (m.id, m))
+            .collect();
+    }
 
-    let results = messages
+    let results = page
+        .hits
         .into_iter()
-        .map(|msg| MessageResponse {
-            id: msg.id,
-            conversation_id: msg.conversation_id,
-            from_user: msg.from_user,
-            content: msg.content,
-            read: msg.read,
-            telegram_message_id: msg.telegram_message_id,
-            media_type: msg.media_type,
-            media_url: msg.media_url,
-            file_name: msg.file_name,
-            file_size: msg.file_size,
-            mime_type: msg.mime_type,
-            duration: msg.duration,
-            created_at: msg.__created_at__,
+        .filter_map(|hit| {
+            let message = messages.remove(&hit.message_id)?;
+            Some(MessageSearchResult {
+                conversation: SearchConversation {
+                    id: message.conversation_id,
+                    status: hit.conversation_status,
+                    user_id: hit.assignee_id,
+                    customer: SearchCustomer {
+                        id: hit.customer_id,
+                        first_name: hit.customer_first_name,
+                        last_name: hit.customer_last_name,
+                        username: hit.customer_username,
+                    },
+                },
+                message: MessageResponse::from(message),
+                rank: hit.rank,
+                snippet: hit.snippet,
+            })
         })
         .collect();
 
-    Ok(Json(results))
-}
\ No newline at end of file
+    Ok(Json(SearchMessagesResponse {
+        results,
+        next_cursor: page.next_cursor.map(|c| c.encode()),
+    }))
+}
+
+/// Parse a `before`/`after` cursor
+fn parse_anchor(value: &str) -> ApiResult<Anchor> {
+    Anchor::parse(value).ok_or_else(|| {
+        AppError::BadRequest(format!("Invalid cursor '{}': expected a message ID or RFC 3339 timestamp", value))
+    })
+}
+
+/// Check access to the conversation a message belongs to
+async fn authorize_message(
+    storehaus: &StoreHaus,
+    auth_user: &AuthUser,
+    message: &Message,
+    access: Access,
+) -> ApiResult<()> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let conversation = conversation_store
+        .get_by_id(&message.conversation_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(storehaus, auth_user, &conversation, access).await
+}
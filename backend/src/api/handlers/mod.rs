@@ -2,11 +2,18 @@
 
 pub mod analytics;
 pub mod auth;
+pub mod bot_texts;
 pub mod conversations;
+pub mod custom_fields;
+pub mod deep_links;
+pub mod dispositions;
 pub mod export;
 pub mod health;
 pub mod messages;
+pub mod priority_rules;
 pub mod settings;
+pub mod sla;
+pub mod tags;
 pub mod telegram_photo;
 pub mod telegram_users;
 pub mod templates;
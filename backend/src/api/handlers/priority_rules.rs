@@ -0,0 +1,279 @@
+use axum::{extract::{Path, State}, Extension, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::api::middleware::AuthUser;
+use crate::errors::{ApiResult, AppError};
+use crate::models::{ConversationPriority, PriorityRule, Tag};
+use crate::services::priority;
+
+/// Create priority rule request
+#[derive(Debug, Deserialize)]
+pub struct CreatePriorityRuleRequest {
+    pub name: String,
+    /// Customer tag, e.g. "VIP"
+    pub tag_id: Option<Uuid>,
+    pub source: Option<String>,
+    pub keywords: Option<Vec<String>>,
+    pub priority: ConversationPriority,
+    pub category: Option<String>,
+    pub position: Option<i32>,
+}
+
+/// Update priority rule request
+/// Source, keywords and category set to empty clear the value
+#[derive(Debug, Deserialize)]
+pub struct UpdatePriorityRuleRequest {
+    pub name: Option<String>,
+    pub tag_id: Option<Uuid>,
+    pub clear_tag: Option<bool>,
+    pub source: Option<String>,
+    pub keywords: Option<Vec<String>>,
+    pub priority: Option<ConversationPriority>,
+    pub category: Option<String>,
+    pub position: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+/// Priority rule response
+#[derive(Debug, Serialize)]
+pub struct PriorityRuleResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub tag_id: Option<Uuid>,
+    pub source: Option<String>,
+    pub keywords: Vec<String>,
+    pub priority: ConversationPriority,
+    pub category: Option<String>,
+    pub position: i32,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<PriorityRule> for PriorityRuleResponse {
+    fn from(rule: PriorityRule) -> Self {
+        let keywords = rule.parsed_keywords();
+
+        Self {
+            id: rule.id,
+            name: rule.name,
+            tag_id: rule.tag_id,
+            source: rule.source,
+            keywords,
+            priority: rule.priority,
+            category: rule.category,
+            position: rule.position,
+            is_active: rule.is_active,
+            created_by: rule.created_by,
+            created_at: rule.__created_at__,
+        }
+    }
+}
+
+/// GET /api/admin/priority-rules
+pub async fn get_priority_rules(
+    Extension(_auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Vec<PriorityRuleResponse>>> {
+    let rule_store = storehaus
+        .get_store::<GenericStore<PriorityRule>>("priority_rules")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut rules = rule_store
+        .find(QueryBuilder::new())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    rules.sort_by_key(|r| (r.position, r.__created_at__));
+
+    Ok(Json(rules.into_iter().map(PriorityRuleResponse::from).collect()))
+}
+
+/// POST /api/admin/priority-rules
+pub async fn create_priority_rule(
+    Extension(auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    Json(req): Json<CreatePriorityRuleRequest>,
+) -> ApiResult<Json<PriorityRuleResponse>> {
+    let rule_store = storehaus
+        .get_store::<GenericStore<PriorityRule>>("priority_rules")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    if let Some(tag_id) = req.tag_id {
+        ensure_tag_exists(&storehaus, tag_id).await?;
+    }
+
+    let rule = PriorityRule::create(
+        validate_name(&req.name)?,
+        req.tag_id,
+        normalize_source(req.source),
+        validate_keywords(req.keywords.unwrap_or_default())?,
+        req.priority,
+        validate_category(req.category)?,
+        req.position.unwrap_or(0),
+        Some(auth_user.user_id),
+    );
+    ensure_conditions(&rule)?;
+
+    let rule = rule_store
+        .create(rule, None)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(rule.into()))
+}
+
+/// PUT /api/admin/priority-rules/:id
+/// Conversations already raised by the rule keep their priority
+pub async fn update_priority_rule(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    Json(req): Json<UpdatePriorityRuleRequest>,
+) -> ApiResult<Json<PriorityRuleResponse>> {
+    let rule_store = storehaus
+        .get_store::<GenericStore<PriorityRule>>("priority_rules")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut rule = rule_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Priority rule not found".to_string()))?;
+
+    if let Some(name) = req.name {
+        rule.name = validate_name(&name)?;
+    }
+
+    if req.clear_tag.unwrap_or(false) {
+        rule.tag_id = None;
+    } else if let Some(tag_id) = req.tag_id {
+        ensure_tag_exists(&storehaus, tag_id).await?;
+        rule.tag_id = Some(tag_id);
+    }
+
+    if let Some(source) = req.source {
+        rule.source = normalize_source(Some(source));
+    }
+
+    if let Some(keywords) = req.keywords {
+        let keywords = validate_keywords(keywords)?;
+        rule.keywords = (!keywords.is_empty()).then(|| json!(keywords).to_string());
+    }
+
+    if let Some(priority) = req.priority {
+        rule.priority = priority;
+    }
+
+    if req.category.is_some() {
+        rule.category = validate_category(req.category)?;
+    }
+
+    if let Some(position) = req.position {
+        rule.position = position;
+    }
+
+    if let Some(is_active) = req.is_active {
+        rule.is_active = is_active;
+    }
+
+    ensure_conditions(&rule)?;
+
+    let rule = rule_store
+        .update(&id, rule, None)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(rule.into()))
+}
+
+/// DELETE /api/admin/priority-rules/:id
+pub async fn delete_priority_rule(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let rule_store = storehaus
+        .get_store::<GenericStore<PriorityRule>>("priority_rules")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    rule_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Priority rule not found".to_string()))?;
+
+    rule_store
+        .delete(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(json!({ "message": "Priority rule deleted successfully" })))
+}
+
+fn validate_name(name: &str) -> ApiResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Rule name cannot be empty".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+fn normalize_source(source: Option<String>) -> Option<String> {
+    source
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Trimmed, de-duplicated keywords
+fn validate_keywords(keywords: Vec<String>) -> ApiResult<Vec<String>> {
+    let mut cleaned: Vec<String> = Vec::with_capacity(keywords.len());
+    for keyword in keywords.iter().map(|k| k.trim()).filter(|k| !k.is_empty()) {
+        if keyword.chars().count() > 100 {
+            return Err(AppError::Validation("Keywords must be at most 100 characters".to_string()));
+        }
+        if !cleaned.iter().any(|c| c.to_lowercase() == keyword.to_lowercase()) {
+            cleaned.push(keyword.to_string());
+        }
+    }
+    Ok(cleaned)
+}
+
+fn validate_category(category: Option<String>) -> ApiResult<Option<String>> {
+    let category = category.as_deref().and_then(priority::normalize_category);
+    if category.as_ref().is_some_and(|c| c.chars().count() > priority::MAX_CATEGORY_LENGTH) {
+        return Err(AppError::Validation(format!(
+            "Category must be at most {} characters",
+            priority::MAX_CATEGORY_LENGTH
+        )));
+    }
+    Ok(category)
+}
+
+/// A rule without conditions would match every conversation
+fn ensure_conditions(rule: &PriorityRule) -> ApiResult<()> {
+    if !rule.has_conditions() {
+        return Err(AppError::Validation(
+            "A rule needs a tag, a source or keywords to match".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn ensure_tag_exists(storehaus: &StoreHaus, tag_id: Uuid) -> ApiResult<()> {
+    let tag_store = storehaus
+        .get_store::<GenericStore<Tag>>("tags")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    tag_store
+        .get_by_id(&tag_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Tag not found".to_string()))?;
+
+    Ok(())
+}
@@ -5,8 +5,11 @@ use storehaus::prelude::*;
 
 use crate::api::middleware::AuthUser;
 use crate::errors::{ApiResult, AppError};
-use crate::models::{Setting, SettingsResponse, UpdateSettingsRequest, User};
-use crate::telegram::{BotManager, BotStatus};
+use crate::models::{RoutingStrategy, Setting, SettingsResponse, UpdateSettingsRequest, User};
+use crate::services::routing;
+use crate::services::settings as settings_service;
+use crate::telegram::BotManager;
+use crate::websocket::WebSocketManager;
 
 /// GET /api/admin/settings - Get system settings (admin only)
 pub async fn get_settings(
@@ -43,13 +46,21 @@ pub async fn get_settings(
         .map_err(|e| AppError::Internal(e.to_string()))?
         .map(|setting| setting.value);
 
-    Ok(Json(SettingsResponse::from_bot_token(bot_token)))
+    let notifications = settings_service::notification_settings(&storehaus).await?;
+    let routing = settings_service::routing_settings(&storehaus).await?;
+    let idle = settings_service::idle_settings(&storehaus).await?;
+    let reopen = settings_service::reopen_settings(&storehaus).await?;
+    let collision = settings_service::collision_settings(&storehaus).await?;
+    let wrap_up = settings_service::wrap_up_settings(&storehaus).await?;
+
+    Ok(Json(SettingsResponse::from_bot_token(bot_token, notifications, routing, idle, reopen, collision, wrap_up)))
 }
 
 /// PUT /api/admin/settings - Update system settings (admin only)
 pub async fn update_settings(
     Extension(auth_user): Extension<AuthUser>,
     State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
     State(bot_manager): State<Arc<BotManager>>,
     Json(req): Json<UpdateSettingsRequest>,
 ) -> ApiResult<Json<SettingsResponse>> {
@@ -70,6 +81,9 @@ pub async fn update_settings(
         return Err(AppError::Forbidden("Admin access required".to_string()));
     }
 
+    // Reject the request before writing anything so it never applies halfway
+    validate_update(&req)?;
+
     let settings_store = storehaus
         .get_store::<GenericStore<Setting>>("settings")
         .map_err(|e| AppError::Internal(e.to_string()))?;
@@ -129,24 +143,97 @@ pub async fn update_settings(
         tracing::info!("[SETTINGS] Bot restart initiated in background");
     }
 
+    // Update customer notification settings
+    if let Some(enabled) = req.notify_operator_assigned {
+        settings_service::set_setting(&storehaus, Setting::NOTIFY_OPERATOR_ASSIGNED, enabled.to_string()).await?;
+    }
+    if let Some(enabled) = req.notify_conversation_closed {
+        settings_service::set_setting(&storehaus, Setting::NOTIFY_CONVERSATION_CLOSED, enabled.to_string()).await?;
+    }
+    if let Some(enabled) = req.notify_queue_position {
+        settings_service::set_setting(&storehaus, Setting::NOTIFY_QUEUE_POSITION, enabled.to_string()).await?;
+    }
+    if let Some(display) = req.operator_name_display {
+        settings_service::set_setting(&storehaus, Setting::OPERATOR_NAME_DISPLAY, display.as_str().to_string()).await?;
+    }
+
+    // Update automatic routing settings
+    if let Some(max_chats) = req.routing_max_concurrent_chats {
+        settings_service::set_setting(&storehaus, Setting::ROUTING_MAX_CONCURRENT_CHATS, max_chats.to_string()).await?;
+    }
+    if let Some(strategy) = req.routing_strategy {
+        settings_service::set_setting(&storehaus, Setting::ROUTING_STRATEGY, strategy.as_str().to_string()).await?;
+
+        // Conversations that queued up under manual routing can be picked up now
+        if strategy != RoutingStrategy::Manual {
+            routing::spawn_drain_queue(storehaus.clone(), ws_manager, bot_manager.clone());
+        }
+    }
+
+    // Update idle conversation settings
+    if let Some(hours) = req.idle_nudge_hours {
+        settings_service::set_setting(&storehaus, Setting::IDLE_NUDGE_HOURS, hours.to_string()).await?;
+    }
+    if let Some(hours) = req.idle_close_hours {
+        settings_service::set_setting(&storehaus, Setting::IDLE_CLOSE_HOURS, hours.to_string()).await?;
+    }
+
+    // Update reopen window
+    if let Some(minutes) = req.reopen_window_minutes {
+        settings_service::set_setting(&storehaus, Setting::REOPEN_WINDOW_MINUTES, minutes.to_string()).await?;
+    }
+
+    // Update reply lock
+    if let Some(mode) = req.reply_lock_mode {
+        settings_service::set_setting(&storehaus, Setting::REPLY_LOCK_MODE, mode.as_str().to_string()).await?;
+    }
+
+    // Update wrap-up requirements
+    if let Some(required) = req.disposition_required {
+        settings_service::set_setting(&storehaus, Setting::DISPOSITION_REQUIRED, required.to_string()).await?;
+    }
+    if let Some(required) = req.resolution_summary_required {
+        settings_service::set_setting(&storehaus, Setting::RESOLUTION_SUMMARY_REQUIRED, required.to_string()).await?;
+    }
+
     // Return updated settings
-    Ok(Json(SettingsResponse::from_bot_token(req.telegram_bot_token)))
+    let bot_token = settings_service::get_setting(&storehaus, Setting::TELEGRAM_BOT_TOKEN).await?;
+    let notifications = settings_service::notification_settings(&storehaus).await?;
+    let routing = settings_service::routing_settings(&storehaus).await?;
+    let idle = settings_service::idle_settings(&storehaus).await?;
+    let reopen = settings_service::reopen_settings(&storehaus).await?;
+    let collision = settings_service::collision_settings(&storehaus).await?;
+    let wrap_up = settings_service::wrap_up_settings(&storehaus).await?;
+
+    Ok(Json(SettingsResponse::from_bot_token(bot_token, notifications, routing, idle, reopen, collision, wrap_up)))
 }
 
-/// GET /api/bot/status - Get bot connection status
+fn validate_update(req: &UpdateSettingsRequest) -> ApiResult<()> {
+    if req.telegram_bot_token.as_deref().is_some_and(|token| token.trim().is_empty()) {
+        return Err(AppError::Validation("telegram_bot_token cannot be empty".to_string()));
+    }
+    if req.routing_max_concurrent_chats == Some(0) {
+        return Err(AppError::Validation("routing_max_concurrent_chats must be at least 1".to_string()));
+    }
+    Ok(())
+}
+
+/// GET /api/bot/status - Get bot connection status and health
 pub async fn get_bot_status(
     State(bot_manager): State<Arc<BotManager>>,
 ) -> ApiResult<Json<serde_json::Value>> {
     let status = bot_manager.status().await;
-
-    let status_str = match status {
-        BotStatus::Disconnected => "disconnected",
-        BotStatus::Connecting => "connecting",
-        BotStatus::Connected => "connected",
-        BotStatus::Error => "error",
-    };
+    let health = bot_manager.health().await;
 
     Ok(Json(json!({
-        "status": status_str
+        "status": status.as_str(),
+        "bot_username": health.bot_username,
+        "connected_since": health.connected_since,
+        "uptime_seconds": health.uptime_seconds(),
+        "last_update_at": health.last_update_at,
+        "last_error": health.last_error,
+        "last_error_at": health.last_error_at,
+        "restart_count": health.restart_count,
+        "next_retry_at": health.next_retry_at,
     })))
-}
\ No newline at end of file
+}
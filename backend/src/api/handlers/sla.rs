@@ -0,0 +1,307 @@
+use axum::{extract::{Path, State}, Extension, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::api::middleware::AuthUser;
+use crate::errors::{ApiResult, AppError};
+use crate::models::{BusinessHours, SlaPolicy, Tag};
+
+/// Default warning lead time in minutes
+const DEFAULT_WARNING_MINUTES: i32 = 5;
+
+/// Create SLA policy request
+#[derive(Debug, Deserialize)]
+pub struct CreateSlaPolicyRequest {
+    pub name: String,
+    pub first_response_minutes: Option<i32>,
+    pub next_response_minutes: Option<i32>,
+    pub resolution_minutes: Option<i32>,
+    pub warning_minutes: Option<i32>,
+    pub tag_id: Option<Uuid>,
+    pub source: Option<String>,
+    pub business_hours: Option<BusinessHours>,
+}
+
+/// Update SLA policy request
+/// Targets, tag and source set to 0 / empty clear the value
+#[derive(Debug, Deserialize)]
+pub struct UpdateSlaPolicyRequest {
+    pub name: Option<String>,
+    pub first_response_minutes: Option<i32>,
+    pub next_response_minutes: Option<i32>,
+    pub resolution_minutes: Option<i32>,
+    pub warning_minutes: Option<i32>,
+    pub tag_id: Option<Uuid>,
+    pub clear_tag: Option<bool>,
+    pub source: Option<String>,
+    pub business_hours: Option<BusinessHours>,
+    pub clear_business_hours: Option<bool>,
+    pub is_active: Option<bool>,
+}
+
+/// SLA policy response
+#[derive(Debug, Serialize)]
+pub struct SlaPolicyResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub first_response_minutes: Option<i32>,
+    pub next_response_minutes: Option<i32>,
+    pub resolution_minutes: Option<i32>,
+    pub warning_minutes: i32,
+    pub tag_id: Option<Uuid>,
+    pub source: Option<String>,
+    pub business_hours: Option<BusinessHours>,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<SlaPolicy> for SlaPolicyResponse {
+    fn from(policy: SlaPolicy) -> Self {
+        let business_hours = policy.parsed_business_hours();
+
+        Self {
+            id: policy.id,
+            name: policy.name,
+            first_response_minutes: policy.first_response_minutes,
+            next_response_minutes: policy.next_response_minutes,
+            resolution_minutes: policy.resolution_minutes,
+            warning_minutes: policy.warning_minutes,
+            tag_id: policy.tag_id,
+            source: policy.source,
+            business_hours,
+            is_active: policy.is_active,
+            created_by: policy.created_by,
+            created_at: policy.__created_at__,
+        }
+    }
+}
+
+/// GET /api/admin/sla-policies
+pub async fn get_sla_policies(
+    Extension(_auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Vec<SlaPolicyResponse>>> {
+    let policy_store = storehaus
+        .get_store::<GenericStore<SlaPolicy>>("sla_policies")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let query = QueryBuilder::new().order_by("__created_at__", SortOrder::Asc);
+    let policies = policy_store
+        .find(query)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(policies.into_iter().map(SlaPolicyResponse::from).collect()))
+}
+
+/// POST /api/admin/sla-policies
+pub async fn create_sla_policy(
+    Extension(auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    Json(req): Json<CreateSlaPolicyRequest>,
+) -> ApiResult<Json<SlaPolicyResponse>> {
+    let policy_store = storehaus
+        .get_store::<GenericStore<SlaPolicy>>("sla_policies")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let name = validate_name(&req.name)?;
+    let first_response_minutes = validate_target(req.first_response_minutes)?;
+    let next_response_minutes = validate_target(req.next_response_minutes)?;
+    let resolution_minutes = validate_target(req.resolution_minutes)?;
+    if first_response_minutes.is_none() && next_response_minutes.is_none() && resolution_minutes.is_none() {
+        return Err(AppError::Validation("At least one SLA target is required".to_string()));
+    }
+
+    let warning_minutes = validate_warning(req.warning_minutes.unwrap_or(DEFAULT_WARNING_MINUTES))?;
+
+    if let Some(tag_id) = req.tag_id {
+        ensure_tag_exists(&storehaus, tag_id).await?;
+    }
+
+    let business_hours = req.business_hours.map(|h| serialize_business_hours(&h)).transpose()?;
+
+    let policy = SlaPolicy::create(
+        name,
+        first_response_minutes,
+        next_response_minutes,
+        resolution_minutes,
+        warning_minutes,
+        req.tag_id,
+        normalize_source(req.source),
+        business_hours,
+        Some(auth_user.user_id),
+    );
+
+    let policy = policy_store
+        .create(policy, None)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(policy.into()))
+}
+
+/// PUT /api/admin/sla-policies/:id
+pub async fn update_sla_policy(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    Json(req): Json<UpdateSlaPolicyRequest>,
+) -> ApiResult<Json<SlaPolicyResponse>> {
+    let policy_store = storehaus
+        .get_store::<GenericStore<SlaPolicy>>("sla_policies")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut policy = policy_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("SLA policy not found".to_string()))?;
+
+    if let Some(name) = req.name {
+        policy.name = validate_name(&name)?;
+    }
+
+    if req.first_response_minutes.is_some() {
+        policy.first_response_minutes = validate_target(req.first_response_minutes)?;
+    }
+    if req.next_response_minutes.is_some() {
+        policy.next_response_minutes = validate_target(req.next_response_minutes)?;
+    }
+    if req.resolution_minutes.is_some() {
+        policy.resolution_minutes = validate_target(req.resolution_minutes)?;
+    }
+    if policy.first_response_minutes.is_none()
+        && policy.next_response_minutes.is_none()
+        && policy.resolution_minutes.is_none()
+    {
+        return Err(AppError::Validation("At least one SLA target is required".to_string()));
+    }
+
+    if let Some(warning_minutes) = req.warning_minutes {
+        policy.warning_minutes = validate_warning(warning_minutes)?;
+    }
+
+    if req.clear_tag.unwrap_or(false) {
+        policy.tag_id = None;
+    } else if let Some(tag_id) = req.tag_id {
+        ensure_tag_exists(&storehaus, tag_id).await?;
+        policy.tag_id = Some(tag_id);
+    }
+
+    if let Some(source) = req.source {
+        policy.source = normalize_source(Some(source));
+    }
+
+    if req.clear_business_hours.unwrap_or(false) {
+        policy.business_hours = None;
+    } else if let Some(hours) = req.business_hours {
+        policy.business_hours = Some(serialize_business_hours(&hours)?);
+    }
+
+    if let Some(is_active) = req.is_active {
+        policy.is_active = is_active;
+    }
+
+    let policy = policy_store
+        .update(&id, policy, None)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(policy.into()))
+}
+
+/// DELETE /api/admin/sla-policies/:id
+/// Running timers keep their deadlines; new conversations stop using the policy
+pub async fn delete_sla_policy(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let policy_store = storehaus
+        .get_store::<GenericStore<SlaPolicy>>("sla_policies")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    policy_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("SLA policy not found".to_string()))?;
+
+    policy_store
+        .delete(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(Json(json!({ "message": "SLA policy deleted successfully" })))
+}
+
+fn validate_name(name: &str) -> ApiResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Policy name cannot be empty".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+/// Targets must be positive; 0 clears the target
+fn validate_target(minutes: Option<i32>) -> ApiResult<Option<i32>> {
+    match minutes {
+        Some(m) if m < 0 => Err(AppError::Validation("SLA targets cannot be negative".to_string())),
+        Some(0) | None => Ok(None),
+        Some(m) => Ok(Some(m)),
+    }
+}
+
+fn validate_warning(minutes: i32) -> ApiResult<i32> {
+    if minutes < 0 {
+        return Err(AppError::Validation("Warning minutes cannot be negative".to_string()));
+    }
+    Ok(minutes)
+}
+
+fn normalize_source(source: Option<String>) -> Option<String> {
+    source
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn serialize_business_hours(hours: &BusinessHours) -> ApiResult<String> {
+    if !hours.is_valid() {
+        return Err(AppError::Validation(
+            "Invalid business hours: days must be 1-7 and start must be before end".to_string(),
+        ));
+    }
+    serde_json::to_string(hours).map_err(|e| AppError::Internal(e.to_string()))
+}
+
+async fn ensure_tag_exists(storehaus: &StoreHaus, tag_id: Uuid) -> ApiResult<()> {
+    let tag_store = storehaus
+        .get_store::<GenericStore<Tag>>("tags")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    tag_store
+        .get_by_id(&tag_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Tag not found".to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_target() {
+        assert_eq!(validate_target(Some(30)).unwrap(), Some(30));
+        assert_eq!(validate_target(Some(0)).unwrap(), None);
+        assert_eq!(validate_target(None).unwrap(), None);
+        assert!(validate_target(Some(-1)).is_err());
+    }
+}
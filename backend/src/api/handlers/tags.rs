@@ -0,0 +1,378 @@
+use axum::{extract::{Path, State}, Extension, Json};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use storehaus::prelude::*;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::api::middleware::{authorize_conversation, AuthUser};
+use crate::errors::{ApiResult, AppError};
+use crate::models::{Conversation, Tag, TelegramUser};
+use crate::services::access::Access;
+use crate::services::{sla, tags};
+use crate::websocket::{WebSocketEvent, WebSocketManager};
+
+/// Create tag request
+#[derive(Debug, Deserialize)]
+pub struct CreateTagRequest {
+    pub name: String,
+    pub color: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Update tag request
+#[derive(Debug, Deserialize)]
+pub struct UpdateTagRequest {
+    pub name: Option<String>,
+    pub color: Option<String>,
+    /// An empty string clears the description
+    pub description: Option<String>,
+}
+
+/// Add tag request
+#[derive(Debug, Deserialize)]
+pub struct AddTagRequest {
+    pub tag_id: Uuid,
+}
+
+/// GET /api/tags
+pub async fn get_tags(
+    Extension(_auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Vec<Tag>>> {
+    let tags = tags::list_tags(&storehaus).await?;
+
+    Ok(Json(tags))
+}
+
+/// POST /api/admin/tags
+pub async fn create_tag(
+    Extension(auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    Json(req): Json<CreateTagRequest>,
+) -> ApiResult<Json<Tag>> {
+    let tag_store = storehaus
+        .get_store::<GenericStore<Tag>>("tags")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let name = validate_name(&req.name)?;
+    if tags::find_by_name(&storehaus, &name).await?.is_some() {
+        return Err(AppError::BadRequest("Tag already exists".to_string()));
+    }
+
+    let color = match req.color {
+        Some(color) => validate_color(&color)?,
+        None => Tag::DEFAULT_COLOR.to_string(),
+    };
+
+    let tag = Tag::create(
+        name,
+        color,
+        req.description.filter(|d| !d.trim().is_empty()),
+        Some(auth_user.user_id),
+    );
+
+    let tag = tag_store
+        .create(tag, None)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    broadcast_tag_changed(&ws_manager, tag.id, "created").await;
+
+    Ok(Json(tag))
+}
+
+/// PATCH /api/admin/tags/:id
+pub async fn update_tag(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    Json(req): Json<UpdateTagRequest>,
+) -> ApiResult<Json<Tag>> {
+    let tag_store = storehaus
+        .get_store::<GenericStore<Tag>>("tags")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut tag = tag_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Tag not found".to_string()))?;
+
+    if let Some(name) = req.name {
+        let name = validate_name(&name)?;
+        if let Some(existing) = tags::find_by_name(&storehaus, &name).await? {
+            if existing.id != id {
+                return Err(AppError::BadRequest("Tag already exists".to_string()));
+            }
+        }
+        tag.name = name;
+    }
+
+    if let Some(color) = req.color {
+        tag.color = validate_color(&color)?;
+    }
+
+    if let Some(description) = req.description {
+        tag.description = Some(description).filter(|d| !d.trim().is_empty());
+    }
+
+    let tag = tag_store
+        .update(&id, tag, None)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    broadcast_tag_changed(&ws_manager, tag.id, "updated").await;
+
+    Ok(Json(tag))
+}
+
+/// DELETE /api/admin/tags/:id
+pub async fn delete_tag(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let tag_store = storehaus
+        .get_store::<GenericStore<Tag>>("tags")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    tag_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Tag not found".to_string()))?;
+
+    tags::remove_tag_links(&storehaus, id).await?;
+
+    tag_store
+        .delete(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    broadcast_tag_changed(&ws_manager, id, "deleted").await;
+
+    Ok(Json(json!({ "message": "Tag deleted successfully" })))
+}
+
+/// GET /api/conversations/:id/tags
+pub async fn get_conversation_tags(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Vec<Tag>>> {
+    ensure_conversation_access(&storehaus, &auth_user, id, Access::Read).await?;
+
+    let mut tags = tags::conversation_tags(&storehaus, &[id]).await?;
+
+    Ok(Json(tags.remove(&id).unwrap_or_default()))
+}
+
+/// POST /api/conversations/:id/tags
+pub async fn add_conversation_tag(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    Json(req): Json<AddTagRequest>,
+) -> ApiResult<Json<Vec<Tag>>> {
+    ensure_conversation_access(&storehaus, &auth_user, id, Access::Write).await?;
+    ensure_tag_exists(&storehaus, req.tag_id).await?;
+
+    let added = tags::add_conversation_tag(&storehaus, id, req.tag_id, Some(auth_user.user_id)).await?;
+
+    let current = tags::conversation_tags(&storehaus, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+
+    if added {
+        broadcast_conversation_tags(&ws_manager, id, &current, auth_user.user_id).await;
+        reapply_sla_policy(&storehaus, id).await;
+    }
+
+    Ok(Json(current))
+}
+
+/// DELETE /api/conversations/:id/tags/:tag_id
+pub async fn remove_conversation_tag(
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, tag_id)): Path<(Uuid, Uuid)>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+) -> ApiResult<Json<Vec<Tag>>> {
+    ensure_conversation_access(&storehaus, &auth_user, id, Access::Write).await?;
+
+    let removed = tags::remove_conversation_tag(&storehaus, id, tag_id).await?;
+
+    let current = tags::conversation_tags(&storehaus, &[id])
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+
+    if removed {
+        broadcast_conversation_tags(&ws_manager, id, &current, auth_user.user_id).await;
+        reapply_sla_policy(&storehaus, id).await;
+    }
+
+    Ok(Json(current))
+}
+
+/// GET /api/telegram-users/:id/tags
+pub async fn get_telegram_user_tags(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Vec<Tag>>> {
+    ensure_telegram_user_exists(&storehaus, id).await?;
+
+    let tags = tags::telegram_user_tags(&storehaus, id).await?;
+
+    Ok(Json(tags))
+}
+
+/// POST /api/telegram-users/:id/tags
+pub async fn add_telegram_user_tag(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    Json(req): Json<AddTagRequest>,
+) -> ApiResult<Json<Vec<Tag>>> {
+    ensure_telegram_user_exists(&storehaus, id).await?;
+    ensure_tag_exists(&storehaus, req.tag_id).await?;
+
+    let added = tags::add_telegram_user_tag(&storehaus, id, req.tag_id, Some(auth_user.user_id)).await?;
+
+    let current = tags::telegram_user_tags(&storehaus, id).await?;
+
+    if added {
+        broadcast_telegram_user_tags(&ws_manager, id, &current, auth_user.user_id).await;
+    }
+
+    Ok(Json(current))
+}
+
+/// DELETE /api/telegram-users/:id/tags/:tag_id
+pub async fn remove_telegram_user_tag(
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, tag_id)): Path<(i64, Uuid)>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
+) -> ApiResult<Json<Vec<Tag>>> {
+    ensure_telegram_user_exists(&storehaus, id).await?;
+
+    let removed = tags::remove_telegram_user_tag(&storehaus, id, tag_id).await?;
+
+    let current = tags::telegram_user_tags(&storehaus, id).await?;
+
+    if removed {
+        broadcast_telegram_user_tags(&ws_manager, id, &current, auth_user.user_id).await;
+    }
+
+    Ok(Json(current))
+}
+
+fn validate_name(name: &str) -> ApiResult<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > 50 {
+        return Err(AppError::Validation("Tag name must be 1-50 characters".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_color(color: &str) -> ApiResult<String> {
+    let color = color.trim();
+    if !Tag::is_valid_color(color) {
+        return Err(AppError::Validation("Tag color must be a hex value like #3B82F6".to_string()));
+    }
+    Ok(color.to_uppercase())
+}
+
+async fn ensure_conversation_access(storehaus: &StoreHaus, auth_user: &AuthUser, id: Uuid, access: Access) -> ApiResult<()> {
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let conversation = conversation_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Conversation not found".to_string()))?;
+
+    authorize_conversation(storehaus, auth_user, &conversation, access).await
+}
+
+async fn ensure_telegram_user_exists(storehaus: &StoreHaus, id: i64) -> ApiResult<()> {
+    let telegram_user_store = storehaus
+        .get_store::<GenericStore<TelegramUser>>("telegram_users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    telegram_user_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+
+    Ok(())
+}
+
+async fn ensure_tag_exists(storehaus: &StoreHaus, id: Uuid) -> ApiResult<()> {
+    let tag_store = storehaus
+        .get_store::<GenericStore<Tag>>("tags")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    tag_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Tag not found".to_string()))?;
+
+    Ok(())
+}
+
+async fn broadcast_tag_changed(ws_manager: &WebSocketManager, tag_id: Uuid, action: &str) {
+    let ws_event = WebSocketEvent::TagChanged {
+        tag_id,
+        action: action.to_string(),
+    };
+
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast TagChanged event: {}", e);
+    }
+}
+
+async fn broadcast_conversation_tags(ws_manager: &WebSocketManager, conversation_id: Uuid, tags: &[Tag], user_id: Uuid) {
+    let ws_event = WebSocketEvent::ConversationTagsChanged {
+        conversation_id,
+        tag_ids: tags.iter().map(|t| t.id).collect(),
+        user_id,
+    };
+
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast ConversationTagsChanged event: {}", e);
+    }
+}
+
+async fn broadcast_telegram_user_tags(ws_manager: &WebSocketManager, telegram_user_id: i64, tags: &[Tag], user_id: Uuid) {
+    let ws_event = WebSocketEvent::TelegramUserTagsChanged {
+        telegram_user_id,
+        tag_ids: tags.iter().map(|t| t.id).collect(),
+        user_id,
+    };
+
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast TelegramUserTagsChanged event: {}", e);
+    }
+}
+
+/// Re-select the SLA policy after a conversation's tags changed
+async fn reapply_sla_policy(storehaus: &StoreHaus, conversation_id: Uuid) {
+    if let Err(e) = sla::reapply_policy(storehaus, conversation_id).await {
+        warn!("Failed to update SLA policy of conversation {}: {}", conversation_id, e);
+    }
+}
@@ -1,13 +1,17 @@
 use axum::{extract::{Path, Query, State}, Extension, Json};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use storehaus::prelude::*;
+use uuid::Uuid;
 
+use crate::api::handlers::messages::MessageResponse;
 use crate::api::middleware::AuthUser;
 use crate::errors::{ApiResult, AppError};
-use crate::models::TelegramUser;
+use crate::models::{Conversation, Message, MessageKind, TelegramUser, User};
+use crate::services::{access, custom_fields, customer_profiles};
 
 /// Telegram user list query parameters
 #[derive(Debug, Deserialize)]
@@ -25,9 +29,36 @@ pub struct TelegramUserResponse {
     pub first_name: String,
     pub last_name: Option<String>,
     pub is_blocked: bool,
+    pub customer_profile_id: Option<Uuid>,
+    /// Custom field values by key (single user responses only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_fields: Option<BTreeMap<String, Value>>,
     pub created_at: DateTime<Utc>,
 }
 
+impl From<TelegramUser> for TelegramUserResponse {
+    fn from(user: TelegramUser) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            is_blocked: user.is_blocked,
+            customer_profile_id: user.customer_profile_id,
+            custom_fields: None,
+            created_at: user.__created_at__,
+        }
+    }
+}
+
+impl TelegramUserResponse {
+    /// Attach the user's custom field values
+    pub fn with_custom_fields(mut self, custom_fields: BTreeMap<String, Value>) -> Self {
+        self.custom_fields = Some(custom_fields);
+        self
+    }
+}
+
 /// GET /api/telegram-users
 pub async fn get_telegram_users(
     Extension(_auth_user): Extension<AuthUser>,
@@ -61,14 +92,7 @@ pub async fn get_telegram_users(
 
     let results = telegram_users
         .into_iter()
-        .map(|user| TelegramUserResponse {
-            id: user.id,
-            username: user.username,
-            first_name: user.first_name,
-            last_name: user.last_name,
-            is_blocked: user.is_blocked,
-            created_at: user.__created_at__,
-        })
+        .map(TelegramUserResponse::from)
         .collect();
 
     Ok(Json(results))
@@ -90,14 +114,9 @@ pub async fn get_telegram_user(
         .map_err(|_| AppError::NotFound("Telegram user not found".to_string()))?
         .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
 
-    Ok(Json(TelegramUserResponse {
-        id: telegram_user.id,
-        username: telegram_user.username,
-        first_name: telegram_user.first_name,
-        last_name: telegram_user.last_name,
-        is_blocked: telegram_user.is_blocked,
-        created_at: telegram_user.__created_at__,
-    }))
+    let custom_fields = custom_fields::values_for(&storehaus, id).await?;
+
+    Ok(Json(TelegramUserResponse::from(telegram_user).with_custom_fields(custom_fields)))
 }
 
 /// PATCH /api/telegram-users/:id/block
@@ -131,12 +150,226 @@ pub async fn block_telegram_user(
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
 
-    Ok(Json(TelegramUserResponse {
-        id: telegram_user.id,
-        username: telegram_user.username,
-        first_name: telegram_user.first_name,
-        last_name: telegram_user.last_name,
-        is_blocked: telegram_user.is_blocked,
-        created_at: telegram_user.__created_at__,
+    Ok(Json(TelegramUserResponse::from(telegram_user)))
+}
+
+/// Timeline query parameters
+#[derive(Debug, Deserialize)]
+pub struct TimelineQuery {
+    /// Include internal notes (default: true)
+    pub include_notes: Option<bool>,
+}
+
+/// One conversation in a customer timeline
+#[derive(Debug, Serialize)]
+pub struct TimelineConversation {
+    pub id: Uuid,
+    /// Account the conversation came from (differs for linked accounts)
+    pub telegram_user_id: i64,
+    pub status: String,
+    pub user_id: Option<Uuid>,
+    pub source: Option<String>,
+    pub previous_conversation_id: Option<Uuid>,
+    pub closed_reason: Option<String>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub messages: Vec<MessageResponse>,
+}
+
+/// Customer history across all conversations
+#[derive(Debug, Serialize)]
+pub struct TimelineResponse {
+    pub telegram_user: TelegramUserResponse,
+    /// Accounts linked through the customer profile, including this one
+    pub linked_accounts: Vec<TelegramUserResponse>,
+    /// Oldest conversation first, messages oldest first
+    pub conversations: Vec<TimelineConversation>,
+}
+
+/// GET /api/telegram-users/:id/timeline
+pub async fn get_telegram_user_timeline(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    Query(query): Query<TimelineQuery>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<TimelineResponse>> {
+    let telegram_user_store = storehaus
+        .get_store::<GenericStore<TelegramUser>>("telegram_users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let conversation_store = storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let message_store = storehaus
+        .get_store::<GenericStore<Message>>("messages")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let user_store = storehaus
+        .get_store::<GenericStore<User>>("users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let current_user = user_store
+        .get_by_id(&auth_user.user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let telegram_user = telegram_user_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))?;
+
+    // Conversations of every linked account
+    let linked_accounts = customer_profiles::linked_accounts(&storehaus, &telegram_user).await?;
+
+    let conversation_query = QueryBuilder::new()
+        .filter(QueryFilter::or(
+            linked_accounts
+                .iter()
+                .map(|account| QueryFilter::eq("telegram_user_id", json!(account.id)))
+                .collect(),
+        ))
+        .order_by("__created_at__", SortOrder::Asc);
+
+    let mut conversations = conversation_store
+        .find(conversation_query)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    // Conversations the user cannot read are left out
+    let scope = access::read_scope(&storehaus, &current_user).await?;
+    conversations.retain(|conv| scope.allows(conv.user_id));
+
+    let include_notes = query.include_notes.unwrap_or(true);
+
+    let mut timeline = Vec::with_capacity(conversations.len());
+    for conv in conversations {
+        let mut message_query = QueryBuilder::new()
+            .filter(QueryFilter::eq("conversation_id", json!(conv.id)))
+            .order_by("__created_at__", SortOrder::Asc);
+
+        if !include_notes {
+            message_query = message_query.filter(QueryFilter::ne("kind", json!(MessageKind::Note.as_str())));
+        }
+
+        let messages = message_store
+            .find(message_query)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        timeline.push(TimelineConversation {
+            id: conv.id,
+            telegram_user_id: conv.telegram_user_id,
+            status: conv.status.as_str().to_string(),
+            user_id: conv.user_id,
+            source: conv.source,
+            previous_conversation_id: conv.previous_conversation_id,
+            closed_reason: conv.closed_reason,
+            closed_at: conv.closed_at,
+            created_at: conv.__created_at__,
+            messages: messages.into_iter().map(MessageResponse::from).collect(),
+        });
+    }
+
+    Ok(Json(TimelineResponse {
+        telegram_user: TelegramUserResponse::from(telegram_user),
+        linked_accounts: linked_accounts.into_iter().map(TelegramUserResponse::from).collect(),
+        conversations: timeline,
     }))
-}
\ No newline at end of file
+}
+
+/// Link accounts request
+#[derive(Debug, Deserialize)]
+pub struct LinkTelegramUserRequest {
+    /// Other Telegram account of the same customer
+    pub telegram_user_id: i64,
+}
+
+/// GET /api/telegram-users/:id/links
+pub async fn get_linked_accounts(
+    Extension(_auth_user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<Vec<TelegramUserResponse>>> {
+    let telegram_user = load_telegram_user(&storehaus, id).await?;
+
+    let accounts = customer_profiles::linked_accounts(&storehaus, &telegram_user).await?;
+
+    Ok(Json(accounts.into_iter().map(TelegramUserResponse::from).collect()))
+}
+
+/// POST /api/telegram-users/:id/links
+pub async fn link_telegram_user(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    Json(req): Json<LinkTelegramUserRequest>,
+) -> ApiResult<Json<Vec<TelegramUserResponse>>> {
+    require_admin(&storehaus, auth_user.user_id).await?;
+
+    if req.telegram_user_id == id {
+        return Err(AppError::BadRequest("Cannot link an account to itself".to_string()));
+    }
+
+    let telegram_user = load_telegram_user(&storehaus, id).await?;
+    let other = load_telegram_user(&storehaus, req.telegram_user_id).await?;
+
+    customer_profiles::link(&storehaus, &telegram_user, &other, Some(auth_user.user_id)).await?;
+
+    // Reload to pick up the profile ID
+    let telegram_user = load_telegram_user(&storehaus, id).await?;
+    let accounts = customer_profiles::linked_accounts(&storehaus, &telegram_user).await?;
+
+    Ok(Json(accounts.into_iter().map(TelegramUserResponse::from).collect()))
+}
+
+/// DELETE /api/telegram-users/:id/links
+pub async fn unlink_telegram_user(
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    State(storehaus): State<Arc<StoreHaus>>,
+) -> ApiResult<Json<serde_json::Value>> {
+    require_admin(&storehaus, auth_user.user_id).await?;
+
+    let telegram_user = load_telegram_user(&storehaus, id).await?;
+
+    if telegram_user.customer_profile_id.is_none() {
+        return Err(AppError::BadRequest("Account is not linked".to_string()));
+    }
+
+    customer_profiles::unlink(&storehaus, &telegram_user).await?;
+
+    Ok(Json(json!({ "message": "Account unlinked successfully" })))
+}
+
+/// Linking merges what operators see of two customers, so only admins may change it
+async fn require_admin(storehaus: &StoreHaus, user_id: Uuid) -> ApiResult<()> {
+    let user_store = storehaus
+        .get_store::<GenericStore<User>>("users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let user = user_store
+        .get_by_id(&user_id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::Unauthorized("User not found".to_string()))?;
+
+    if !user.has_admin_access() {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+    Ok(())
+}
+
+async fn load_telegram_user(storehaus: &StoreHaus, id: i64) -> ApiResult<TelegramUser> {
+    let telegram_user_store = storehaus
+        .get_store::<GenericStore<TelegramUser>>("telegram_users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    telegram_user_store
+        .get_by_id(&id)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Telegram user not found".to_string()))
+}
@@ -8,7 +8,9 @@ use uuid::Uuid;
 
 use crate::api::middleware::AuthUser;
 use crate::errors::{ApiResult, AppError};
-use crate::models::{Conversation, ConversationStatus, Message, User, UserResponse, UserSettings};
+use crate::models::{Conversation, ConversationStatus, OperatorStatus, User, UserResponse, UserSettings};
+use crate::services::{assignments, routing};
+use crate::telegram::BotManager;
 use crate::websocket::{WebSocketEvent, WebSocketManager};
 
 /// GET /api/users
@@ -60,18 +62,42 @@ pub struct UpdateStatusRequest {
 /// PATCH /api/users/me/status
 pub async fn update_user_status(
     Extension(auth_user): Extension<AuthUser>,
+    State(storehaus): State<Arc<StoreHaus>>,
     State(ws_manager): State<Arc<WebSocketManager>>,
+    State(bot_manager): State<Arc<BotManager>>,
     Json(req): Json<UpdateStatusRequest>,
 ) -> ApiResult<Json<serde_json::Value>> {
     // Validate status
-    if !["online", "away", "offline"].contains(&req.status.as_str()) {
-        return Err(AppError::BadRequest(
-            "Invalid status. Must be 'online', 'away', or 'offline'".to_string(),
-        ));
-    }
+    let status = OperatorStatus::parse(&req.status).ok_or_else(|| {
+        AppError::BadRequest("Invalid status. Must be 'online', 'away', or 'offline'".to_string())
+    })?;
+
+    // Persist the status so routing knows who is available
+    let user_store = storehaus
+        .get_store::<GenericStore<User>>("users")
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut user = user_store
+        .get_by_id(&auth_user.user_id)
+        .await
+        .map_err(|_| AppError::NotFound("User not found".to_string()))?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let mut settings = user.parsed_settings();
+    settings.status = status;
+    user.settings = Some(
+        serde_json::to_string(&settings)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize settings: {}", e)))?,
+    );
+    user.last_seen_at = Some(chrono::Utc::now());
+
+    user_store
+        .update(&auth_user.user_id, user, None)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
 
     // Broadcast user status event
-    let ws_event = if req.status == "online" {
+    let ws_event = if status == OperatorStatus::Online {
         WebSocketEvent::UserOnline {
             user_id: auth_user.user_id,
             user_name: auth_user.email.clone(),
@@ -86,8 +112,13 @@ pub async fn update_user_status(
         warn!("Failed to broadcast user status event: {}", e);
     }
 
+    // Queued conversations can go to the operator who just came online
+    if status == OperatorStatus::Online {
+        routing::spawn_drain_queue(storehaus.clone(), ws_manager.clone(), bot_manager);
+    }
+
     Ok(Json(json!({
-        "status": req.status,
+        "status": status.as_str(),
         "message": "Status updated successfully"
     })))
 }
@@ -109,56 +140,7 @@ pub async fn get_user_stats(
     Extension(auth_user): Extension<AuthUser>,
     State(storehaus): State<Arc<StoreHaus>>,
 ) -> ApiResult<Json<UserStatsResponse>> {
-    let conversation_store = storehaus
-        .get_store::<GenericStore<Conversation>>("conversations")
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    let message_store = storehaus
-        .get_store::<GenericStore<Message>>("messages")
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    // Get conversations assigned to this user
-    let query = QueryBuilder::new()
-        .filter(QueryFilter::eq("user_id", json!(auth_user.user_id)));
-
-    let conversations = conversation_store
-        .find(query)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
-
-    let total_conversations = conversations.len() as i64;
-    let active_conversations = conversations
-        .iter()
-        .filter(|c| c.status == ConversationStatus::Active || c.status == ConversationStatus::Waiting)
-        .count() as i64;
-    let closed_conversations = conversations
-        .iter()
-        .filter(|c| c.status == ConversationStatus::Closed)
-        .count() as i64;
-
-    // Count messages sent by this user in their conversations
-    let mut total_messages_sent = 0i64;
-    for conversation in &conversations {
-        let msg_query = QueryBuilder::new()
-            .filter(QueryFilter::eq("conversation_id", json!(conversation.id)))
-            .filter(QueryFilter::eq("from_user", json!(true)));
-
-        let messages = message_store
-            .find(msg_query)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
-
-        total_messages_sent += messages.len() as i64;
-    }
-
-    Ok(Json(UserStatsResponse {
-        user_id: auth_user.user_id,
-        total_conversations,
-        active_conversations,
-        closed_conversations,
-        total_messages_sent,
-        average_response_time_seconds: None, // TODO: implement with real timestamps
-    }))
+    Ok(Json(user_stats(&storehaus, auth_user.user_id).await?))
 }
 
 /// GET /api/users/:id/stats
@@ -168,26 +150,42 @@ pub async fn get_user_stats_by_id(
     Path(user_id): Path<Uuid>,
     State(storehaus): State<Arc<StoreHaus>>,
 ) -> ApiResult<Json<UserStatsResponse>> {
+    Ok(Json(user_stats(&storehaus, user_id).await?))
+}
+
+/// Statistics for conversations the user handles now or handled before a transfer
+async fn user_stats(storehaus: &StoreHaus, user_id: Uuid) -> ApiResult<UserStatsResponse> {
     let conversation_store = storehaus
         .get_store::<GenericStore<Conversation>>("conversations")
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    let message_store = storehaus
-        .get_store::<GenericStore<Message>>("messages")
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    // Get conversations assigned to this user
+    // Conversations assigned to this user
     let query = QueryBuilder::new()
         .filter(QueryFilter::eq("user_id", json!(user_id)));
 
-    let conversations = conversation_store
+    let mut conversations = conversation_store
         .find(query)
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
 
-    let total_conversations = conversations.len() as i64;
+    // Plus conversations transferred away from this user
+    let handled = assignments::conversations_handled_by(storehaus, user_id).await?;
+    for id in handled {
+        if conversations.iter().any(|c| c.id == id) {
+            continue;
+        }
+        if let Some(conv) = conversation_store
+            .get_by_id(&id)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?
+        {
+            conversations.push(conv);
+        }
+    }
+
     let active_conversations = conversations
         .iter()
+        .filter(|c| c.user_id == Some(user_id))
         .filter(|c| c.status == ConversationStatus::Active || c.status == ConversationStatus::Waiting)
         .count() as i64;
     let closed_conversations = conversations
@@ -195,29 +193,19 @@ pub async fn get_user_stats_by_id(
         .filter(|c| c.status == ConversationStatus::Closed)
         .count() as i64;
 
-    // Count messages sent by this user
-    let mut total_messages_sent = 0i64;
-    for conversation in &conversations {
-        let msg_query = QueryBuilder::new()
-            .filter(QueryFilter::eq("conversation_id", json!(conversation.id)))
-            .filter(QueryFilter::eq("from_user", json!(true)));
-
-        let messages = message_store
-            .find(msg_query)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
-
-        total_messages_sent += messages.len() as i64;
-    }
+    let activity = assignments::operator_activity(storehaus, Some(user_id))
+        .await?
+        .remove(&user_id)
+        .unwrap_or_default();
 
-    Ok(Json(UserStatsResponse {
+    Ok(UserStatsResponse {
         user_id,
-        total_conversations,
+        total_conversations: conversations.len() as i64,
         active_conversations,
         closed_conversations,
-        total_messages_sent,
-        average_response_time_seconds: None,
-    }))
+        total_messages_sent: activity.messages_sent,
+        average_response_time_seconds: activity.average_response_time,
+    })
 }
 
 /// Update user profile request
@@ -0,0 +1,26 @@
+use storehaus::prelude::*;
+
+use crate::api::middleware::AuthUser;
+use crate::errors::AppError;
+use crate::models::Conversation;
+use crate::services::access::{self, Access};
+
+/// Conversation authorization
+/// Called by every handler that touches a conversation, after loading it
+pub async fn authorize_conversation(
+    storehaus: &StoreHaus,
+    auth_user: &AuthUser,
+    conversation: &Conversation,
+    access: Access,
+) -> Result<(), AppError> {
+    if access::check(storehaus, auth_user.user_id, conversation, access).await? {
+        return Ok(());
+    }
+
+    let message = match access {
+        Access::Read => "You do not have access to this conversation",
+        Access::Write => "You cannot modify this conversation",
+        Access::Delete => "Only admins can delete conversations",
+    };
+    Err(AppError::Forbidden(message.to_string()))
+}
@@ -1,9 +1,11 @@
 // API middleware
 
+pub mod access;
 pub mod admin;
 pub mod auth;
 pub mod cors;
 
+pub use access::authorize_conversation;
 pub use admin::admin_middleware;
 pub use auth::{auth_middleware, AuthUser};
 pub use cors::create_cors_layer;
@@ -1,7 +1,7 @@
 use axum::{
     extract::FromRef,
     middleware,
-    routing::{delete, get, patch, post},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use std::sync::Arc;
@@ -13,7 +13,7 @@ use crate::config::AppConfig;
 use crate::telegram::BotManager;
 use crate::websocket::{websocket_handler, WebSocketManager};
 
-use super::handlers::{analytics, auth, conversations, export, health, messages, users, settings, telegram_photo, telegram_users, templates, admin};
+use super::handlers::{analytics, auth, bot_texts, conversations, custom_fields, deep_links, dispositions, export, health, messages, users, settings, priority_rules, sla, tags, telegram_photo, telegram_users, templates, admin};
 use super::middleware::{admin_middleware, auth_middleware, create_cors_layer};
 
 /// Application state type
@@ -84,11 +84,30 @@ pub fn create_router(
         // Conversations
         .route("/conversations", get(conversations::get_conversations))
         // Specific routes first (before generic :id)
+        .route("/conversations/queue", get(conversations::get_queue))
+        .route("/conversations/unread", get(conversations::get_unread))
         .route("/conversations/:id/assign", patch(conversations::assign_conversation))
+        .route("/conversations/:id/transfer", post(conversations::transfer_conversation))
+        .route("/conversations/:id/assignments", get(conversations::get_assignment_history))
         .route("/conversations/:id/status", patch(conversations::update_conversation_status))
         .route("/conversations/:id/close", patch(conversations::close_conversation))
+        .route("/conversations/:id/merge", post(conversations::merge_conversation))
+        .route("/conversations/:id/merges", get(conversations::get_merge_history))
+        .route("/conversations/:id/snooze", patch(conversations::snooze_conversation))
+        .route("/conversations/:id/wake", patch(conversations::wake_conversation))
+        .route("/conversations/:id/priority", patch(conversations::update_priority))
+        .route(
+            "/conversations/:id/presence",
+            get(conversations::get_presence).put(conversations::report_presence),
+        )
         .route("/conversations/:id/mark-read", patch(conversations::mark_conversation_read))
         .route("/conversations/:id/export", get(export::export_conversation))
+        .route("/conversations/:id/notes", post(messages::create_note))
+        .route(
+            "/conversations/:id/tags",
+            get(tags::get_conversation_tags).post(tags::add_conversation_tag),
+        )
+        .route("/conversations/:id/tags/:tag_id", delete(tags::remove_conversation_tag))
         // Generic :id route last
         .route(
             "/conversations/:id",
@@ -105,6 +124,25 @@ pub fn create_router(
         .route("/telegram-users", get(telegram_users::get_telegram_users))
         .route("/telegram-users/:id", get(telegram_users::get_telegram_user))
         .route("/telegram-users/:id/block", patch(telegram_users::block_telegram_user))
+        .route("/telegram-users/:id/timeline", get(telegram_users::get_telegram_user_timeline))
+        .route(
+            "/telegram-users/:id/links",
+            get(telegram_users::get_linked_accounts)
+                .post(telegram_users::link_telegram_user)
+                .delete(telegram_users::unlink_telegram_user),
+        )
+        .route(
+            "/telegram-users/:id/tags",
+            get(tags::get_telegram_user_tags).post(tags::add_telegram_user_tag),
+        )
+        .route("/telegram-users/:id/tags/:tag_id", delete(tags::remove_telegram_user_tag))
+        .route("/telegram-users/:id/custom-fields", patch(custom_fields::set_custom_field_values))
+        // Tags
+        .route("/tags", get(tags::get_tags))
+        // Custom fields
+        .route("/custom-fields", get(custom_fields::get_custom_fields))
+        // Disposition codes
+        .route("/disposition-codes", get(dispositions::get_disposition_codes))
         // Templates
         .route("/templates", get(templates::get_templates))
         .route("/templates", post(templates::create_template))
@@ -125,6 +163,10 @@ pub fn create_router(
         .route("/analytics/users", get(analytics::get_users_stats))
         .route("/analytics/response-times", get(analytics::get_response_time_stats))
         .route("/analytics/message-volume", get(analytics::get_message_volume))
+        .route("/analytics/sources", get(analytics::get_source_stats))
+        .route("/analytics/tags", get(analytics::get_tag_stats))
+        .route("/analytics/sla", get(analytics::get_sla_stats))
+        .route("/analytics/dispositions", get(analytics::get_disposition_stats))
         .route_layer(middleware::from_fn_with_state(
             config.clone(),
             auth_middleware,
@@ -140,6 +182,49 @@ pub fn create_router(
         .route("/admin/users/:id/toggle-admin", patch(admin::toggle_user_admin))
         // Settings
         .route("/admin/settings", get(settings::get_settings).put(settings::update_settings))
+        // Bot texts
+        .route("/admin/bot-texts", get(bot_texts::get_bot_texts))
+        .route("/admin/bot-texts/preview", post(bot_texts::preview_bot_text))
+        .route(
+            "/admin/bot-texts/:locale/:key",
+            put(bot_texts::update_bot_text).delete(bot_texts::reset_bot_text),
+        )
+        // SLA policies
+        .route("/admin/sla-policies", get(sla::get_sla_policies).post(sla::create_sla_policy))
+        .route(
+            "/admin/sla-policies/:id",
+            put(sla::update_sla_policy).delete(sla::delete_sla_policy),
+        )
+        // Tags
+        .route("/admin/tags", post(tags::create_tag))
+        .route("/admin/tags/:id", patch(tags::update_tag).delete(tags::delete_tag))
+        // Custom fields
+        .route("/admin/custom-fields", post(custom_fields::create_custom_field))
+        .route(
+            "/admin/custom-fields/:id",
+            patch(custom_fields::update_custom_field).delete(custom_fields::delete_custom_field),
+        )
+        // Disposition codes
+        .route("/admin/disposition-codes", post(dispositions::create_disposition_code))
+        .route(
+            "/admin/disposition-codes/:id",
+            patch(dispositions::update_disposition_code).delete(dispositions::delete_disposition_code),
+        )
+        // Priority rules
+        .route(
+            "/admin/priority-rules",
+            get(priority_rules::get_priority_rules).post(priority_rules::create_priority_rule),
+        )
+        .route(
+            "/admin/priority-rules/:id",
+            put(priority_rules::update_priority_rule).delete(priority_rules::delete_priority_rule),
+        )
+        // Deep links
+        .route("/admin/deep-links", post(deep_links::create_deep_link))
+        // Conversation trash
+        .route("/admin/conversations/trash", get(conversations::get_trash))
+        .route("/admin/conversations/:id/restore", post(conversations::restore_conversation))
+        .route("/admin/conversations/:id/purge", delete(conversations::purge_conversation))
         .route_layer(middleware::from_fn_with_state(
             storehaus.clone(),
             admin_middleware,
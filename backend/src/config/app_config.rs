@@ -23,6 +23,12 @@ pub struct AppConfig {
 
     /// Environment (development, production)
     pub environment: String,
+
+    /// Custom Telegram Bot API base URL (defaults to https://api.telegram.org)
+    pub telegram_api_url: Option<String>,
+
+    /// Secret for signing deep-link `/start` payloads with customer IDs
+    pub deep_link_secret: Option<String>,
 }
 
 impl AppConfig {
@@ -46,6 +52,12 @@ impl AppConfig {
                 .parse()?,
             environment: env::var("ENVIRONMENT")
                 .unwrap_or_else(|_| "development".to_string()),
+            telegram_api_url: env::var("TELEGRAM_API_URL")
+                .ok()
+                .filter(|url| !url.is_empty()),
+            deep_link_secret: env::var("DEEP_LINK_SECRET")
+                .ok()
+                .filter(|secret| !secret.is_empty()),
         };
 
         Ok(config)
@@ -1,9 +1,14 @@
-use crate::models::{Conversation, Message, MessageTemplate, Setting, TelegramUser, User};
+use crate::models::{
+    AuditEntry, BotText, Conversation, ConversationAssignment, ConversationMerge, ConversationTag, CustomField,
+    CustomFieldValue, CustomerProfile, DispositionCode, Message, MessageEdit, MessageTemplate, PriorityRule,
+    ReadCursor, Setting, SlaEvent, SlaPolicy, Tag, TelegramUser, TelegramUserTag, User,
+};
+use crate::services::message_search;
 use anyhow::{anyhow, Result};
 use storehaus::prelude::*;
 use std::env;
 use std::fs;
-use tracing::info;
+use tracing::{info, warn};
 
 /// Initialize StoreHaus with all models and stores
 pub async fn initialize_database() -> Result<StoreHaus> {
@@ -272,12 +277,64 @@ pub async fn initialize_database() -> Result<StoreHaus> {
     storehaus.auto_migrate::<Message>(false).await?;
     info!("  ✓ Message table migrated");
 
+    storehaus.auto_migrate::<MessageEdit>(false).await?;
+    info!("  ✓ MessageEdit table migrated");
+
     storehaus.auto_migrate::<MessageTemplate>(false).await?;
     info!("  ✓ MessageTemplate table migrated");
 
     storehaus.auto_migrate::<Setting>(false).await?;
     info!("  ✓ Setting table migrated");
 
+    storehaus.auto_migrate::<BotText>(false).await?;
+    unique_bot_texts(&storehaus).await?;
+    info!("  ✓ BotText table migrated");
+
+    storehaus.auto_migrate::<Tag>(false).await?;
+    info!("  ✓ Tag table migrated");
+
+    storehaus.auto_migrate::<ConversationTag>(false).await?;
+    info!("  ✓ ConversationTag table migrated");
+
+    storehaus.auto_migrate::<TelegramUserTag>(false).await?;
+    info!("  ✓ TelegramUserTag table migrated");
+
+    storehaus.auto_migrate::<ConversationAssignment>(false).await?;
+    info!("  ✓ ConversationAssignment table migrated");
+
+    storehaus.auto_migrate::<SlaPolicy>(false).await?;
+    info!("  ✓ SlaPolicy table migrated");
+
+    storehaus.auto_migrate::<SlaEvent>(false).await?;
+    info!("  ✓ SlaEvent table migrated");
+
+    storehaus.auto_migrate::<ConversationMerge>(false).await?;
+    info!("  ✓ ConversationMerge table migrated");
+
+    storehaus.auto_migrate::<CustomerProfile>(false).await?;
+    info!("  ✓ CustomerProfile table migrated");
+
+    storehaus.auto_migrate::<AuditEntry>(false).await?;
+    info!("  ✓ AuditEntry table migrated");
+
+    storehaus.auto_migrate::<ReadCursor>(false).await?;
+    unique_read_cursors(&storehaus).await?;
+    info!("  ✓ ReadCursor table migrated");
+
+    storehaus.auto_migrate::<CustomField>(false).await?;
+    info!("  ✓ CustomField table migrated");
+
+    storehaus.auto_migrate::<CustomFieldValue>(false).await?;
+    info!("  ✓ CustomFieldValue table migrated");
+
+    storehaus.auto_migrate::<PriorityRule>(false).await?;
+    info!("  ✓ PriorityRule table migrated");
+
+    storehaus.auto_migrate::<DispositionCode>(false).await?;
+    info!("  ✓ DispositionCode table migrated");
+
+    create_indexes(&storehaus).await;
+
     // Register stores
     info!("Registering stores...");
     storehaus.register_store(
@@ -300,6 +357,11 @@ pub async fn initialize_database() -> Result<StoreHaus> {
         GenericStore::<Message>::new(storehaus.pool().clone(), None, None),
     )?;
 
+    storehaus.register_store(
+        "message_edits".to_string(),
+        GenericStore::<MessageEdit>::new(storehaus.pool().clone(), None, None),
+    )?;
+
     storehaus.register_store(
         "templates".to_string(),
         GenericStore::<MessageTemplate>::new(storehaus.pool().clone(), None, None),
@@ -310,11 +372,186 @@ pub async fn initialize_database() -> Result<StoreHaus> {
         GenericStore::<Setting>::new(storehaus.pool().clone(), None, None),
     )?;
 
+    storehaus.register_store(
+        "bot_texts".to_string(),
+        GenericStore::<BotText>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "tags".to_string(),
+        GenericStore::<Tag>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "conversation_tags".to_string(),
+        GenericStore::<ConversationTag>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "telegram_user_tags".to_string(),
+        GenericStore::<TelegramUserTag>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "conversation_assignments".to_string(),
+        GenericStore::<ConversationAssignment>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "sla_policies".to_string(),
+        GenericStore::<SlaPolicy>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "sla_events".to_string(),
+        GenericStore::<SlaEvent>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "conversation_merges".to_string(),
+        GenericStore::<ConversationMerge>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "customer_profiles".to_string(),
+        GenericStore::<CustomerProfile>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "audit_log".to_string(),
+        GenericStore::<AuditEntry>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "read_cursors".to_string(),
+        GenericStore::<ReadCursor>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "custom_fields".to_string(),
+        GenericStore::<CustomField>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "custom_field_values".to_string(),
+        GenericStore::<CustomFieldValue>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "priority_rules".to_string(),
+        GenericStore::<PriorityRule>::new(storehaus.pool().clone(), None, None),
+    )?;
+
+    storehaus.register_store(
+        "disposition_codes".to_string(),
+        GenericStore::<DispositionCode>::new(storehaus.pool().clone(), None, None),
+    )?;
+
     info!("Database initialization complete!");
 
     Ok(storehaus)
 }
 
+/// Indexes for the conversation list (trigram indexes for customer search,
+/// a btree matching the last message order), message history (a btree
+/// matching the history order), custom field lookups and
+/// message search (one full-text index per search configuration plus a
+/// trigram index).
+/// Failures are logged rather than fatal — without them queries are slower,
+/// not wrong.
+async fn create_indexes(storehaus: &StoreHaus) {
+    if let Err(e) = sqlx::query("CREATE EXTENSION IF NOT EXISTS pg_trgm").execute(storehaus.pool()).await {
+        warn!("Failed to enable pg_trgm, trigram search will not be available: {}", e);
+    }
+
+    let indexes = [
+        "CREATE INDEX IF NOT EXISTS idx_conversations_last_message \
+         ON conversations (last_message_at DESC NULLS LAST, id DESC)",
+        "CREATE INDEX IF NOT EXISTS idx_conversations_telegram_user ON conversations (telegram_user_id)",
+        "CREATE INDEX IF NOT EXISTS idx_conversations_user_status ON conversations (user_id, status)",
+        "CREATE INDEX IF NOT EXISTS idx_telegram_users_first_name_trgm \
+         ON telegram_users USING gin (first_name gin_trgm_ops)",
+        "CREATE INDEX IF NOT EXISTS idx_telegram_users_last_name_trgm \
+         ON telegram_users USING gin (last_name gin_trgm_ops)",
+        "CREATE INDEX IF NOT EXISTS idx_telegram_users_username_trgm \
+         ON telegram_users USING gin (username gin_trgm_ops)",
+        "CREATE INDEX IF NOT EXISTS idx_telegram_users_id_trgm \
+         ON telegram_users USING gin ((id::text) gin_trgm_ops)",
+        "CREATE INDEX IF NOT EXISTS idx_messages_conversation_created \
+         ON messages (conversation_id, __created_at__, id)",
+        "CREATE INDEX IF NOT EXISTS idx_custom_field_values_user ON custom_field_values (telegram_user_id)",
+        "CREATE INDEX IF NOT EXISTS idx_custom_field_values_value_trgm \
+         ON custom_field_values USING gin (value gin_trgm_ops)",
+        "CREATE INDEX IF NOT EXISTS idx_messages_content_trgm ON messages USING gin (content gin_trgm_ops)",
+    ];
+
+    let full_text_indexes = message_search::SEARCH_CONFIGS.iter().map(|config| {
+        format!(
+            "CREATE INDEX IF NOT EXISTS idx_messages_content_fts_{config} \
+             ON messages USING gin (to_tsvector('{config}', content))",
+            config = config
+        )
+    });
+    let indexes = indexes.into_iter().map(str::to_string).chain(full_text_indexes);
+
+    for sql in indexes {
+        if let Err(e) = sqlx::query(&sql).execute(storehaus.pool()).await {
+            warn!("Failed to create index: {} ({})", e, sql);
+        }
+    }
+    info!("  ✓ Query indexes created");
+}
+
+/// One read cursor per operator and conversation; `read_state::mark_read`
+/// upserts on this index, so unlike the query indexes it is required.
+/// Duplicates left by earlier versions are dropped, keeping the furthest cursor.
+async fn unique_read_cursors(storehaus: &StoreHaus) -> Result<()> {
+    let pool = storehaus.pool();
+
+    sqlx::query(
+        "DELETE FROM read_cursors a USING read_cursors b \
+         WHERE a.user_id = b.user_id AND a.conversation_id = b.conversation_id \
+             AND (a.last_read_at, a.last_read_message_id, a.id) < (b.last_read_at, b.last_read_message_id, b.id)",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("DROP INDEX IF EXISTS idx_read_cursors_user_conversation")
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_read_cursors_user_conversation_unique \
+         ON read_cursors (user_id, conversation_id)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// One override per locale and message key; `bot_texts::save_override`
+/// upserts on this index. Duplicates left by earlier versions are dropped,
+/// keeping the live, most recently updated override.
+async fn unique_bot_texts(storehaus: &StoreHaus) -> Result<()> {
+    let pool = storehaus.pool();
+
+    sqlx::query(
+        "DELETE FROM bot_texts a USING bot_texts b \
+         WHERE a.locale = b.locale AND a.message_key = b.message_key \
+             AND (a.__deleted_at__ IS NULL, a.__updated_at__, a.id) \
+                 < (b.__deleted_at__ IS NULL, b.__updated_at__, b.id)",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_bot_texts_locale_key_unique \
+         ON bot_texts (locale, message_key)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 /// Seed database with initial data (admin user, operator, templates).
 /// Safe to call in any environment — checks for existing records before inserting.
 pub async fn seed_database(storehaus: &StoreHaus) -> Result<()> {
@@ -414,6 +651,16 @@ pub async fn seed_database(storehaus: &StoreHaus) -> Result<()> {
         }
     }
 
+    // Create default disposition codes when there are none yet
+    let code_store = storehaus.get_store::<GenericStore<DispositionCode>>("disposition_codes")?;
+    if code_store.find(QueryBuilder::new()).await?.is_empty() {
+        for (position, (code, label)) in DispositionCode::DEFAULTS.into_iter().enumerate() {
+            let code = DispositionCode::create(code.to_string(), label.to_string(), position as i32);
+            code_store.create(code, None).await?;
+        }
+        info!("  ✓ Default disposition codes created");
+    }
+
     info!("Database seeding complete!");
 
     Ok(())
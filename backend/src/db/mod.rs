@@ -1,5 +1,6 @@
 // Database module
 
 mod init;
+pub mod sql;
 
 pub use init::{initialize_database, seed_database};
\ No newline at end of file
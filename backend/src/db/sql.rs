@@ -0,0 +1,91 @@
+//! Helpers for hand-written SQL with a variable set of conditions
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgArguments;
+use sqlx::query::Query;
+use sqlx::Postgres;
+use uuid::Uuid;
+
+/// Value bound to a positional SQL parameter
+#[derive(Debug, Clone)]
+pub enum Bind {
+    Text(String),
+    Id(Uuid),
+    Ids(Vec<Uuid>),
+    Time(DateTime<Utc>),
+    Real(f32),
+}
+
+/// WHERE clause with its parameters
+#[derive(Debug, Default)]
+pub struct Conditions {
+    clauses: Vec<String>,
+    binds: Vec<Bind>,
+}
+
+impl Conditions {
+    /// Add a parameter and return its placeholder
+    pub fn bind(&mut self, value: Bind) -> String {
+        self.binds.push(value);
+        format!("${}", self.binds.len())
+    }
+
+    pub fn push(&mut self, clause: String) {
+        self.clauses.push(clause);
+    }
+
+    pub fn binds(&self) -> &[Bind] {
+        &self.binds
+    }
+
+    /// Clauses joined with AND
+    pub fn sql(&self) -> String {
+        self.clauses.join(" AND ")
+    }
+}
+
+/// Bind `binds` to `query` in placeholder order
+pub fn apply<'q>(mut query: Query<'q, Postgres, PgArguments>, binds: &[Bind]) -> Query<'q, Postgres, PgArguments> {
+    for bind in binds {
+        query = match bind {
+            Bind::Text(value) => query.bind(value.clone()),
+            Bind::Id(value) => query.bind(*value),
+            Bind::Ids(value) => query.bind(value.clone()),
+            Bind::Time(value) => query.bind(*value),
+            Bind::Real(value) => query.bind(*value),
+        };
+    }
+    query
+}
+
+/// LIKE pattern matching `term` literally anywhere in the value
+pub fn like_pattern(term: &str) -> String {
+    let escaped = term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Whitespace-separated search terms, ignoring a leading `@`
+pub fn search_terms(search: &str) -> Vec<String> {
+    search
+        .split_whitespace()
+        .map(|term| term.trim_start_matches('@'))
+        .filter(|term| !term.is_empty())
+        .map(|term| term.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_like_pattern_and_terms() {
+        assert_eq!(like_pattern("50%_off\\"), "%50\\%\\_off\\\\%");
+        assert_eq!(search_terms("  @john  Smith "), vec!["john", "Smith"]);
+
+        let mut conditions = Conditions::default();
+        let first = conditions.bind(Bind::Text("a".to_string()));
+        let second = conditions.bind(Bind::Id(Uuid::nil()));
+        assert_eq!((first.as_str(), second.as_str()), ("$1", "$2"));
+    }
+}
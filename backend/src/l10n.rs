@@ -1,15 +1,73 @@
 use once_cell::sync::Lazy;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::RwLock;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct BotMessages {
     pub welcome: String,
     pub operator_assigned: String,
+    pub anonymous_operator_name: String,
     pub conversation_closed: String,
     pub operator_typing: String,
     pub message_sent: String,
     pub error: String,
+    pub queue_position: String,
+    pub queue_wait_estimate: String,
+    pub idle_nudge: String,
+}
+
+impl BotMessages {
+    /// Message keys that can be overridden by admins
+    pub const KEYS: &'static [&'static str] = &[
+        "welcome",
+        "operator_assigned",
+        "anonymous_operator_name",
+        "conversation_closed",
+        "operator_typing",
+        "message_sent",
+        "error",
+        "queue_position",
+        "queue_wait_estimate",
+        "idle_nudge",
+    ];
+
+    /// Get message text by key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let value = match key {
+            "welcome" => &self.welcome,
+            "operator_assigned" => &self.operator_assigned,
+            "anonymous_operator_name" => &self.anonymous_operator_name,
+            "conversation_closed" => &self.conversation_closed,
+            "operator_typing" => &self.operator_typing,
+            "message_sent" => &self.message_sent,
+            "error" => &self.error,
+            "queue_position" => &self.queue_position,
+            "queue_wait_estimate" => &self.queue_wait_estimate,
+            "idle_nudge" => &self.idle_nudge,
+            _ => return None,
+        };
+        Some(value.as_str())
+    }
+
+    /// Set message text by key, returns false for unknown keys
+    pub fn set(&mut self, key: &str, value: String) -> bool {
+        let slot = match key {
+            "welcome" => &mut self.welcome,
+            "operator_assigned" => &mut self.operator_assigned,
+            "anonymous_operator_name" => &mut self.anonymous_operator_name,
+            "conversation_closed" => &mut self.conversation_closed,
+            "operator_typing" => &mut self.operator_typing,
+            "message_sent" => &mut self.message_sent,
+            "error" => &mut self.error,
+            "queue_position" => &mut self.queue_position,
+            "queue_wait_estimate" => &mut self.queue_wait_estimate,
+            "idle_nudge" => &mut self.idle_nudge,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -17,18 +75,29 @@ pub struct LocaleData {
     pub bot: BotMessages,
 }
 
+/// Locale directories, relative to the working directory.
+/// The second entry covers `cargo test`, which runs from `backend/`.
+const LOCALE_DIRS: &[&str] = &["locales/backend", "../locales/backend"];
+
+/// Read a locale file from the first directory that has it
+fn read_locale_file(file_name: &str) -> Option<String> {
+    LOCALE_DIRS
+        .iter()
+        .find_map(|dir| std::fs::read_to_string(format!("{}/{}", dir, file_name)).ok())
+}
+
 pub static LOCALES: Lazy<HashMap<String, LocaleData>> = Lazy::new(|| {
     let mut locales = HashMap::new();
 
     // Load Russian locale
-    if let Ok(ru_content) = std::fs::read_to_string("locales/backend/ru.json") {
+    if let Some(ru_content) = read_locale_file("ru.json") {
         if let Ok(ru_data) = serde_json::from_str::<LocaleData>(&ru_content) {
             locales.insert("ru".to_string(), ru_data);
         }
     }
 
     // Load English locale
-    if let Ok(en_content) = std::fs::read_to_string("locales/backend/en.json") {
+    if let Some(en_content) = read_locale_file("en.json") {
         if let Ok(en_data) = serde_json::from_str::<LocaleData>(&en_content) {
             locales.insert("en".to_string(), en_data);
         }
@@ -37,20 +106,101 @@ pub static LOCALES: Lazy<HashMap<String, LocaleData>> = Lazy::new(|| {
     locales
 });
 
-/// Get locale based on user's country code
+/// Bot message overrides edited by admins (locale -> key -> text).
+/// Loaded from the database on startup and updated live by the admin API.
+static OVERRIDES: Lazy<RwLock<HashMap<String, HashMap<String, String>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Get locale key based on user's country code
 /// Russia (RU) -> ru, otherwise -> en
-pub fn get_locale(country_code: Option<&str>) -> &'static LocaleData {
-    let locale_key = match country_code {
+pub fn locale_key(country_code: Option<&str>) -> &'static str {
+    match country_code {
         Some("RU") => "ru",
         _ => "en",
-    };
+    }
+}
+
+/// Get locale file defaults based on user's country code
+pub fn get_locale(country_code: Option<&str>) -> &'static LocaleData {
+    get_locale_by_key(locale_key(country_code))
+}
 
+/// Get locale file defaults by locale key, falling back to English
+pub fn get_locale_by_key(locale: &str) -> &'static LocaleData {
     LOCALES
-        .get(locale_key)
+        .get(locale)
         .or_else(|| LOCALES.get("en"))
         .expect("Default English locale must be available")
 }
 
+/// List of loaded locale keys
+pub fn available_locales() -> Vec<&'static str> {
+    let mut locales: Vec<&'static str> = LOCALES.keys().map(|k| k.as_str()).collect();
+    locales.sort_unstable();
+    locales
+}
+
+/// Get bot messages for a user's country code with admin overrides applied
+pub fn bot_messages(country_code: Option<&str>) -> BotMessages {
+    bot_messages_for_locale(locale_key(country_code))
+}
+
+/// Get bot messages for a locale key with admin overrides applied
+pub fn bot_messages_for_locale(locale: &str) -> BotMessages {
+    let defaults = &get_locale_by_key(locale).bot;
+    let overrides = OVERRIDES.read().unwrap_or_else(|e| e.into_inner());
+
+    match overrides.get(locale) {
+        Some(locale_overrides) => apply_overrides(defaults, locale_overrides),
+        None => defaults.clone(),
+    }
+}
+
+/// Apply overrides on top of default messages (unknown keys are ignored)
+pub fn apply_overrides(defaults: &BotMessages, overrides: &HashMap<String, String>) -> BotMessages {
+    let mut messages = defaults.clone();
+    for (key, value) in overrides {
+        messages.set(key, value.clone());
+    }
+    messages
+}
+
+/// Get current overrides for a locale
+pub fn overrides_for_locale(locale: &str) -> HashMap<String, String> {
+    OVERRIDES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(locale)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Replace all overrides (used when loading from the database)
+pub fn replace_overrides(overrides: HashMap<String, HashMap<String, String>>) {
+    *OVERRIDES.write().unwrap_or_else(|e| e.into_inner()) = overrides;
+}
+
+/// Set a single override
+pub fn set_override(locale: &str, key: &str, value: String) {
+    OVERRIDES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(locale.to_string())
+        .or_default()
+        .insert(key.to_string(), value);
+}
+
+/// Remove a single override, falling back to the file default
+pub fn remove_override(locale: &str, key: &str) {
+    if let Some(locale_overrides) = OVERRIDES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_mut(locale)
+    {
+        locale_overrides.remove(key);
+    }
+}
+
 /// Format a message with variables
 pub fn format_message(template: &str, vars: &HashMap<&str, &str>) -> String {
     let mut result = template.to_string();
@@ -65,6 +215,21 @@ pub fn format_message(template: &str, vars: &HashMap<&str, &str>) -> String {
 mod tests {
     use super::*;
 
+    fn sample_messages() -> BotMessages {
+        BotMessages {
+            welcome: "Hello".to_string(),
+            operator_assigned: "Operator {operator_name} has joined.".to_string(),
+            anonymous_operator_name: "An operator".to_string(),
+            conversation_closed: "Closed".to_string(),
+            operator_typing: "Typing".to_string(),
+            message_sent: "Sent".to_string(),
+            error: "Error".to_string(),
+            queue_position: "You are number {position} in the queue.".to_string(),
+            queue_wait_estimate: "Estimated wait: about {minutes} min.".to_string(),
+            idle_nudge: "Still there?".to_string(),
+        }
+    }
+
     #[test]
     fn test_get_locale_ru() {
         let locale = get_locale(Some("RU"));
@@ -84,4 +249,25 @@ mod tests {
         let result = format_message("Operator {operator_name} has joined.", &vars);
         assert_eq!(result, "Operator John has joined.");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_bot_messages_keys_are_gettable() {
+        let messages = sample_messages();
+        for key in BotMessages::KEYS {
+            assert!(messages.get(key).is_some(), "missing key {}", key);
+        }
+        assert!(messages.get("unknown").is_none());
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let defaults = sample_messages();
+        let mut overrides = HashMap::new();
+        overrides.insert("welcome".to_string(), "Hi there".to_string());
+        overrides.insert("unknown".to_string(), "ignored".to_string());
+
+        let messages = apply_overrides(&defaults, &overrides);
+        assert_eq!(messages.welcome, "Hi there");
+        assert_eq!(messages.conversation_closed, defaults.conversation_closed);
+    }
+}
@@ -9,6 +9,7 @@ use flashback_backend::{
     config::AppConfig,
     db::{initialize_database, seed_database},
     models::Setting,
+    services::{bot_texts, idle, routing, sla, snooze},
     telegram::BotManager,
     websocket::WebSocketManager,
 };
@@ -85,6 +86,11 @@ async fn main() -> Result<()> {
         error!("Error seeding database: {}", e);
     }
 
+    // Load admin-edited bot texts
+    if let Err(e) = bot_texts::load_overrides(&storehaus).await {
+        error!("Error loading bot text overrides: {}", e);
+    }
+
     let storehaus = Arc::new(storehaus);
 
     // Create WebSocket manager
@@ -92,11 +98,25 @@ async fn main() -> Result<()> {
         .with_max_connections(1000)
         .with_broadcast_buffer(500)
         .with_ping_interval(30);
-    let ws_manager = Arc::new(WebSocketManager::new(ws_config));
+    let ws_manager = Arc::new(WebSocketManager::new(ws_config, storehaus.clone()));
     info!("WebSocket manager initialized");
 
     // Create Bot Manager
-    let bot_manager = Arc::new(BotManager::new(storehaus.clone(), ws_manager.clone()));
+    let telegram_api_url = config
+        .telegram_api_url
+        .as_deref()
+        .map(reqwest::Url::parse)
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid TELEGRAM_API_URL: {}", e))?;
+    if let Some(url) = &telegram_api_url {
+        info!("Using custom Telegram Bot API URL: {}", url);
+    }
+
+    let bot_manager = Arc::new(
+        BotManager::new(storehaus.clone(), ws_manager.clone())
+            .with_api_url(telegram_api_url)
+            .with_deep_link_secret(config.deep_link_secret.clone()),
+    );
     info!("Bot manager initialized");
 
     // Try to load bot token from database and start bot
@@ -116,6 +136,18 @@ async fn main() -> Result<()> {
         info!("No bot token found in database. Bot will start when configured via settings.");
     }
 
+    // Assign queued conversations whenever operators have capacity
+    let queue_worker = routing::spawn_queue_worker(storehaus.clone(), ws_manager.clone(), bot_manager.clone());
+
+    // Warn operators about approaching and breached SLA deadlines
+    let sla_worker = sla::spawn_sla_worker(storehaus.clone(), ws_manager.clone(), bot_manager.clone());
+
+    // Nudge customers who went quiet and close abandoned conversations
+    let idle_worker = idle::spawn_idle_worker(storehaus.clone(), ws_manager.clone(), bot_manager.clone());
+
+    // Bring snoozed conversations back when their time comes
+    let snooze_worker = snooze::spawn_snooze_worker(storehaus.clone(), ws_manager.clone(), bot_manager.clone());
+
     // Create HTTP API router
     let app = create_router(
         config.clone(),
@@ -137,6 +169,11 @@ async fn main() -> Result<()> {
 
     info!("Server shutting down gracefully...");
 
+    queue_worker.abort();
+    sla_worker.abort();
+    idle_worker.abort();
+    snooze_worker.abort();
+
     // Stop bot manager
     if let Err(e) = bot_manager.stop().await {
         error!("Error stopping bot manager: {}", e);
@@ -0,0 +1,53 @@
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+/// Audit log model
+/// Represents one administrative action on a record
+#[model]
+#[table(name = "audit_log")]
+pub struct AuditEntry {
+    /// Entry ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Action, e.g. "conversation.deleted"
+    #[field(create)]
+    pub action: String,
+
+    /// Kind of record acted on, e.g. "conversation"
+    #[field(create)]
+    pub entity_type: String,
+
+    /// ID of the record acted on
+    #[field(create)]
+    pub entity_id: String,
+
+    /// User who performed the action
+    #[field(create)]
+    pub user_id: Option<Uuid>,
+
+    /// Action details (JSON string)
+    #[field(create)]
+    pub details: Option<String>,
+}
+
+impl AuditEntry {
+    /// Create a new audit entry
+    pub fn create(
+        action: &str,
+        entity_type: &str,
+        entity_id: String,
+        user_id: Option<Uuid>,
+        details: Option<serde_json::Value>,
+    ) -> Self {
+        Self::new(
+            Uuid::new_v4(),
+            action.to_string(),
+            entity_type.to_string(),
+            entity_id,
+            user_id,
+            details.map(|d| d.to_string()),
+        )
+    }
+}
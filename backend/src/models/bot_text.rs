@@ -0,0 +1,47 @@
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+/// Bot text override model
+/// Represents an admin-edited bot message for a specific locale
+#[model]
+#[table(name = "bot_texts")]
+pub struct BotText {
+    /// Override ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Locale key ("en", "ru")
+    #[field(create)]
+    pub locale: String,
+
+    /// Bot message key (see `BotMessages::KEYS`)
+    #[field(create)]
+    pub message_key: String,
+
+    /// Overridden message text
+    #[field(create, update)]
+    pub value: String,
+
+    /// User (admin) who last changed the text
+    #[field(create, update)]
+    pub updated_by: Option<Uuid>,
+}
+
+impl BotText {
+    /// Create a new bot text override
+    pub fn create(
+        locale: String,
+        message_key: String,
+        value: String,
+        updated_by: Option<Uuid>,
+    ) -> Self {
+        Self::new(
+            Uuid::new_v4(),
+            locale,
+            message_key,
+            value,
+            updated_by,
+        )
+    }
+}
@@ -11,6 +11,8 @@ pub enum ConversationStatus {
     #[default]
     Waiting,
     Active,
+    /// Put aside by an operator until `snoozed_until` or the next customer message
+    Snoozed,
     Closed,
 }
 
@@ -19,6 +21,7 @@ impl ConversationStatus {
         match self {
             Self::Waiting => "waiting",
             Self::Active => "active",
+            Self::Snoozed => "snoozed",
             Self::Closed => "closed",
         }
     }
@@ -35,12 +38,60 @@ impl From<String> for ConversationStatus {
         match s.as_str() {
             "waiting" => Self::Waiting,
             "active" => Self::Active,
+            "snoozed" => Self::Snoozed,
             "closed" => Self::Closed,
             _ => Self::Waiting,
         }
     }
 }
 
+/// Conversation priority; the queue and the conversation list put higher
+/// priorities first
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::Type, Default)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum ConversationPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl ConversationPriority {
+    /// All priorities, lowest first
+    pub const ALL: [Self; 4] = [Self::Low, Self::Normal, Self::High, Self::Urgent];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+            Self::Urgent => "urgent",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == value)
+    }
+
+    /// Sort rank, higher is more urgent
+    pub fn rank(&self) -> i32 {
+        match self {
+            Self::Low => 0,
+            Self::Normal => 1,
+            Self::High => 2,
+            Self::Urgent => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for ConversationPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Conversation model
 /// Represents a dialog between a telegram user and an user
 #[model]
@@ -67,12 +118,116 @@ pub struct Conversation {
     #[field(create, update)]
     pub last_message_at: Option<DateTime<Utc>>,
 
-    /// Unread message count (for user)
+    /// Conversation-wide unread counter, kept for existing data; API
+    /// responses report the caller's own count from their read cursor
     #[field(create, update)]
     pub unread_count: i32,
+
+    /// Deep-link source that led to this conversation
+    #[field(create, update)]
+    pub source: Option<String>,
+
+    /// Queue position the customer was last told about
+    #[field(create, update)]
+    pub queue_position_notified: Option<i32>,
+
+    /// SLA policy applied to this conversation
+    #[field(create, update)]
+    pub sla_policy_id: Option<Uuid>,
+
+    /// First operator reply timestamp
+    #[field(create, update)]
+    pub first_responded_at: Option<DateTime<Utc>>,
+
+    /// Deadline for the first operator reply (None when met or not tracked)
+    #[field(create, update)]
+    pub first_response_due_at: Option<DateTime<Utc>>,
+
+    /// Deadline for answering the latest customer message
+    #[field(create, update)]
+    pub next_response_due_at: Option<DateTime<Utc>>,
+
+    /// Deadline for closing the conversation
+    #[field(create, update)]
+    pub resolution_due_at: Option<DateTime<Utc>>,
+
+    /// When the customer was last asked whether they are still there
+    #[field(create, update)]
+    pub idle_nudged_at: Option<DateTime<Utc>>,
+
+    /// Why the conversation was closed (e.g. "auto_closed"); None for manual closes
+    #[field(create, update)]
+    pub closed_reason: Option<String>,
+
+    /// When the conversation was last closed
+    #[field(create, update)]
+    pub closed_at: Option<DateTime<Utc>>,
+
+    /// Operator who closed the conversation (None for automatic closes)
+    #[field(create, update)]
+    pub closed_by: Option<Uuid>,
+
+    /// Disposition code picked when closing (e.g. "resolved")
+    #[field(create, update)]
+    pub disposition: Option<String>,
+
+    /// How the issue was resolved, written when closing
+    #[field(create, update)]
+    pub resolution_summary: Option<String>,
+
+    /// Earlier closed conversation of the same customer
+    #[field(create)]
+    pub previous_conversation_id: Option<Uuid>,
+
+    /// When a snoozed conversation wakes up
+    #[field(create, update)]
+    pub snoozed_until: Option<DateTime<Utc>>,
+
+    /// Operator who snoozed the conversation
+    #[field(create, update)]
+    pub snoozed_by: Option<Uuid>,
+
+    /// User who moved the conversation to the trash (`__deleted_at__` holds when)
+    #[field(create, update)]
+    pub deleted_by: Option<Uuid>,
+
+    /// Priority, set by operators or priority rules
+    #[field(create, update)]
+    pub priority: ConversationPriority,
+
+    /// Category (e.g. "billing"), set by operators or priority rules
+    #[field(create, update)]
+    pub category: Option<String>,
 }
 
 impl Conversation {
+    /// `closed_reason` of conversations closed for inactivity
+    pub const AUTO_CLOSED_REASON: &'static str = "auto_closed";
+
+    /// `closed_reason` of conversations merged into another one
+    pub const MERGED_REASON: &'static str = "merged";
+
+    /// Create a new waiting conversation for a telegram user
+    pub fn new_waiting(telegram_user_id: i64, source: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            telegram_user_id,
+            status: ConversationStatus::Waiting,
+            last_message_at: Some(Utc::now()),
+            source,
+            ..Default::default()
+        }
+    }
+
+    /// Forget how the conversation was last closed (on reopening)
+    pub fn clear_close(&mut self) {
+        self.closed_reason = None;
+        self.closed_at = None;
+        self.closed_by = None;
+        self.disposition = None;
+        self.resolution_summary = None;
+    }
+
     /// Get status as enum (now just returns a reference)
     pub fn get_status(&self) -> &ConversationStatus {
         &self.status
@@ -93,8 +248,21 @@ impl Conversation {
         self.status == ConversationStatus::Waiting
     }
 
+    /// Check if conversation is snoozed
+    pub fn is_snoozed(&self) -> bool {
+        self.status == ConversationStatus::Snoozed
+    }
+
     /// Check if conversation is closed
     pub fn is_closed(&self) -> bool {
         self.status == ConversationStatus::Closed
     }
+
+    /// Earliest running SLA deadline
+    pub fn sla_due_at(&self) -> Option<DateTime<Utc>> {
+        [self.first_response_due_at, self.next_response_due_at, self.resolution_due_at]
+            .into_iter()
+            .flatten()
+            .min()
+    }
 }
\ No newline at end of file
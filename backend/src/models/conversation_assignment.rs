@@ -0,0 +1,53 @@
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+/// Conversation assignment history model
+/// Represents one change of the operator responsible for a conversation
+#[model]
+#[table(name = "conversation_assignments")]
+pub struct ConversationAssignment {
+    /// Record ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Conversation ID
+    #[field(create)]
+    pub conversation_id: Uuid,
+
+    /// Previous operator (if any)
+    #[field(create)]
+    pub from_user_id: Option<Uuid>,
+
+    /// New operator (None when unassigned)
+    #[field(create)]
+    pub to_user_id: Option<Uuid>,
+
+    /// User who made the change (None for system changes)
+    #[field(create)]
+    pub assigned_by: Option<Uuid>,
+
+    /// Reason or handoff note
+    #[field(create)]
+    pub reason: Option<String>,
+}
+
+impl ConversationAssignment {
+    /// Create a new assignment record
+    pub fn create(
+        conversation_id: Uuid,
+        from_user_id: Option<Uuid>,
+        to_user_id: Option<Uuid>,
+        assigned_by: Option<Uuid>,
+        reason: Option<String>,
+    ) -> Self {
+        Self::new(
+            Uuid::new_v4(),
+            conversation_id,
+            from_user_id,
+            to_user_id,
+            assigned_by,
+            reason,
+        )
+    }
+}
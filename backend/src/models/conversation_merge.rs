@@ -0,0 +1,47 @@
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+/// Conversation merge history model
+/// Represents one conversation folded into another
+#[model]
+#[table(name = "conversation_merges")]
+pub struct ConversationMerge {
+    /// Record ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Conversation whose messages were moved (closed by the merge)
+    #[field(create)]
+    pub source_conversation_id: Uuid,
+
+    /// Conversation that received the messages
+    #[field(create)]
+    pub target_conversation_id: Uuid,
+
+    /// Number of messages moved
+    #[field(create)]
+    pub message_count: i64,
+
+    /// User who merged the conversations
+    #[field(create)]
+    pub merged_by: Option<Uuid>,
+}
+
+impl ConversationMerge {
+    /// Create a new merge record
+    pub fn create(
+        source_conversation_id: Uuid,
+        target_conversation_id: Uuid,
+        message_count: i64,
+        merged_by: Option<Uuid>,
+    ) -> Self {
+        Self::new(
+            Uuid::new_v4(),
+            source_conversation_id,
+            target_conversation_id,
+            message_count,
+            merged_by,
+        )
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+/// Custom field value type
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type, Default)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum CustomFieldType {
+    #[default]
+    Text,
+    Number,
+    /// Calendar date ("YYYY-MM-DD")
+    Date,
+    /// One of the field's options
+    Enum,
+    /// http(s) URL
+    Url,
+}
+
+impl CustomFieldType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Text => "text",
+            Self::Number => "number",
+            Self::Date => "date",
+            Self::Enum => "enum",
+            Self::Url => "url",
+        }
+    }
+}
+
+impl std::fmt::Display for CustomFieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Custom field model
+/// Represents an admin-defined profile field for telegram users
+#[model]
+#[table(name = "custom_fields")]
+pub struct CustomField {
+    /// Field ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Machine name used in the API ("account_id"), unique
+    #[field(create)]
+    pub key: String,
+
+    /// Display name
+    #[field(create, update)]
+    pub label: String,
+
+    /// Value type
+    #[field(create)]
+    pub field_type: CustomFieldType,
+
+    /// Allowed values of enum fields (JSON array of strings)
+    #[field(create, update)]
+    pub options: Option<String>,
+
+    /// Operators cannot clear the value once set
+    #[field(create, update)]
+    pub required: bool,
+
+    /// Display order
+    #[field(create, update)]
+    pub position: i32,
+}
+
+impl CustomField {
+    /// Create a new custom field
+    pub fn create(
+        key: String,
+        label: String,
+        field_type: CustomFieldType,
+        options: Vec<String>,
+        required: bool,
+        position: i32,
+    ) -> Self {
+        let options = (!options.is_empty()).then(|| serde_json::to_string(&options).unwrap_or_default());
+        Self::new(Uuid::new_v4(), key, label, field_type, options, required, position)
+    }
+
+    /// Allowed values of an enum field
+    pub fn parsed_options(&self) -> Vec<String> {
+        self.options
+            .as_deref()
+            .and_then(|o| serde_json::from_str(o).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Custom field value model
+/// A telegram user's value of a custom field, stored in normalized text form
+#[model]
+#[table(name = "custom_field_values")]
+pub struct CustomFieldValue {
+    /// Record ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Telegram user ID
+    #[field(create)]
+    pub telegram_user_id: i64,
+
+    /// Custom field ID
+    #[field(create)]
+    pub field_id: Uuid,
+
+    /// Normalized value
+    #[field(create, update)]
+    pub value: String,
+
+    /// User who last changed the value
+    #[field(create, update)]
+    pub updated_by: Option<Uuid>,
+}
+
+impl CustomFieldValue {
+    /// Create a new value
+    pub fn create(telegram_user_id: i64, field_id: Uuid, value: String, updated_by: Option<Uuid>) -> Self {
+        Self::new(Uuid::new_v4(), telegram_user_id, field_id, value, updated_by)
+    }
+}
@@ -0,0 +1,28 @@
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+/// Customer profile model
+/// Groups the Telegram accounts of one customer
+#[model]
+#[table(name = "customer_profiles")]
+pub struct CustomerProfile {
+    /// Profile ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Display name (optional, defaults to the first linked account)
+    #[field(create, update)]
+    pub name: Option<String>,
+
+    /// User who linked the accounts
+    #[field(create)]
+    pub created_by: Option<Uuid>,
+}
+
+impl CustomerProfile {
+    /// Create a new customer profile
+    pub fn create(name: Option<String>, created_by: Option<Uuid>) -> Self {
+        Self::new(Uuid::new_v4(), name, created_by)
+    }
+}
@@ -0,0 +1,44 @@
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+/// Disposition code model
+/// Admin-managed wrap-up code recording how a conversation was resolved
+#[model]
+#[table(name = "disposition_codes")]
+pub struct DispositionCode {
+    /// Code ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Stable code stored on conversations (e.g. "resolved")
+    #[field(create)]
+    pub code: String,
+
+    /// Display label
+    #[field(create, update)]
+    pub label: String,
+
+    /// Display order
+    #[field(create, update)]
+    pub position: i32,
+
+    /// Inactive codes stay on closed conversations but cannot be picked
+    #[field(create, update)]
+    pub is_active: bool,
+}
+
+impl DispositionCode {
+    /// Codes created on first start
+    pub const DEFAULTS: [(&'static str, &'static str); 4] = [
+        ("resolved", "Resolved"),
+        ("duplicate", "Duplicate"),
+        ("spam", "Spam"),
+        ("escalated", "Escalated"),
+    ];
+
+    /// Create a new active disposition code
+    pub fn create(code: String, label: String, position: i32) -> Self {
+        Self::new(Uuid::new_v4(), code, label, position, true)
+    }
+}
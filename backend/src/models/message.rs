@@ -1,6 +1,34 @@
+use serde::{Deserialize, Serialize};
 use storehaus::prelude::*;
 use uuid::Uuid;
 
+/// Message kind enum
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type, Default)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum MessageKind {
+    /// Regular chat message between customer and operator
+    #[default]
+    Chat,
+    /// Internal note visible to operators only, never sent to Telegram
+    Note,
+}
+
+impl MessageKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Chat => "chat",
+            Self::Note => "note",
+        }
+    }
+}
+
+impl std::fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Message model
 /// Represents a message in a conversation
 #[model]
@@ -54,6 +82,14 @@ pub struct Message {
     /// Duration in seconds (for audio/video/voice)
     #[field(create)]
     pub duration: Option<i32>,
+
+    /// Message kind (chat message or internal note)
+    #[field(create)]
+    pub kind: MessageKind,
+
+    /// Operator who wrote the message or note
+    #[field(create)]
+    pub user_id: Option<Uuid>,
 }
 
 impl Message {
@@ -76,6 +112,8 @@ impl Message {
             None,
             None,
             None,
+            MessageKind::Chat,
+            None,
         )
     }
 
@@ -100,6 +138,8 @@ impl Message {
             None,
             None,
             None,
+            MessageKind::Chat,
+            None,
         )
     }
 
@@ -128,6 +168,8 @@ impl Message {
             file_size,
             mime_type,
             duration,
+            MessageKind::Chat,
+            None,
         )
     }
 
@@ -149,6 +191,42 @@ impl Message {
             None,
             None,
             None,
+            MessageKind::Chat,
+            None,
         )
     }
+
+    /// Create an internal note written by an operator
+    pub fn internal_note(
+        conversation_id: Uuid,
+        content: String,
+        user_id: Uuid,
+    ) -> Self {
+        Self::new(
+            Uuid::new_v4(),
+            conversation_id,
+            true,
+            content,
+            true,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            MessageKind::Note,
+            Some(user_id),
+        )
+    }
+
+    /// Check if message is an internal note
+    pub fn is_note(&self) -> bool {
+        self.kind == MessageKind::Note
+    }
+
+    /// Check if message is an operator reply sent to the customer
+    pub fn is_operator_reply(&self) -> bool {
+        self.from_user && !self.is_note()
+    }
 }
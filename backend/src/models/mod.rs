@@ -1,18 +1,44 @@
 //! Database models
 
+mod audit_entry;
+mod bot_text;
 mod conversation;
+mod conversation_assignment;
+mod conversation_merge;
+mod custom_field;
+mod customer_profile;
+mod disposition_code;
 mod message;
 mod message_edit;
+mod priority_rule;
+mod read_cursor;
 mod user;
 mod telegram_user;
 mod template;
 mod settings;
+mod sla;
+mod tag;
 
 // Re-exports
-pub use conversation::{Conversation, ConversationStatus};
-pub use message::Message;
+pub use audit_entry::AuditEntry;
+pub use bot_text::BotText;
+pub use conversation::{Conversation, ConversationPriority, ConversationStatus};
+pub use conversation_assignment::ConversationAssignment;
+pub use conversation_merge::ConversationMerge;
+pub use custom_field::{CustomField, CustomFieldType, CustomFieldValue};
+pub use customer_profile::CustomerProfile;
+pub use disposition_code::DispositionCode;
+pub use message::{Message, MessageKind};
 pub use message_edit::MessageEdit;
-pub use user::{User, UserResponse, UserSettings};
+pub use priority_rule::PriorityRule;
+pub use read_cursor::ReadCursor;
+pub use user::{OperatorStatus, User, UserResponse, UserSettings};
 pub use telegram_user::TelegramUser;
 pub use template::MessageTemplate;
-pub use settings::{Setting, SettingsResponse, UpdateSettingsRequest};
\ No newline at end of file
+pub use sla::{BusinessHours, SlaEvent, SlaEventKind, SlaPolicy, SlaTimer};
+pub use tag::{ConversationTag, Tag, TelegramUserTag};
+pub use settings::{
+    CollisionSettings, IdleSettings, NotificationSettings, OperatorNameDisplay, ReopenSettings,
+    ReplyLockMode, RoutingSettings, RoutingStrategy, Setting, SettingsResponse, UpdateSettingsRequest,
+    WrapUpSettings,
+};
\ No newline at end of file
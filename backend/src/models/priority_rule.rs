@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use super::ConversationPriority;
+
+/// Priority rule model
+/// Admin-defined rule raising the priority or setting the category of
+/// conversations that match all of its conditions
+#[model]
+#[table(name = "priority_rules")]
+pub struct PriorityRule {
+    /// Rule ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Rule name
+    #[field(create, update)]
+    pub name: String,
+
+    /// Only customers with this tag, e.g. "VIP" (optional)
+    #[field(create, update)]
+    pub tag_id: Option<Uuid>,
+
+    /// Only conversations from this deep-link source / topic (optional)
+    #[field(create, update)]
+    pub source: Option<String>,
+
+    /// Customer messages containing any of these words (JSON array, optional)
+    #[field(create, update)]
+    pub keywords: Option<String>,
+
+    /// Priority given to matching conversations
+    #[field(create, update)]
+    pub priority: ConversationPriority,
+
+    /// Category given to matching conversations without one (optional)
+    #[field(create, update)]
+    pub category: Option<String>,
+
+    /// Evaluation order; the first matching rule with a category sets it
+    #[field(create, update)]
+    pub position: i32,
+
+    /// Is rule active
+    #[field(create, update)]
+    pub is_active: bool,
+
+    /// User (admin) who created the rule
+    #[field(create)]
+    pub created_by: Option<Uuid>,
+}
+
+impl PriorityRule {
+    /// Create a new priority rule
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(
+        name: String,
+        tag_id: Option<Uuid>,
+        source: Option<String>,
+        keywords: Vec<String>,
+        priority: ConversationPriority,
+        category: Option<String>,
+        position: i32,
+        created_by: Option<Uuid>,
+    ) -> Self {
+        let keywords = (!keywords.is_empty()).then(|| serde_json::json!(keywords).to_string());
+
+        Self::new(
+            Uuid::new_v4(),
+            name,
+            tag_id,
+            source,
+            keywords,
+            priority,
+            category,
+            position,
+            true,
+            created_by,
+        )
+    }
+
+    /// Parse keywords
+    pub fn parsed_keywords(&self) -> Vec<String> {
+        self.keywords
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether the rule has any condition; a rule without one would match
+    /// every conversation
+    pub fn has_conditions(&self) -> bool {
+        self.tag_id.is_some() || self.source.is_some() || self.keywords.is_some()
+    }
+}
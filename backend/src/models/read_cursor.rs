@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+/// Read cursor model
+/// The last message an operator has read in a conversation
+#[model]
+#[table(name = "read_cursors")]
+pub struct ReadCursor {
+    /// Record ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Operator
+    #[field(create)]
+    pub user_id: Uuid,
+
+    /// Conversation ID
+    #[field(create)]
+    pub conversation_id: Uuid,
+
+    /// Last read message
+    #[field(create, update)]
+    pub last_read_message_id: Uuid,
+
+    /// Creation time of the last read message
+    #[field(create, update)]
+    pub last_read_at: DateTime<Utc>,
+}
+
+impl ReadCursor {
+    /// Create a cursor at a message
+    pub fn create(user_id: Uuid, conversation_id: Uuid, message_id: Uuid, message_created_at: DateTime<Utc>) -> Self {
+        Self::new(Uuid::new_v4(), user_id, conversation_id, message_id, message_created_at)
+    }
+
+    /// Whether the message at (`created_at`, `message_id`) comes after the cursor
+    pub fn is_before(&self, created_at: DateTime<Utc>, message_id: Uuid) -> bool {
+        (self.last_read_at, self.last_read_message_id) < (created_at, message_id)
+    }
+}
@@ -18,12 +18,238 @@ pub struct Setting {
 impl Setting {
     /// Telegram bot token setting key
     pub const TELEGRAM_BOT_TOKEN: &'static str = "telegram_bot_token";
+
+    /// Send the `operator_assigned` message to customers
+    pub const NOTIFY_OPERATOR_ASSIGNED: &'static str = "notify_operator_assigned";
+
+    /// Send the `conversation_closed` message to customers
+    pub const NOTIFY_CONVERSATION_CLOSED: &'static str = "notify_conversation_closed";
+
+    /// Tell waiting customers their queue position and estimated wait
+    pub const NOTIFY_QUEUE_POSITION: &'static str = "notify_queue_position";
+
+    /// How operator names are shown to customers
+    pub const OPERATOR_NAME_DISPLAY: &'static str = "operator_name_display";
+
+    /// Automatic routing strategy for new conversations
+    pub const ROUTING_STRATEGY: &'static str = "routing_strategy";
+
+    /// Default per-operator limit of concurrent conversations
+    pub const ROUTING_MAX_CONCURRENT_CHATS: &'static str = "routing_max_concurrent_chats";
+
+    /// Last operator picked by round-robin routing
+    pub const ROUTING_LAST_ASSIGNED: &'static str = "routing_last_assigned";
+
+    /// Hours without a customer reply before the "are you still there?" nudge
+    pub const IDLE_NUDGE_HOURS: &'static str = "idle_nudge_hours";
+
+    /// Hours after the nudge before an idle conversation is closed
+    pub const IDLE_CLOSE_HOURS: &'static str = "idle_close_hours";
+
+    /// Minutes after closing during which a new customer message reopens the conversation
+    pub const REOPEN_WINDOW_MINUTES: &'static str = "reopen_window_minutes";
+
+    /// What happens when an operator replies while another one holds the reply lock
+    pub const REPLY_LOCK_MODE: &'static str = "reply_lock_mode";
+
+    /// Operators must pick a disposition code when closing a conversation
+    pub const DISPOSITION_REQUIRED: &'static str = "disposition_required";
+
+    /// Operators must write a resolution summary when closing a conversation
+    pub const RESOLUTION_SUMMARY_REQUIRED: &'static str = "resolution_summary_required";
+}
+
+/// How an operator is presented to customers in bot messages
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OperatorNameDisplay {
+    /// Operator's account name
+    #[default]
+    RealName,
+    /// Per-operator alias from user settings (falls back to the anonymous label)
+    Alias,
+    /// Localized anonymous label, e.g. "An operator"
+    Anonymous,
+}
+
+impl OperatorNameDisplay {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperatorNameDisplay::RealName => "real_name",
+            OperatorNameDisplay::Alias => "alias",
+            OperatorNameDisplay::Anonymous => "anonymous",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "real_name" => Some(OperatorNameDisplay::RealName),
+            "alias" => Some(OperatorNameDisplay::Alias),
+            "anonymous" => Some(OperatorNameDisplay::Anonymous),
+            _ => None,
+        }
+    }
+}
+
+/// How new conversations are assigned to operators
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingStrategy {
+    /// No automatic assignment, operators pick conversations themselves
+    #[default]
+    Manual,
+    /// Rotate through available operators
+    RoundRobin,
+    /// Operator with the fewest open conversations
+    LeastLoaded,
+    /// Best skill match on customer language and topic, then least loaded
+    Skills,
+}
+
+impl RoutingStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoutingStrategy::Manual => "manual",
+            RoutingStrategy::RoundRobin => "round_robin",
+            RoutingStrategy::LeastLoaded => "least_loaded",
+            RoutingStrategy::Skills => "skills",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "manual" => Some(RoutingStrategy::Manual),
+            "round_robin" => Some(RoutingStrategy::RoundRobin),
+            "least_loaded" => Some(RoutingStrategy::LeastLoaded),
+            "skills" => Some(RoutingStrategy::Skills),
+            _ => None,
+        }
+    }
+}
+
+/// Automatic routing settings
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutingSettings {
+    pub routing_strategy: RoutingStrategy,
+    pub routing_max_concurrent_chats: u32,
+}
+
+impl Default for RoutingSettings {
+    fn default() -> Self {
+        Self {
+            routing_strategy: RoutingStrategy::Manual,
+            routing_max_concurrent_chats: 5,
+        }
+    }
+}
+
+/// Idle conversation settings
+#[derive(Debug, Clone, Serialize)]
+pub struct IdleSettings {
+    /// 0 disables nudging and auto-closing
+    pub idle_nudge_hours: u32,
+    /// 0 nudges without closing
+    pub idle_close_hours: u32,
+}
+
+impl Default for IdleSettings {
+    fn default() -> Self {
+        Self {
+            idle_nudge_hours: 0,
+            idle_close_hours: 24,
+        }
+    }
+}
+
+/// Conversation reopen settings
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ReopenSettings {
+    /// 0 always starts a new conversation
+    pub reopen_window_minutes: u32,
+}
+
+/// What `send_message` does when another operator holds the reply lock on
+/// a conversation assigned to someone else
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplyLockMode {
+    /// Presence is shown, replies are never blocked
+    #[default]
+    Off,
+    /// Reply is rejected unless the operator confirms with `force`
+    Warn,
+    /// Reply is rejected
+    Enforce,
+}
+
+impl ReplyLockMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReplyLockMode::Off => "off",
+            ReplyLockMode::Warn => "warn",
+            ReplyLockMode::Enforce => "enforce",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(ReplyLockMode::Off),
+            "warn" => Some(ReplyLockMode::Warn),
+            "enforce" => Some(ReplyLockMode::Enforce),
+            _ => None,
+        }
+    }
+}
+
+/// Operator collision settings
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CollisionSettings {
+    pub reply_lock_mode: ReplyLockMode,
+}
+
+/// Wrap-up settings for closing conversations
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct WrapUpSettings {
+    pub disposition_required: bool,
+    pub resolution_summary_required: bool,
+}
+
+/// Customer notification settings
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationSettings {
+    pub notify_operator_assigned: bool,
+    pub notify_conversation_closed: bool,
+    pub notify_queue_position: bool,
+    pub operator_name_display: OperatorNameDisplay,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            notify_operator_assigned: true,
+            notify_conversation_closed: true,
+            notify_queue_position: true,
+            operator_name_display: OperatorNameDisplay::RealName,
+        }
+    }
 }
 
 /// Request to update settings
 #[derive(Debug, Deserialize)]
 pub struct UpdateSettingsRequest {
     pub telegram_bot_token: Option<String>,
+    pub notify_operator_assigned: Option<bool>,
+    pub notify_conversation_closed: Option<bool>,
+    pub notify_queue_position: Option<bool>,
+    pub operator_name_display: Option<OperatorNameDisplay>,
+    pub routing_strategy: Option<RoutingStrategy>,
+    pub routing_max_concurrent_chats: Option<u32>,
+    pub idle_nudge_hours: Option<u32>,
+    pub idle_close_hours: Option<u32>,
+    pub reopen_window_minutes: Option<u32>,
+    pub reply_lock_mode: Option<ReplyLockMode>,
+    pub disposition_required: Option<bool>,
+    pub resolution_summary_required: Option<bool>,
 }
 
 /// Response with settings (without sensitive data for non-admins)
@@ -31,11 +257,31 @@ pub struct UpdateSettingsRequest {
 pub struct SettingsResponse {
     pub has_telegram_bot_token: bool,
     pub telegram_bot_token_preview: Option<String>,
+    #[serde(flatten)]
+    pub notifications: NotificationSettings,
+    #[serde(flatten)]
+    pub routing: RoutingSettings,
+    #[serde(flatten)]
+    pub idle: IdleSettings,
+    #[serde(flatten)]
+    pub reopen: ReopenSettings,
+    #[serde(flatten)]
+    pub collision: CollisionSettings,
+    #[serde(flatten)]
+    pub wrap_up: WrapUpSettings,
 }
 
 impl SettingsResponse {
-    /// Create response from optional bot token
-    pub fn from_bot_token(token: Option<String>) -> Self {
+    /// Create response from optional bot token and the typed settings groups
+    pub fn from_bot_token(
+        token: Option<String>,
+        notifications: NotificationSettings,
+        routing: RoutingSettings,
+        idle: IdleSettings,
+        reopen: ReopenSettings,
+        collision: CollisionSettings,
+        wrap_up: WrapUpSettings,
+    ) -> Self {
         let (has_token, preview) = if let Some(ref token) = token {
             let preview = if token.len() > 10 {
                 format!("{}...{}", &token[..4], &token[token.len()-4..])
@@ -50,6 +296,12 @@ impl SettingsResponse {
         Self {
             has_telegram_bot_token: has_token,
             telegram_bot_token_preview: preview,
+            notifications,
+            routing,
+            idle,
+            reopen,
+            collision,
+            wrap_up,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,237 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+/// SLA policy model
+/// Represents admin-defined response and resolution targets
+#[model]
+#[table(name = "sla_policies")]
+pub struct SlaPolicy {
+    /// Policy ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Policy name
+    #[field(create, update)]
+    pub name: String,
+
+    /// Minutes until the first operator reply
+    #[field(create, update)]
+    pub first_response_minutes: Option<i32>,
+
+    /// Minutes until the operator answers each later customer message
+    #[field(create, update)]
+    pub next_response_minutes: Option<i32>,
+
+    /// Minutes until the conversation is closed
+    #[field(create, update)]
+    pub resolution_minutes: Option<i32>,
+
+    /// Minutes before a deadline when operators are warned
+    #[field(create, update)]
+    pub warning_minutes: i32,
+
+    /// Only conversations with this tag (optional)
+    #[field(create, update)]
+    pub tag_id: Option<Uuid>,
+
+    /// Only conversations from this deep-link source / topic (optional)
+    #[field(create, update)]
+    pub source: Option<String>,
+
+    /// Business hours (JSON string); targets count only working time
+    #[field(create, update)]
+    pub business_hours: Option<String>,
+
+    /// Is policy active
+    #[field(create, update)]
+    pub is_active: bool,
+
+    /// User (admin) who created the policy
+    #[field(create)]
+    pub created_by: Option<Uuid>,
+}
+
+impl SlaPolicy {
+    /// Create a new SLA policy
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(
+        name: String,
+        first_response_minutes: Option<i32>,
+        next_response_minutes: Option<i32>,
+        resolution_minutes: Option<i32>,
+        warning_minutes: i32,
+        tag_id: Option<Uuid>,
+        source: Option<String>,
+        business_hours: Option<String>,
+        created_by: Option<Uuid>,
+    ) -> Self {
+        Self::new(
+            Uuid::new_v4(),
+            name,
+            first_response_minutes,
+            next_response_minutes,
+            resolution_minutes,
+            warning_minutes,
+            tag_id,
+            source,
+            business_hours,
+            true,
+            created_by,
+        )
+    }
+
+    /// Parse business hours, `None` meaning round-the-clock
+    pub fn parsed_business_hours(&self) -> Option<BusinessHours> {
+        self.business_hours
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+    }
+
+    /// Target in minutes for a timer
+    pub fn target_minutes(&self, timer: SlaTimer) -> Option<i32> {
+        match timer {
+            SlaTimer::FirstResponse => self.first_response_minutes,
+            SlaTimer::NextResponse => self.next_response_minutes,
+            SlaTimer::Resolution => self.resolution_minutes,
+        }
+    }
+}
+
+/// Working hours used by SLA timers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BusinessHours {
+    /// Offset from UTC in minutes (e.g. 180 for UTC+3)
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+    /// Working days, 1 = Monday ... 7 = Sunday
+    pub days: Vec<u32>,
+    /// Start of the working day, local time ("09:00")
+    pub start: NaiveTime,
+    /// End of the working day, local time ("18:00")
+    pub end: NaiveTime,
+}
+
+impl BusinessHours {
+    /// Check that days, hours and offset make sense
+    pub fn is_valid(&self) -> bool {
+        !self.days.is_empty()
+            && self.days.iter().all(|d| (1..=7).contains(d))
+            && self.start < self.end
+            && self.utc_offset_minutes.abs() <= 14 * 60
+    }
+}
+
+/// SLA timer kind
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SlaTimer {
+    FirstResponse,
+    NextResponse,
+    Resolution,
+}
+
+impl SlaTimer {
+    pub const ALL: [SlaTimer; 3] = [SlaTimer::FirstResponse, SlaTimer::NextResponse, SlaTimer::Resolution];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SlaTimer::FirstResponse => "first_response",
+            SlaTimer::NextResponse => "next_response",
+            SlaTimer::Resolution => "resolution",
+        }
+    }
+}
+
+/// SLA event kind
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SlaEventKind {
+    Warning,
+    Breach,
+}
+
+impl SlaEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SlaEventKind::Warning => "warning",
+            SlaEventKind::Breach => "breach",
+        }
+    }
+}
+
+/// SLA event model
+/// Represents a warning or breach of one conversation timer
+#[model]
+#[table(name = "sla_events")]
+pub struct SlaEvent {
+    /// Event ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Conversation ID
+    #[field(create)]
+    pub conversation_id: Uuid,
+
+    /// Policy the timer belonged to
+    #[field(create)]
+    pub policy_id: Uuid,
+
+    /// Timer ("first_response", "next_response", "resolution")
+    #[field(create)]
+    pub timer: String,
+
+    /// Event kind ("warning" or "breach")
+    #[field(create)]
+    pub kind: String,
+
+    /// Deadline of the timer
+    #[field(create)]
+    pub due_at: DateTime<Utc>,
+
+    /// Operator assigned at the time (if any)
+    #[field(create)]
+    pub user_id: Option<Uuid>,
+}
+
+impl SlaEvent {
+    /// Create a new SLA event
+    pub fn create(
+        conversation_id: Uuid,
+        policy_id: Uuid,
+        timer: SlaTimer,
+        kind: SlaEventKind,
+        due_at: DateTime<Utc>,
+        user_id: Option<Uuid>,
+    ) -> Self {
+        Self::new(
+            Uuid::new_v4(),
+            conversation_id,
+            policy_id,
+            timer.as_str().to_string(),
+            kind.as_str().to_string(),
+            due_at,
+            user_id,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_business_hours_is_valid() {
+        let hours: BusinessHours =
+            serde_json::from_str(r#"{"utc_offset_minutes": 180, "days": [1, 2, 3, 4, 5], "start": "09:00", "end": "18:00"}"#)
+                .unwrap();
+        assert!(hours.is_valid());
+
+        assert!(!BusinessHours { days: vec![0], ..hours.clone() }.is_valid());
+        assert!(!BusinessHours { start: hours.end, end: hours.start, ..hours.clone() }.is_valid());
+        assert!(!BusinessHours { days: vec![], ..hours }.is_valid());
+    }
+}
@@ -0,0 +1,129 @@
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+/// Tag model
+/// Represents an admin-managed label for conversations and telegram users
+#[model]
+#[table(name = "tags")]
+pub struct Tag {
+    /// Tag ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Tag name (unique, case-insensitive)
+    #[field(create, update)]
+    pub name: String,
+
+    /// Display color ("#RRGGBB")
+    #[field(create, update)]
+    pub color: String,
+
+    /// Description (optional)
+    #[field(create, update)]
+    pub description: Option<String>,
+
+    /// User (admin) who created the tag
+    #[field(create)]
+    pub created_by: Option<Uuid>,
+}
+
+impl Tag {
+    /// Default color for tags created without one
+    pub const DEFAULT_COLOR: &'static str = "#6B7280";
+
+    /// Create a new tag
+    pub fn create(
+        name: String,
+        color: String,
+        description: Option<String>,
+        created_by: Option<Uuid>,
+    ) -> Self {
+        Self::new(
+            Uuid::new_v4(),
+            name,
+            color,
+            description,
+            created_by,
+        )
+    }
+
+    /// Check that a color is a "#RRGGBB" hex value
+    pub fn is_valid_color(color: &str) -> bool {
+        color.len() == 7
+            && color.starts_with('#')
+            && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+    }
+}
+
+/// Conversation tag link model
+#[model]
+#[table(name = "conversation_tags")]
+pub struct ConversationTag {
+    /// Link ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Conversation ID
+    #[field(create)]
+    pub conversation_id: Uuid,
+
+    /// Tag ID
+    #[field(create)]
+    pub tag_id: Uuid,
+
+    /// User who added the tag
+    #[field(create)]
+    pub added_by: Option<Uuid>,
+}
+
+impl ConversationTag {
+    /// Create a new conversation tag link
+    pub fn create(conversation_id: Uuid, tag_id: Uuid, added_by: Option<Uuid>) -> Self {
+        Self::new(Uuid::new_v4(), conversation_id, tag_id, added_by)
+    }
+}
+
+/// Telegram user tag link model
+#[model]
+#[table(name = "telegram_user_tags")]
+pub struct TelegramUserTag {
+    /// Link ID
+    #[primary_key]
+    #[field(create)]
+    pub id: Uuid,
+
+    /// Telegram user ID
+    #[field(create)]
+    pub telegram_user_id: i64,
+
+    /// Tag ID
+    #[field(create)]
+    pub tag_id: Uuid,
+
+    /// User who added the tag
+    #[field(create)]
+    pub added_by: Option<Uuid>,
+}
+
+impl TelegramUserTag {
+    /// Create a new telegram user tag link
+    pub fn create(telegram_user_id: i64, tag_id: Uuid, added_by: Option<Uuid>) -> Self {
+        Self::new(Uuid::new_v4(), telegram_user_id, tag_id, added_by)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_color() {
+        assert!(Tag::is_valid_color("#FF0000"));
+        assert!(Tag::is_valid_color(Tag::DEFAULT_COLOR));
+        assert!(!Tag::is_valid_color("FF0000"));
+        assert!(!Tag::is_valid_color("#FFF"));
+        assert!(!Tag::is_valid_color("#GG0000"));
+    }
+}
@@ -1,4 +1,5 @@
 use storehaus::prelude::*;
+use uuid::Uuid;
 
 /// Telegram user model
 /// Represents a user who interacts with the bot
@@ -33,6 +34,22 @@ pub struct TelegramUser {
     /// Is user blocked from using the bot
     #[field(create, update)]
     pub is_blocked: bool,
+
+    /// Deep-link source of the first `/start` (first touch)
+    #[field(create, update)]
+    pub first_source: Option<String>,
+
+    /// Deep-link source of the latest `/start` (last touch)
+    #[field(create, update)]
+    pub last_source: Option<String>,
+
+    /// Verified customer ID from our own systems (signed deep link)
+    #[field(create, update)]
+    pub external_customer_id: Option<String>,
+
+    /// Customer profile shared with the customer's other Telegram accounts
+    #[field(create, update)]
+    pub customer_profile_id: Option<Uuid>,
 }
 
 impl TelegramUser {
@@ -64,6 +64,56 @@ pub struct UserSettings {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub telegram_notifications_user_id: Option<String>,
+
+    /// Name shown to customers instead of the real name (set by admins)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator_alias: Option<String>,
+
+    /// Availability set by the operator (used by automatic routing)
+    #[serde(default)]
+    pub status: OperatorStatus,
+
+    /// Routing skills: languages ("ru", "en") and topics ("billing"), lowercase
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skills: Vec<String>,
+
+    /// Teams the operator belongs to (set by admins); teammates can read
+    /// each other's conversations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub teams: Vec<String>,
+
+    /// Maximum concurrent conversations (falls back to the system default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_chats: Option<u32>,
+}
+
+/// Operator availability
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OperatorStatus {
+    Online,
+    Away,
+    #[default]
+    Offline,
+}
+
+impl OperatorStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperatorStatus::Online => "online",
+            OperatorStatus::Away => "away",
+            OperatorStatus::Offline => "offline",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "online" => Some(OperatorStatus::Online),
+            "away" => Some(OperatorStatus::Away),
+            "offline" => Some(OperatorStatus::Offline),
+            _ => None,
+        }
+    }
 }
 
 fn default_theme() -> String {
@@ -86,11 +136,24 @@ impl Default for UserSettings {
             notifications_enabled: true,
             notification_sound_enabled: true,
             telegram_notifications_user_id: None,
+            operator_alias: None,
+            status: OperatorStatus::Offline,
+            skills: Vec::new(),
+            teams: Vec::new(),
+            max_concurrent_chats: None,
         }
     }
 }
 
 impl User {
+    /// Parse user settings, falling back to defaults
+    pub fn parsed_settings(&self) -> UserSettings {
+        self.settings
+            .as_deref()
+            .and_then(|s| serde_json::from_str::<UserSettings>(s).ok())
+            .unwrap_or_default()
+    }
+
     /// Check if user is online (last seen within 5 minutes)
     pub fn is_online(&self) -> bool {
         if let Some(last_seen) = self.last_seen_at {
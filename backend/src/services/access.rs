@@ -0,0 +1,251 @@
+//! Conversation access control
+//!
+//! Admins can do anything. Operators read and write conversations assigned
+//! to them and unassigned ones (the shared queue), and can read the
+//! conversations of their teammates (operators sharing a team). Deleting is
+//! admin-only. Inactive users and users without operator access get nothing.
+
+use anyhow::Result;
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::models::{Conversation, User};
+
+/// What a user wants to do with a conversation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// View the conversation, its messages, notes, tags and history
+    Read,
+    /// Reply, assign, transfer, change status, tag
+    Write,
+    /// Remove the conversation
+    Delete,
+}
+
+/// Whether two users share a team
+pub fn same_team(a: &User, b: &User) -> bool {
+    let a_teams = a.parsed_settings().teams;
+    let b_teams = b.parsed_settings().teams;
+    a_teams.iter().any(|team| b_teams.contains(team))
+}
+
+/// Whether `user` may `access` a conversation assigned to `assignee`
+/// (`None` for unassigned conversations or when the assignee is gone)
+pub fn can_access(user: &User, conversation: &Conversation, assignee: Option<&User>, access: Access) -> bool {
+    allowed(user, conversation.user_id, assignee, access)
+}
+
+fn allowed(user: &User, assignee_id: Option<Uuid>, assignee: Option<&User>, access: Access) -> bool {
+    if !user.has_operator_access() {
+        return false;
+    }
+    if user.has_admin_access() {
+        return true;
+    }
+
+    match (access, assignee_id) {
+        (Access::Delete, _) => false,
+        (_, None) => true,
+        (_, Some(assignee_id)) if assignee_id == user.id => true,
+        (Access::Read, Some(_)) => assignee.is_some_and(|assignee| same_team(user, assignee)),
+        (Access::Write, Some(_)) => false,
+    }
+}
+
+/// Load `user_id` and the conversation's assignee and check access
+pub async fn check(storehaus: &StoreHaus, user_id: Uuid, conversation: &Conversation, access: Access) -> Result<bool> {
+    let user_store = storehaus.get_store::<GenericStore<User>>("users")?;
+
+    let Some(user) = user_store.get_by_id(&user_id).await? else {
+        return Ok(false);
+    };
+
+    let assignee = match conversation.user_id {
+        Some(assignee_id) if assignee_id != user.id && !user.has_admin_access() => {
+            user_store.get_by_id(&assignee_id).await?
+        }
+        _ => None,
+    };
+
+    Ok(can_access(&user, conversation, assignee.as_ref(), access))
+}
+
+/// Users whose conversations `user` can read (`user` included)
+pub async fn teammate_ids(storehaus: &StoreHaus, user: &User) -> Result<Vec<Uuid>> {
+    let mut ids = vec![user.id];
+    if user.parsed_settings().teams.is_empty() {
+        return Ok(ids);
+    }
+
+    let user_store = storehaus.get_store::<GenericStore<User>>("users")?;
+    let users = user_store.find(QueryBuilder::new()).await?;
+    ids.extend(users.iter().filter(|other| other.id != user.id && same_team(user, other)).map(|other| other.id));
+
+    Ok(ids)
+}
+
+/// Users among `users` who can read a conversation assigned to `assignee_id`
+pub fn readers(users: &[User], assignee_id: Option<Uuid>) -> Vec<Uuid> {
+    let assignee = assignee_id.and_then(|id| users.iter().find(|u| u.id == id));
+    users
+        .iter()
+        .filter(|user| allowed(user, assignee_id, assignee, Access::Read))
+        .map(|user| user.id)
+        .collect()
+}
+
+/// Users who can read a conversation. Removed conversations keep their
+/// readers; purged ones are left to admins.
+pub async fn conversation_readers(storehaus: &StoreHaus, conversation_id: Uuid) -> Result<Vec<Uuid>> {
+    let assignee: Option<Option<Uuid>> = sqlx::query_scalar("SELECT user_id FROM conversations WHERE id = $1")
+        .bind(conversation_id)
+        .fetch_optional(storehaus.pool())
+        .await?;
+
+    let user_store = storehaus.get_store::<GenericStore<User>>("users")?;
+    let users = user_store.find(QueryBuilder::new()).await?;
+
+    Ok(match assignee {
+        Some(assignee_id) => readers(&users, assignee_id),
+        None => users.iter().filter(|u| u.has_admin_access()).map(|u| u.id).collect(),
+    })
+}
+
+/// Conversations a user can read, as a condition on the assignee
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadScope {
+    /// Every conversation (admins)
+    All,
+    /// Unassigned conversations and those assigned to these users
+    Assignees(Vec<Uuid>),
+    /// None at all (inactive users and users without operator access)
+    Nothing,
+}
+
+impl ReadScope {
+    /// Whether a conversation assigned to `assignee` is readable
+    pub fn allows(&self, assignee: Option<Uuid>) -> bool {
+        match self {
+            ReadScope::All => true,
+            ReadScope::Assignees(ids) => assignee.is_none_or(|id| ids.contains(&id)),
+            ReadScope::Nothing => false,
+        }
+    }
+}
+
+/// Which conversations `user` can read
+pub async fn read_scope(storehaus: &StoreHaus, user: &User) -> Result<ReadScope> {
+    if !user.has_operator_access() {
+        return Ok(ReadScope::Nothing);
+    }
+    if user.has_admin_access() {
+        return Ok(ReadScope::All);
+    }
+
+    Ok(ReadScope::Assignees(teammate_ids(storehaus, user).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::UserSettings;
+
+    fn operator(teams: &[&str]) -> User {
+        let settings = UserSettings {
+            teams: teams.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        };
+        User {
+            id: Uuid::new_v4(),
+            is_operator: true,
+            is_active: true,
+            settings: Some(serde_json::to_string(&settings).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    fn assigned_to(user: Option<&User>) -> Conversation {
+        Conversation {
+            user_id: user.map(|u| u.id),
+            ..Conversation::new_waiting(1, None)
+        }
+    }
+
+    #[test]
+    fn test_assignment_and_queue() {
+        let alice = operator(&[]);
+        let bob = operator(&[]);
+
+        let own = assigned_to(Some(&alice));
+        assert!(can_access(&alice, &own, Some(&alice), Access::Read));
+        assert!(can_access(&alice, &own, Some(&alice), Access::Write));
+        assert!(!can_access(&alice, &own, Some(&alice), Access::Delete));
+
+        let others = assigned_to(Some(&bob));
+        assert!(!can_access(&alice, &others, Some(&bob), Access::Read));
+        assert!(!can_access(&alice, &others, Some(&bob), Access::Write));
+
+        let queued = assigned_to(None);
+        assert!(can_access(&alice, &queued, None, Access::Read));
+        assert!(can_access(&alice, &queued, None, Access::Write));
+        assert!(!can_access(&alice, &queued, None, Access::Delete));
+    }
+
+    #[test]
+    fn test_teams() {
+        let alice = operator(&["billing"]);
+        let bob = operator(&["billing", "vip"]);
+        let carol = operator(&["vip"]);
+
+        let bobs = assigned_to(Some(&bob));
+        assert!(can_access(&alice, &bobs, Some(&bob), Access::Read));
+        assert!(!can_access(&alice, &bobs, Some(&bob), Access::Write));
+
+        let carols = assigned_to(Some(&carol));
+        assert!(!can_access(&alice, &carols, Some(&carol), Access::Read));
+    }
+
+    #[test]
+    fn test_read_scope() {
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+
+        let scope = ReadScope::Assignees(vec![alice]);
+        assert!(scope.allows(None));
+        assert!(scope.allows(Some(alice)));
+        assert!(!scope.allows(Some(bob)));
+
+        assert!(ReadScope::All.allows(Some(bob)));
+        assert!(!ReadScope::Nothing.allows(None));
+    }
+
+    #[test]
+    fn test_admin_and_inactive() {
+        let bob = operator(&[]);
+        let conversation = assigned_to(Some(&bob));
+
+        let admin = User { is_admin: true, ..operator(&[]) };
+        assert!(can_access(&admin, &conversation, Some(&bob), Access::Write));
+        assert!(can_access(&admin, &conversation, Some(&bob), Access::Delete));
+
+        let inactive = User { is_active: false, ..bob.clone() };
+        assert!(!can_access(&inactive, &conversation, Some(&bob), Access::Read));
+
+        let not_operator = User { is_operator: false, ..bob.clone() };
+        assert!(!can_access(&not_operator, &conversation, Some(&bob), Access::Read));
+    }
+
+    #[test]
+    fn test_readers() {
+        let alice = operator(&["support"]);
+        let bob = operator(&["support"]);
+        let carol = operator(&["sales"]);
+        let admin = User { is_admin: true, ..operator(&[]) };
+        let users = vec![alice.clone(), bob.clone(), carol.clone(), admin.clone()];
+
+        let readers_of_alice = readers(&users, Some(alice.id));
+        assert_eq!(readers_of_alice, vec![alice.id, bob.id, admin.id]);
+
+        assert_eq!(readers(&users, None).len(), 4);
+    }
+}
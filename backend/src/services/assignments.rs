@@ -0,0 +1,157 @@
+//! Conversation assignment history
+//!
+//! Every assignment, transfer or unassignment writes a row to
+//! `conversation_assignments`. Analytics use the history to attribute
+//! operator replies (and response times) to whoever handled the
+//! conversation at the time, not to its current assignee.
+
+use anyhow::Result;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::models::{ConversationAssignment, MessageKind};
+
+/// Record an assignment change
+pub async fn record_assignment(
+    storehaus: &StoreHaus,
+    conversation_id: Uuid,
+    from_user_id: Option<Uuid>,
+    to_user_id: Option<Uuid>,
+    assigned_by: Option<Uuid>,
+    reason: Option<String>,
+) -> Result<ConversationAssignment> {
+    let store = storehaus.get_store::<GenericStore<ConversationAssignment>>("conversation_assignments")?;
+
+    let record = ConversationAssignment::create(conversation_id, from_user_id, to_user_id, assigned_by, reason);
+    let record = store.create(record, Some(vec!["assignment".to_string()])).await?;
+
+    Ok(record)
+}
+
+/// Assignment history of a conversation, oldest first
+pub async fn history(storehaus: &StoreHaus, conversation_id: Uuid) -> Result<Vec<ConversationAssignment>> {
+    let store = storehaus.get_store::<GenericStore<ConversationAssignment>>("conversation_assignments")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("conversation_id", json!(conversation_id)))
+        .order_by("__created_at__", SortOrder::Asc);
+
+    Ok(store.find(query).await?)
+}
+
+/// IDs of conversations that were ever assigned to `user_id`
+pub async fn conversations_handled_by(storehaus: &StoreHaus, user_id: Uuid) -> Result<HashSet<Uuid>> {
+    let store = storehaus.get_store::<GenericStore<ConversationAssignment>>("conversation_assignments")?;
+
+    let query = QueryBuilder::new().filter(QueryFilter::eq("to_user_id", json!(user_id)));
+
+    Ok(store.find(query).await?.into_iter().map(|r| r.conversation_id).collect())
+}
+
+/// Per-operator activity
+#[derive(Debug, Default)]
+pub struct OperatorActivity {
+    /// Conversations the operator was assigned to or replied in
+    pub conversations: i64,
+    /// Replies sent to customers
+    pub messages_sent: i64,
+    /// Average seconds from the first unanswered customer message to the reply
+    pub average_response_time: Option<f64>,
+}
+
+/// Operator activity across all conversations, or of `user_id` only.
+///
+/// A reply is attributed to its author if known, otherwise to whoever was
+/// assigned when it was sent: the latest assignment record before it, the
+/// `from_user_id` of the first record for replies older than the history,
+/// or the current assignee when the conversation has no history (created
+/// before assignments were recorded).
+///
+/// A reply's response time runs from the first customer message after the
+/// previous reply; `turn` counts the replies before each message, so the
+/// customer messages a reply answers share its turn.
+pub async fn operator_activity(
+    storehaus: &StoreHaus,
+    user_id: Option<Uuid>,
+) -> Result<HashMap<Uuid, OperatorActivity>> {
+    let sql = format!(
+        "WITH timeline AS ( \
+             SELECT m.conversation_id, m.from_user, m.user_id, m.__created_at__ AS at, \
+                    c.user_id AS current_user_id, \
+                    COUNT(*) FILTER (WHERE m.from_user) OVER ( \
+                        PARTITION BY m.conversation_id ORDER BY m.__created_at__, m.id \
+                        ROWS BETWEEN UNBOUNDED PRECEDING AND 1 PRECEDING \
+                    ) AS turn \
+             FROM messages m \
+             JOIN conversations c ON c.id = m.conversation_id AND c.__deleted_at__ IS NULL \
+             WHERE m.__deleted_at__ IS NULL AND m.kind <> '{note}' \
+         ), \
+         waits AS ( \
+             SELECT conversation_id, turn, MIN(at) AS since FROM timeline \
+             WHERE NOT from_user GROUP BY conversation_id, turn \
+         ), \
+         replies AS ( \
+             SELECT t.conversation_id, \
+                    COALESCE(t.user_id, CASE \
+                        WHEN latest.found THEN latest.to_user_id \
+                        WHEN earliest.found THEN earliest.from_user_id \
+                        ELSE t.current_user_id \
+                    END) AS operator_id, \
+                    FLOOR(EXTRACT(EPOCH FROM t.at - w.since)) AS seconds \
+             FROM timeline t \
+             LEFT JOIN waits w ON w.conversation_id = t.conversation_id AND w.turn = t.turn \
+             LEFT JOIN LATERAL ( \
+                 SELECT TRUE AS found, ca.to_user_id FROM conversation_assignments ca \
+                 WHERE ca.conversation_id = t.conversation_id AND ca.__deleted_at__ IS NULL \
+                     AND ca.__created_at__ <= t.at \
+                 ORDER BY ca.__created_at__ DESC LIMIT 1 \
+             ) latest ON TRUE \
+             LEFT JOIN LATERAL ( \
+                 SELECT TRUE AS found, ca.from_user_id FROM conversation_assignments ca \
+                 WHERE ca.conversation_id = t.conversation_id AND ca.__deleted_at__ IS NULL \
+                 ORDER BY ca.__created_at__ ASC LIMIT 1 \
+             ) earliest ON TRUE \
+             WHERE t.from_user \
+         ), \
+         handled AS ( \
+             SELECT user_id AS operator_id, id AS conversation_id FROM conversations \
+             WHERE __deleted_at__ IS NULL AND user_id IS NOT NULL \
+             UNION \
+             SELECT ca.to_user_id, ca.conversation_id FROM conversation_assignments ca \
+             JOIN conversations c ON c.id = ca.conversation_id AND c.__deleted_at__ IS NULL \
+             WHERE ca.__deleted_at__ IS NULL AND ca.to_user_id IS NOT NULL \
+             UNION \
+             SELECT operator_id, conversation_id FROM replies WHERE operator_id IS NOT NULL \
+         ) \
+         SELECT h.operator_id, h.conversations, \
+                COALESCE(r.messages_sent, 0) AS messages_sent, r.average_response_time \
+         FROM ( \
+             SELECT operator_id, COUNT(*) AS conversations FROM handled GROUP BY operator_id \
+         ) h \
+         LEFT JOIN ( \
+             SELECT operator_id, COUNT(*) AS messages_sent, \
+                    (AVG(seconds) FILTER (WHERE seconds > 0))::float8 AS average_response_time \
+             FROM replies WHERE operator_id IS NOT NULL GROUP BY operator_id \
+         ) r ON r.operator_id = h.operator_id \
+         WHERE $1::uuid IS NULL OR h.operator_id = $1",
+        note = MessageKind::Note.as_str()
+    );
+
+    let rows = sqlx::query(&sql).bind(user_id).fetch_all(storehaus.pool()).await?;
+
+    let mut activity = HashMap::with_capacity(rows.len());
+    for row in rows {
+        activity.insert(
+            row.try_get("operator_id")?,
+            OperatorActivity {
+                conversations: row.try_get("conversations")?,
+                messages_sent: row.try_get("messages_sent")?,
+                average_response_time: row.try_get("average_response_time")?,
+            },
+        );
+    }
+
+    Ok(activity)
+}
@@ -0,0 +1,58 @@
+//! Audit log of administrative actions
+
+use anyhow::Result;
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::models::AuditEntry;
+
+/// Conversation moved to the trash
+pub const CONVERSATION_DELETED: &str = "conversation.deleted";
+
+/// Conversation restored from the trash
+pub const CONVERSATION_RESTORED: &str = "conversation.restored";
+
+/// Conversation and its history removed for good
+pub const CONVERSATION_PURGED: &str = "conversation.purged";
+
+/// Record an action on a conversation
+pub async fn record_conversation(
+    storehaus: &StoreHaus,
+    action: &str,
+    conversation_id: Uuid,
+    user_id: Option<Uuid>,
+    details: Option<serde_json::Value>,
+) -> Result<AuditEntry> {
+    let store = storehaus.get_store::<GenericStore<AuditEntry>>("audit_log")?;
+
+    let entry = AuditEntry::create(action, "conversation", conversation_id.to_string(), user_id, details);
+
+    Ok(store.create(entry, None).await?)
+}
+
+/// Record an action on a conversation inside a transaction, so the entry is
+/// only kept if the action is
+pub async fn record_conversation_in(
+    conn: &mut sqlx::PgConnection,
+    action: &str,
+    conversation_id: Uuid,
+    user_id: Option<Uuid>,
+    details: Option<serde_json::Value>,
+) -> Result<()> {
+    let entry = AuditEntry::create(action, "conversation", conversation_id.to_string(), user_id, details);
+
+    sqlx::query(
+        "INSERT INTO audit_log (id, action, entity_type, entity_id, user_id, details) \
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(entry.id)
+    .bind(&entry.action)
+    .bind(&entry.entity_type)
+    .bind(&entry.entity_id)
+    .bind(entry.user_id)
+    .bind(&entry.details)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! Admin-editable bot texts
+//!
+//! Overrides are stored in the `bot_texts` table and mirrored into the
+//! in-memory cache in `l10n`, so changes apply without a restart.
+
+use anyhow::Result;
+use serde_json::json;
+use std::collections::HashMap;
+use storehaus::prelude::*;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::l10n;
+use crate::models::BotText;
+
+/// Load all overrides from the database into the l10n cache
+pub async fn load_overrides(storehaus: &StoreHaus) -> Result<usize> {
+    let store = storehaus.get_store::<GenericStore<BotText>>("bot_texts")?;
+    let texts = store.find(QueryBuilder::new()).await?;
+
+    let count = texts.len();
+    let mut overrides: HashMap<String, HashMap<String, String>> = HashMap::new();
+    for text in texts {
+        overrides
+            .entry(text.locale)
+            .or_default()
+            .insert(text.message_key, text.value);
+    }
+
+    l10n::replace_overrides(overrides);
+    info!("Loaded {} bot text overrides", count);
+
+    Ok(count)
+}
+
+/// Create or update an override and apply it live. Upserts on the unique
+/// `(locale, message_key)` index, so concurrent saves never leave duplicates.
+pub async fn save_override(
+    storehaus: &StoreHaus,
+    locale: &str,
+    key: &str,
+    value: String,
+    updated_by: Option<Uuid>,
+) -> Result<BotText> {
+    let text = sqlx::query_as::<_, BotText>(
+        "INSERT INTO bot_texts (id, locale, message_key, value, updated_by) \
+         VALUES ($1, $2, $3, $4, $5) \
+         ON CONFLICT (locale, message_key) DO UPDATE \
+             SET value = EXCLUDED.value, \
+                 updated_by = EXCLUDED.updated_by, \
+                 __updated_at__ = NOW(), \
+                 __deleted_at__ = NULL \
+         RETURNING *",
+    )
+    .bind(Uuid::new_v4())
+    .bind(locale)
+    .bind(key)
+    .bind(&value)
+    .bind(updated_by)
+    .fetch_one(storehaus.pool())
+    .await?;
+
+    l10n::set_override(locale, key, value);
+
+    Ok(text)
+}
+
+/// Delete an override, reverting to the locale file default
+pub async fn delete_override(storehaus: &StoreHaus, locale: &str, key: &str) -> Result<bool> {
+    let store = storehaus.get_store::<GenericStore<BotText>>("bot_texts")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("locale", json!(locale)))
+        .filter(QueryFilter::eq("message_key", json!(key)));
+
+    let existed = match store.find_one(query).await? {
+        Some(existing) => {
+            store.delete(&existing.id).await?;
+            true
+        }
+        None => false,
+    };
+
+    l10n::remove_override(locale, key);
+
+    Ok(existed)
+}
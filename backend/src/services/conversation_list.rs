@@ -0,0 +1,338 @@
+//! Conversation list queries
+//!
+//! Filtering, search and pagination happen in one SQL query over
+//! `conversations` joined with `telegram_users`, so the cost of a page does
+//! not grow with the number of conversations. Search is a case-insensitive
+//! substring match on the customer's name, username, Telegram ID and
+//! custom field values, served by the trigram indexes created in `db::init`. The
+//! default order puts urgent conversations first and, within a priority, the
+//! ones waiting longest. Pages ordered by the last message use keyset
+//! pagination: the cursor is the position of the last row of the previous
+//! page.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::db::sql::{self, like_pattern, search_terms, Bind, Conditions};
+use crate::models::{ConversationPriority, ConversationStatus};
+
+/// Conversation list filters
+#[derive(Debug, Clone, Default)]
+pub struct ListFilter {
+    /// Exact status; when `None` closed conversations are left out
+    pub status: Option<String>,
+    /// Keep snoozed conversations when no status is given
+    pub include_snoozed: bool,
+    /// Assigned operator
+    pub user_id: Option<Uuid>,
+    /// Deep link source
+    pub source: Option<String>,
+    /// Conversations with any of these tags
+    pub tag_ids: Option<Vec<Uuid>>,
+    /// Matched against the customer's name, username, ID and custom
+    /// field values
+    pub search: Option<String>,
+    /// Customers whose custom field (ID) has this normalized value
+    pub custom_field: Option<(Uuid, String)>,
+    /// Exact priority
+    pub priority: Option<ConversationPriority>,
+    /// Exact category
+    pub category: Option<String>,
+}
+
+/// Conversation list order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListSort {
+    /// Highest priority first, then longest waiting (oldest conversation)
+    Priority,
+    /// Most recent message first
+    LastMessage,
+    /// Nearest SLA deadline first, conversations without running timers last
+    TimeToBreach,
+}
+
+impl ListSort {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "priority" => Some(ListSort::Priority),
+            "last_message" => Some(ListSort::LastMessage),
+            "time_to_breach" => Some(ListSort::TimeToBreach),
+            _ => None,
+        }
+    }
+}
+
+/// Position of a row in the last message order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub last_message_at: Option<DateTime<Utc>>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    /// Encode as `<last message timestamp in microseconds or "-">:<id>`
+    pub fn encode(&self) -> String {
+        let position = match self.last_message_at {
+            Some(at) => at.timestamp_micros().to_string(),
+            None => "-".to_string(),
+        };
+        format!("{}:{}", position, self.id)
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let (position, id) = value.split_once(':')?;
+        let last_message_at = match position {
+            "-" => None,
+            micros => Some(DateTime::from_timestamp_micros(micros.parse().ok()?)?),
+        };
+        Some(Self {
+            last_message_at,
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
+
+/// Where a page starts
+#[derive(Debug, Clone, Copy)]
+pub enum PageStart {
+    Offset(i64),
+    After(Cursor),
+}
+
+/// One page of conversation IDs in list order
+#[derive(Debug, Clone)]
+pub struct ListPage {
+    pub ids: Vec<Uuid>,
+    /// Conversations matching the filter across all pages
+    pub total: i64,
+    /// Cursor of the next page when this one is full
+    pub next_cursor: Option<Cursor>,
+}
+
+fn conditions(filter: &ListFilter) -> Conditions {
+    let mut conditions = Conditions::default();
+    conditions.push("c.__deleted_at__ IS NULL".to_string());
+
+    match &filter.status {
+        Some(status) => {
+            let status = conditions.bind(Bind::Text(status.clone()));
+            conditions.push(format!("c.status = {}", status));
+        }
+        None => {
+            conditions.push(format!("c.status <> '{}'", ConversationStatus::Closed.as_str()));
+            if !filter.include_snoozed {
+                conditions.push(format!("c.status <> '{}'", ConversationStatus::Snoozed.as_str()));
+            }
+        }
+    }
+
+    if let Some(user_id) = filter.user_id {
+        let user_id = conditions.bind(Bind::Id(user_id));
+        conditions.push(format!("c.user_id = {}", user_id));
+    }
+
+    if let Some(source) = &filter.source {
+        let source = conditions.bind(Bind::Text(source.clone()));
+        conditions.push(format!("c.source = {}", source));
+    }
+
+    if let Some(tag_ids) = &filter.tag_ids {
+        let tag_ids = conditions.bind(Bind::Ids(tag_ids.clone()));
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM conversation_tags ct \
+             WHERE ct.conversation_id = c.id AND ct.tag_id = ANY({}) AND ct.__deleted_at__ IS NULL)",
+            tag_ids
+        ));
+    }
+
+    if let Some((field_id, value)) = &filter.custom_field {
+        let field_id = conditions.bind(Bind::Id(*field_id));
+        let value = conditions.bind(Bind::Text(value.clone()));
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM custom_field_values cfv WHERE cfv.telegram_user_id = c.telegram_user_id \
+             AND cfv.field_id = {} AND cfv.value = {} AND cfv.__deleted_at__ IS NULL)",
+            field_id, value
+        ));
+    }
+
+    if let Some(priority) = filter.priority {
+        conditions.push(format!("c.priority = '{}'", priority.as_str()));
+    }
+
+    if let Some(category) = &filter.category {
+        let category = conditions.bind(Bind::Text(category.clone()));
+        conditions.push(format!("c.category = {}", category));
+    }
+
+    for term in filter.search.as_deref().map(search_terms).unwrap_or_default() {
+        let pattern = conditions.bind(Bind::Text(like_pattern(&term)));
+        conditions.push(format!(
+            "(tu.first_name ILIKE {p} OR tu.last_name ILIKE {p} OR tu.username ILIKE {p} \
+             OR tu.id::text ILIKE {p} \
+             OR EXISTS (SELECT 1 FROM custom_field_values cfv WHERE cfv.telegram_user_id = tu.id \
+                 AND cfv.value ILIKE {p} AND cfv.__deleted_at__ IS NULL))",
+            p = pattern
+        ));
+    }
+
+    conditions
+}
+
+/// SQL expression ranking a priority column like `ConversationPriority::rank`
+fn priority_rank_sql(column: &str) -> String {
+    let cases: Vec<String> = ConversationPriority::ALL
+        .iter()
+        .map(|p| format!("WHEN '{}' THEN {}", p.as_str(), p.rank()))
+        .collect();
+    format!(
+        "CASE {} {} ELSE {} END",
+        column,
+        cases.join(" "),
+        ConversationPriority::default().rank()
+    )
+}
+
+/// Rows after `cursor` in `last_message_at DESC NULLS LAST, id DESC` order
+fn after_cursor(conditions: &mut Conditions, cursor: &Cursor) {
+    let id = conditions.bind(Bind::Id(cursor.id));
+    let clause = match cursor.last_message_at {
+        Some(at) => {
+            let at = conditions.bind(Bind::Time(at));
+            format!(
+                "(c.last_message_at < {at} OR (c.last_message_at = {at} AND c.id < {id}) \
+                 OR c.last_message_at IS NULL)",
+                at = at,
+                id = id
+            )
+        }
+        None => format!("(c.last_message_at IS NULL AND c.id < {})", id),
+    };
+    conditions.push(clause);
+}
+
+/// A page of at most `limit` conversation IDs matching `filter`. Cursors
+/// only apply to the last message order.
+pub async fn page(
+    storehaus: &StoreHaus,
+    filter: &ListFilter,
+    sort: ListSort,
+    start: PageStart,
+    limit: i64,
+) -> Result<ListPage> {
+    let mut conditions = conditions(filter);
+
+    let from = if filter.search.is_some() {
+        "conversations c JOIN telegram_users tu ON tu.id = c.telegram_user_id"
+    } else {
+        "conversations c"
+    };
+
+    let count_sql = format!("SELECT COUNT(*) FROM {} WHERE {}", from, conditions.sql());
+    let total: i64 = sql::apply(sqlx::query(&count_sql), conditions.binds())
+        .fetch_one(storehaus.pool())
+        .await?
+        .try_get(0)?;
+
+    let offset = match start {
+        PageStart::Offset(offset) => offset.max(0),
+        PageStart::After(cursor) => {
+            if sort != ListSort::LastMessage {
+                return Err(anyhow!("Cursor pagination requires the last message order"));
+            }
+            after_cursor(&mut conditions, &cursor);
+            0
+        }
+    };
+
+    let order = match sort {
+        ListSort::Priority => format!("{} DESC, c.__created_at__ ASC, c.id ASC", priority_rank_sql("c.priority")),
+        ListSort::LastMessage => "c.last_message_at DESC NULLS LAST, c.id DESC".to_string(),
+        ListSort::TimeToBreach => "LEAST(c.first_response_due_at, c.next_response_due_at, c.resolution_due_at) \
+             ASC NULLS LAST, c.last_message_at DESC NULLS LAST, c.id DESC"
+            .to_string(),
+    };
+
+    let limit = limit.max(1);
+    let mut page_sql = format!(
+        "SELECT c.id, c.last_message_at FROM {} WHERE {} ORDER BY {} LIMIT {}",
+        from,
+        conditions.sql(),
+        order,
+        limit
+    );
+    if offset > 0 {
+        page_sql.push_str(&format!(" OFFSET {}", offset));
+    }
+
+    let rows = sql::apply(sqlx::query(&page_sql), conditions.binds())
+        .fetch_all(storehaus.pool())
+        .await?;
+
+    let mut positions = Vec::with_capacity(rows.len());
+    for row in rows {
+        positions.push(Cursor {
+            id: row.try_get("id")?,
+            last_message_at: row.try_get("last_message_at")?,
+        });
+    }
+
+    let page_full = positions.len() as i64 == limit;
+    let next_cursor = match sort {
+        ListSort::LastMessage if page_full => positions.last().copied(),
+        _ => None,
+    };
+
+    Ok(ListPage {
+        ids: positions.into_iter().map(|p| p.id).collect(),
+        total,
+        next_cursor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor {
+            last_message_at: DateTime::from_timestamp_micros(1_700_000_000_123_456),
+            id: Uuid::new_v4(),
+        };
+        assert_eq!(Cursor::parse(&cursor.encode()), Some(cursor));
+
+        let without_messages = Cursor {
+            last_message_at: None,
+            id: Uuid::new_v4(),
+        };
+        assert_eq!(Cursor::parse(&without_messages.encode()), Some(without_messages));
+
+        assert_eq!(Cursor::parse("garbage"), None);
+        assert_eq!(Cursor::parse("123:not-a-uuid"), None);
+    }
+
+    #[test]
+    fn test_search_conditions() {
+        let filter = ListFilter {
+            status: Some("active".to_string()),
+            search: Some("john smith".to_string()),
+            ..Default::default()
+        };
+        let conditions = conditions(&filter);
+        assert_eq!(conditions.binds().len(), 3);
+        assert!(conditions.sql().contains("c.status = $1"));
+        assert!(conditions.sql().contains("tu.username ILIKE $3"));
+    }
+
+    #[test]
+    fn test_priority_order() {
+        assert_eq!(
+            priority_rank_sql("c.priority"),
+            "CASE c.priority WHEN 'low' THEN 0 WHEN 'normal' THEN 1 WHEN 'high' THEN 2 WHEN 'urgent' THEN 3 \
+             ELSE 1 END"
+        );
+        assert_eq!(ListSort::parse("priority"), Some(ListSort::Priority));
+    }
+}
@@ -0,0 +1,259 @@
+//! Conversation lifecycle shared by API handlers and background workers
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use storehaus::prelude::*;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::models::{Conversation, ConversationMerge, ConversationStatus, ConversationTag};
+use crate::services::dispositions::WrapUp;
+use crate::services::{customer_notifications, routing, sla};
+use crate::telegram::BotManager;
+use crate::websocket::{WebSocketEvent, WebSocketManager};
+
+/// Close a conversation: stop SLA timers, broadcast `ConversationClosed`,
+/// free the operator's slot for the queue and send the customer the
+/// `conversation_closed` text. `reason` is stored as `closed_reason`,
+/// `wrap_up` records the operator's disposition (None for automatic closes).
+pub async fn close(
+    storehaus: &Arc<StoreHaus>,
+    ws_manager: &Arc<WebSocketManager>,
+    bot_manager: &Arc<BotManager>,
+    mut conversation: Conversation,
+    reason: Option<&str>,
+    wrap_up: Option<WrapUp>,
+) -> Result<Conversation> {
+    let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+
+    let was_closed = conversation.status == ConversationStatus::Closed;
+
+    conversation.status = ConversationStatus::Closed;
+    conversation.closed_reason = reason.map(str::to_string);
+    conversation.closed_at = Some(Utc::now());
+    conversation.closed_by = wrap_up.as_ref().map(|w| w.closed_by);
+    conversation.disposition = wrap_up.as_ref().and_then(|w| w.disposition.clone());
+    conversation.resolution_summary = wrap_up.and_then(|w| w.resolution_summary);
+    sla::stop_timers(&mut conversation);
+
+    let id = conversation.id;
+    let conversation = conversation_store
+        .update(&id, conversation, Some(vec!["closed".to_string()]))
+        .await?;
+
+    let ws_event = WebSocketEvent::ConversationClosed {
+        conversation_id: conversation.id,
+    };
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast ConversationClosed event: {}", e);
+    }
+
+    if !was_closed {
+        // The operator has a free slot now
+        routing::spawn_drain_queue(storehaus.clone(), ws_manager.clone(), bot_manager.clone());
+        spawn_conversation_closed_notification(storehaus.clone(), bot_manager.clone(), conversation.clone());
+    }
+
+    Ok(conversation)
+}
+
+/// Whether a closed conversation can still be reopened at `now`
+pub fn can_reopen(conversation: &Conversation, window_minutes: u32, now: DateTime<Utc>) -> bool {
+    if window_minutes == 0 || !conversation.is_closed() {
+        return false;
+    }
+
+    conversation
+        .closed_at
+        .is_some_and(|closed_at| now - closed_at <= Duration::minutes(window_minutes as i64))
+}
+
+/// Reopen a closed conversation for a new customer message. It goes back to
+/// its previous operator, or to the queue when it had none; SLA timers
+/// restart from `now`.
+pub async fn reopen(
+    storehaus: &StoreHaus,
+    ws_manager: &WebSocketManager,
+    mut conversation: Conversation,
+    now: DateTime<Utc>,
+) -> Result<Conversation> {
+    let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+
+    conversation.status = if conversation.user_id.is_some() {
+        ConversationStatus::Active
+    } else {
+        ConversationStatus::Waiting
+    };
+    conversation.clear_close();
+    conversation.idle_nudged_at = None;
+    conversation.queue_position_notified = None;
+
+    let policy = sla::policy_for(storehaus, &conversation).await?;
+    sla::start_timers(&mut conversation, policy.as_ref(), now);
+
+    let id = conversation.id;
+    let conversation = conversation_store
+        .update(&id, conversation, Some(vec!["reopened".to_string()]))
+        .await?;
+
+    let ws_event = WebSocketEvent::ConversationReopened {
+        conversation_id: conversation.id,
+        status: conversation.status.as_str().to_string(),
+        user_id: conversation.user_id,
+    };
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast ConversationReopened event: {}", e);
+    }
+
+    Ok(conversation)
+}
+
+/// Fold `source` into `target`: messages (notes included) move over with
+/// their original timestamps, tags are copied and `source` is closed with
+/// `closed_reason` "merged" by `merged_by` without messaging the customer.
+/// Unread counts follow from the moved messages and each operator's read
+/// cursor. Returns the updated target and the merge record.
+///
+/// Everything happens in one transaction, so a failure never leaves an
+/// emptied open source, moved messages without a merge record or a
+/// half-updated target.
+pub async fn merge(
+    storehaus: &StoreHaus,
+    ws_manager: &WebSocketManager,
+    source: Conversation,
+    target: Conversation,
+    merged_by: Option<Uuid>,
+) -> Result<(Conversation, ConversationMerge)> {
+    let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+
+    let mut tx = storehaus.pool().begin().await?;
+
+    // `conversation_id` is create-only on the model, so move rows directly
+    let moved = sqlx::query(
+        "UPDATE messages SET conversation_id = $1 \
+         WHERE conversation_id = $2 AND __deleted_at__ IS NULL",
+    )
+    .bind(target.id)
+    .bind(source.id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected() as i64;
+
+    // Same fields as `close`, plus the snooze and SLA timers
+    sqlx::query(
+        "UPDATE conversations SET status = $2, closed_reason = $3, closed_by = $4, closed_at = NOW(), \
+             unread_count = 0, snoozed_until = NULL, snoozed_by = NULL, first_response_due_at = NULL, \
+             next_response_due_at = NULL, resolution_due_at = NULL, __updated_at__ = NOW() \
+         WHERE id = $1",
+    )
+    .bind(source.id)
+    .bind(ConversationStatus::Closed)
+    .bind(Conversation::MERGED_REASON)
+    .bind(merged_by)
+    .execute(&mut *tx)
+    .await?;
+
+    // Carry over the source's tags the target does not have yet
+    let tag_ids: Vec<Uuid> = sqlx::query_scalar(
+        "SELECT DISTINCT s.tag_id FROM conversation_tags s \
+         WHERE s.conversation_id = $1 AND s.__deleted_at__ IS NULL \
+           AND NOT EXISTS ( \
+               SELECT 1 FROM conversation_tags t \
+               WHERE t.conversation_id = $2 AND t.tag_id = s.tag_id AND t.__deleted_at__ IS NULL \
+           )",
+    )
+    .bind(source.id)
+    .bind(target.id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for tag_id in tag_ids {
+        let link = ConversationTag::create(target.id, tag_id, merged_by);
+        sqlx::query("INSERT INTO conversation_tags (id, conversation_id, tag_id, added_by) VALUES ($1, $2, $3, $4)")
+            .bind(link.id)
+            .bind(link.conversation_id)
+            .bind(link.tag_id)
+            .bind(link.added_by)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    sqlx::query(
+        "UPDATE conversations SET last_message_at = GREATEST(last_message_at, $2), \
+             __tags__ = ARRAY['merged'], __updated_at__ = NOW() \
+         WHERE id = $1",
+    )
+    .bind(target.id)
+    .bind(source.last_message_at)
+    .execute(&mut *tx)
+    .await?;
+
+    let mut record = ConversationMerge::create(source.id, target.id, moved, merged_by);
+    record.__created_at__ = sqlx::query_scalar(
+        "INSERT INTO conversation_merges \
+             (id, source_conversation_id, target_conversation_id, message_count, merged_by) \
+         VALUES ($1, $2, $3, $4, $5) RETURNING __created_at__",
+    )
+    .bind(record.id)
+    .bind(record.source_conversation_id)
+    .bind(record.target_conversation_id)
+    .bind(record.message_count)
+    .bind(record.merged_by)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let target_id = target.id;
+    let target = conversation_store
+        .get_by_id(&target_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Conversation {} disappeared during merge", target_id))?;
+
+    let ws_event = WebSocketEvent::ConversationMerged {
+        conversation_id: target_id,
+        source_conversation_id: source.id,
+        message_count: moved,
+        merged_by,
+    };
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast ConversationMerged event: {}", e);
+    }
+
+    Ok((target, record))
+}
+
+/// Send the conversation_closed message to the customer in the background
+fn spawn_conversation_closed_notification(
+    storehaus: Arc<StoreHaus>,
+    bot_manager: Arc<BotManager>,
+    conv: Conversation,
+) {
+    tokio::spawn(async move {
+        if let Err(e) = customer_notifications::notify_conversation_closed(&storehaus, &bot_manager, &conv).await {
+            warn!("Failed to notify customer about closing of conversation {}: {}", conv.id, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_reopen() {
+        let now = Utc::now();
+        let closed = Conversation {
+            status: ConversationStatus::Closed,
+            closed_at: Some(now - Duration::minutes(20)),
+            ..Conversation::new_waiting(1, None)
+        };
+
+        assert!(can_reopen(&closed, 30, now));
+        assert!(!can_reopen(&closed, 10, now));
+        assert!(!can_reopen(&closed, 0, now));
+        assert!(!can_reopen(&Conversation { closed_at: None, ..closed.clone() }, 30, now));
+        assert!(!can_reopen(&Conversation::new_waiting(1, None), 30, now));
+    }
+}
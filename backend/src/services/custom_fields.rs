@@ -0,0 +1,255 @@
+//! Custom profile fields
+//!
+//! Admins define fields (key, label, type, enum options); operators set
+//! per-customer values. Values are validated against the field type and
+//! stored normalized as text in `custom_field_values`, so they can be
+//! searched and filtered in SQL; `typed_value` turns them back into JSON
+//! for responses and exports.
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::models::{CustomField, CustomFieldType, CustomFieldValue};
+
+/// Longest accepted text value
+pub const MAX_TEXT_LENGTH: usize = 1000;
+
+/// Whether `key` is a valid field key: lowercase letters, digits and
+/// underscores, starting with a letter
+pub fn is_valid_key(key: &str) -> bool {
+    key.len() <= 64
+        && key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Validate a value for `field` and return its normalized text form
+pub fn normalize(field: &CustomField, value: &Value) -> std::result::Result<String, String> {
+    let text = match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) if field.field_type == CustomFieldType::Number => n.to_string(),
+        _ => return Err(format!("'{}' expects a {} value", field.key, field.field_type)),
+    };
+    if text.is_empty() {
+        return Err(format!("'{}' cannot be empty", field.key));
+    }
+
+    match field.field_type {
+        CustomFieldType::Text => {
+            if text.chars().count() > MAX_TEXT_LENGTH {
+                return Err(format!("'{}' is longer than {} characters", field.key, MAX_TEXT_LENGTH));
+            }
+            Ok(text)
+        }
+        CustomFieldType::Number => text
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(|n| n.to_string())
+            .ok_or_else(|| format!("'{}' must be a number", field.key)),
+        CustomFieldType::Date => NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .map_err(|_| format!("'{}' must be a date (YYYY-MM-DD)", field.key)),
+        CustomFieldType::Enum => field
+            .parsed_options()
+            .into_iter()
+            .find(|option| option.eq_ignore_ascii_case(&text))
+            .ok_or_else(|| format!("'{}' must be one of: {}", field.key, field.parsed_options().join(", "))),
+        CustomFieldType::Url => match reqwest::Url::parse(&text) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") && url.host().is_some() => Ok(url.to_string()),
+            _ => Err(format!("'{}' must be an http(s) URL", field.key)),
+        },
+    }
+}
+
+/// JSON form of a stored value: numbers as numbers, everything else as strings
+pub fn typed_value(field: &CustomField, value: &str) -> Value {
+    match field.field_type {
+        CustomFieldType::Number => value.parse::<f64>().map(|n| json!(n)).unwrap_or_else(|_| json!(value)),
+        _ => json!(value),
+    }
+}
+
+/// Load all fields in display order
+pub async fn list_fields(storehaus: &StoreHaus) -> Result<Vec<CustomField>> {
+    let store = storehaus.get_store::<GenericStore<CustomField>>("custom_fields")?;
+
+    let mut fields = store.find(QueryBuilder::new()).await?;
+    fields.sort_by(|a, b| a.position.cmp(&b.position).then_with(|| a.label.cmp(&b.label)));
+    Ok(fields)
+}
+
+/// Find a field by key
+pub async fn find_by_key(storehaus: &StoreHaus, key: &str) -> Result<Option<CustomField>> {
+    let store = storehaus.get_store::<GenericStore<CustomField>>("custom_fields")?;
+
+    let query = QueryBuilder::new().filter(QueryFilter::eq("key", json!(key)));
+    Ok(store.find(query).await?.into_iter().next())
+}
+
+/// Stored values of a telegram user by field ID
+async fn stored_values(storehaus: &StoreHaus, telegram_user_id: i64) -> Result<HashMap<Uuid, CustomFieldValue>> {
+    let store = storehaus.get_store::<GenericStore<CustomFieldValue>>("custom_field_values")?;
+
+    let query = QueryBuilder::new().filter(QueryFilter::eq("telegram_user_id", json!(telegram_user_id)));
+    Ok(store.find(query).await?.into_iter().map(|v| (v.field_id, v)).collect())
+}
+
+/// A telegram user's fields with values, in display order
+pub async fn field_values(storehaus: &StoreHaus, telegram_user_id: i64) -> Result<Vec<(CustomField, Value)>> {
+    let fields = list_fields(storehaus).await?;
+    let stored = stored_values(storehaus, telegram_user_id).await?;
+
+    Ok(fields
+        .into_iter()
+        .filter_map(|field| {
+            let value = typed_value(&field, &stored.get(&field.id)?.value);
+            Some((field, value))
+        })
+        .collect())
+}
+
+/// A telegram user's values keyed by field key
+pub async fn values_for(storehaus: &StoreHaus, telegram_user_id: i64) -> Result<BTreeMap<String, Value>> {
+    Ok(field_values(storehaus, telegram_user_id)
+        .await?
+        .into_iter()
+        .map(|(field, value)| (field.key, value))
+        .collect())
+}
+
+/// Validate changes to a telegram user's values (`null` clears a value)
+/// and return the normalized value per field ID
+pub fn validate_changes(
+    fields: &[CustomField],
+    changes: &HashMap<String, Value>,
+) -> std::result::Result<Vec<(Uuid, Option<String>)>, String> {
+    changes
+        .iter()
+        .map(|(key, value)| {
+            let field = fields
+                .iter()
+                .find(|f| &f.key == key)
+                .ok_or_else(|| format!("Unknown custom field '{}'", key))?;
+            let normalized = match value {
+                Value::Null if field.required => return Err(format!("'{}' is required", key)),
+                Value::Null => None,
+                value => Some(normalize(field, value)?),
+            };
+            Ok((field.id, normalized))
+        })
+        .collect()
+}
+
+/// Write validated changes to a telegram user's values
+pub async fn apply_changes(
+    storehaus: &StoreHaus,
+    telegram_user_id: i64,
+    changes: Vec<(Uuid, Option<String>)>,
+    updated_by: Uuid,
+) -> Result<()> {
+    let store = storehaus.get_store::<GenericStore<CustomFieldValue>>("custom_field_values")?;
+    let mut stored = stored_values(storehaus, telegram_user_id).await?;
+
+    for (field_id, normalized) in changes {
+        match (stored.remove(&field_id), normalized) {
+            (Some(mut existing), Some(value)) => {
+                if existing.value != value {
+                    existing.value = value;
+                    existing.updated_by = Some(updated_by);
+                    store.update(&existing.id, existing.clone(), None).await?;
+                }
+            }
+            (Some(existing), None) => {
+                store.delete(&existing.id).await?;
+            }
+            (None, Some(value)) => {
+                store
+                    .create(CustomFieldValue::create(telegram_user_id, field_id, value, Some(updated_by)), None)
+                    .await?;
+            }
+            (None, None) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove every value of a field (before deleting it)
+pub async fn remove_field_values(storehaus: &StoreHaus, field_id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM custom_field_values WHERE field_id = $1")
+        .bind(field_id)
+        .execute(storehaus.pool())
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(field_type: CustomFieldType, options: &[&str]) -> CustomField {
+        CustomField::create(
+            "field".to_string(),
+            "Field".to_string(),
+            field_type,
+            options.iter().map(|o| o.to_string()).collect(),
+            false,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_normalize() {
+        let number = field(CustomFieldType::Number, &[]);
+        assert_eq!(normalize(&number, &json!(" 42.50 ")), Ok("42.5".to_string()));
+        assert_eq!(normalize(&number, &json!(7)), Ok("7".to_string()));
+        assert!(normalize(&number, &json!("seven")).is_err());
+
+        let date = field(CustomFieldType::Date, &[]);
+        assert_eq!(normalize(&date, &json!("2024-02-29")), Ok("2024-02-29".to_string()));
+        assert!(normalize(&date, &json!("2023-02-29")).is_err());
+
+        let plan = field(CustomFieldType::Enum, &["Free", "Pro"]);
+        assert_eq!(normalize(&plan, &json!("pro")), Ok("Pro".to_string()));
+        assert!(normalize(&plan, &json!("enterprise")).is_err());
+
+        let url = field(CustomFieldType::Url, &[]);
+        assert!(normalize(&url, &json!("https://example.com/orders/1")).is_ok());
+        assert!(normalize(&url, &json!("javascript:alert(1)")).is_err());
+
+        let text = field(CustomFieldType::Text, &[]);
+        assert!(normalize(&text, &json!("   ")).is_err());
+        assert!(normalize(&text, &json!(5)).is_err());
+    }
+
+    #[test]
+    fn test_validate_changes() {
+        let plan = CustomField { required: true, ..field(CustomFieldType::Enum, &["Free", "Pro"]) };
+        let fields = vec![plan.clone()];
+
+        let changes = HashMap::from([("field".to_string(), json!("free"))]);
+        assert_eq!(validate_changes(&fields, &changes), Ok(vec![(plan.id, Some("Free".to_string()))]));
+
+        let cleared = HashMap::from([("field".to_string(), Value::Null)]);
+        assert!(validate_changes(&fields, &cleared).is_err());
+
+        let unknown = HashMap::from([("other".to_string(), json!("x"))]);
+        assert!(validate_changes(&fields, &unknown).is_err());
+    }
+
+    #[test]
+    fn test_keys_and_typed_values() {
+        assert!(is_valid_key("account_id"));
+        assert!(!is_valid_key("Account"));
+        assert!(!is_valid_key("1st"));
+        assert!(!is_valid_key("order-number"));
+
+        let number = field(CustomFieldType::Number, &[]);
+        assert_eq!(typed_value(&number, "42.5"), json!(42.5));
+        assert_eq!(typed_value(&field(CustomFieldType::Text, &[]), "42"), json!("42"));
+    }
+}
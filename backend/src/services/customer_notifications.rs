@@ -0,0 +1,269 @@
+//! Lifecycle notifications sent to customers through the bot
+//!
+//! Messages use the customer's locale (with admin overrides) and respect
+//! the notification toggles in system settings. The idle nudge has no
+//! toggle of its own; `idle_nudge_hours` controls it.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use storehaus::prelude::*;
+use teloxide::Bot;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::l10n::{bot_messages, format_message, BotMessages};
+use crate::models::{Conversation, OperatorNameDisplay, TelegramUser, User};
+use crate::services::settings::notification_settings;
+use crate::telegram::{send_message_to_telegram_user, BotManager, SendMessageResult};
+
+/// Name shown to the customer for an operator
+pub fn operator_display_name(
+    mode: OperatorNameDisplay,
+    real_name: &str,
+    alias: Option<&str>,
+    messages: &BotMessages,
+) -> String {
+    let anonymous = || messages.anonymous_operator_name.clone();
+
+    match mode {
+        OperatorNameDisplay::RealName if !real_name.trim().is_empty() => real_name.to_string(),
+        OperatorNameDisplay::RealName => anonymous(),
+        OperatorNameDisplay::Alias => alias
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(anonymous),
+        OperatorNameDisplay::Anonymous => anonymous(),
+    }
+}
+
+/// Tell the customer that an operator joined the conversation
+pub async fn notify_operator_assigned(
+    storehaus: &StoreHaus,
+    bot_manager: &BotManager,
+    conversation: &Conversation,
+    operator_id: Uuid,
+) -> Result<()> {
+    let Some(bot) = bot_manager.bot().await else {
+        debug!("Bot is not connected, skipping notification for conversation {}", conversation.id);
+        return Ok(());
+    };
+
+    notify_operator_assigned_with_bot(storehaus, &bot, conversation, operator_id).await
+}
+
+/// Same as [`notify_operator_assigned`], for callers that already hold a bot
+/// (e.g. message handlers running inside the dispatcher)
+pub async fn notify_operator_assigned_with_bot(
+    storehaus: &StoreHaus,
+    bot: &Bot,
+    conversation: &Conversation,
+    operator_id: Uuid,
+) -> Result<()> {
+    let settings = notification_settings(storehaus).await?;
+    if !settings.notify_operator_assigned {
+        return Ok(());
+    }
+
+    let Some(telegram_user) = load_customer(storehaus, conversation.telegram_user_id).await? else {
+        return Ok(());
+    };
+
+    let user_store = storehaus.get_store::<GenericStore<User>>("users")?;
+    let Some(operator) = user_store.get_by_id(&operator_id).await? else {
+        return Ok(());
+    };
+
+    let messages = bot_messages(telegram_user.country_code.as_deref());
+    let operator_settings = operator.parsed_settings();
+    let operator_name = operator_display_name(
+        settings.operator_name_display,
+        &operator.name,
+        operator_settings.operator_alias.as_deref(),
+        &messages,
+    );
+
+    let mut vars = HashMap::new();
+    vars.insert("operator_name", operator_name.as_str());
+    let text = format_message(&messages.operator_assigned, &vars);
+
+    send_with_bot(storehaus, bot, &telegram_user, &text).await
+}
+
+/// Tell the customer that the conversation was closed
+pub async fn notify_conversation_closed(
+    storehaus: &StoreHaus,
+    bot_manager: &BotManager,
+    conversation: &Conversation,
+) -> Result<()> {
+    let settings = notification_settings(storehaus).await?;
+    if !settings.notify_conversation_closed {
+        return Ok(());
+    }
+
+    let Some(telegram_user) = load_customer(storehaus, conversation.telegram_user_id).await? else {
+        return Ok(());
+    };
+
+    let messages = bot_messages(telegram_user.country_code.as_deref());
+
+    send(storehaus, bot_manager, &telegram_user, &messages.conversation_closed).await
+}
+
+/// Tell a waiting customer their queue position and estimated wait
+pub async fn notify_queue_position(
+    storehaus: &StoreHaus,
+    bot: &Bot,
+    conversation: &Conversation,
+    position: usize,
+    estimated_wait_seconds: Option<i64>,
+) -> Result<()> {
+    let settings = notification_settings(storehaus).await?;
+    if !settings.notify_queue_position {
+        return Ok(());
+    }
+
+    let Some(telegram_user) = load_customer(storehaus, conversation.telegram_user_id).await? else {
+        return Ok(());
+    };
+
+    let messages = bot_messages(telegram_user.country_code.as_deref());
+    let text = queue_position_text(&messages, position, estimated_wait_seconds);
+
+    send_with_bot(storehaus, bot, &telegram_user, &text).await
+}
+
+/// Ask a customer who went quiet whether they are still there.
+///
+/// Always sent: the idle auto-close counts from this nudge, so a toggle
+/// that silenced it would close conversations without warning the customer.
+/// Setting `idle_nudge_hours` to 0 turns off nudging and auto-closing
+/// together.
+pub async fn notify_idle_nudge(storehaus: &StoreHaus, bot: &Bot, conversation: &Conversation) -> Result<()> {
+    let Some(telegram_user) = load_customer(storehaus, conversation.telegram_user_id).await? else {
+        return Ok(());
+    };
+
+    let messages = bot_messages(telegram_user.country_code.as_deref());
+
+    send_with_bot(storehaus, bot, &telegram_user, &messages.idle_nudge).await
+}
+
+/// Queue position message, with the wait estimate rounded up to minutes
+pub fn queue_position_text(messages: &BotMessages, position: usize, estimated_wait_seconds: Option<i64>) -> String {
+    let position = position.to_string();
+    let mut vars = HashMap::new();
+    vars.insert("position", position.as_str());
+    let mut text = format_message(&messages.queue_position, &vars);
+
+    if let Some(seconds) = estimated_wait_seconds {
+        let minutes = ((seconds + 59) / 60).max(1).to_string();
+        let mut vars = HashMap::new();
+        vars.insert("minutes", minutes.as_str());
+        text.push(' ');
+        text.push_str(&format_message(&messages.queue_wait_estimate, &vars));
+    }
+
+    text
+}
+
+/// Load the customer, skipping users who blocked the bot
+async fn load_customer(storehaus: &StoreHaus, telegram_user_id: i64) -> Result<Option<TelegramUser>> {
+    let store = storehaus.get_store::<GenericStore<TelegramUser>>("telegram_users")?;
+    let telegram_user = store.get_by_id(&telegram_user_id).await?;
+
+    Ok(telegram_user.filter(|u| !u.is_blocked))
+}
+
+async fn send(
+    storehaus: &StoreHaus,
+    bot_manager: &BotManager,
+    telegram_user: &TelegramUser,
+    text: &str,
+) -> Result<()> {
+    let Some(bot) = bot_manager.bot().await else {
+        debug!("Bot is not connected, skipping notification to {}", telegram_user.id);
+        return Ok(());
+    };
+
+    send_with_bot(storehaus, &bot, telegram_user, text).await
+}
+
+async fn send_with_bot(
+    storehaus: &StoreHaus,
+    bot: &Bot,
+    telegram_user: &TelegramUser,
+    text: &str,
+) -> Result<()> {
+    match send_message_to_telegram_user(bot, telegram_user.id, text).await {
+        SendMessageResult::Success(_) => Ok(()),
+        SendMessageResult::UserBlocked => {
+            // Mark user as blocked so later notifications are skipped
+            let store = storehaus.get_store::<GenericStore<TelegramUser>>("telegram_users")?;
+            let mut user = telegram_user.clone();
+            user.is_blocked = true;
+            if let Err(e) = store.update(&telegram_user.id, user, None).await {
+                warn!("Failed to update user blocked status: {}", e);
+            }
+            Ok(())
+        }
+        SendMessageResult::Error(err) => Err(anyhow::anyhow!("Failed to send notification: {}", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> BotMessages {
+        BotMessages {
+            welcome: "Hello".to_string(),
+            operator_assigned: "{operator_name} has joined.".to_string(),
+            anonymous_operator_name: "An operator".to_string(),
+            conversation_closed: "Closed".to_string(),
+            operator_typing: "Typing".to_string(),
+            message_sent: "Sent".to_string(),
+            error: "Error".to_string(),
+            queue_position: "You are number {position} in the queue.".to_string(),
+            queue_wait_estimate: "Estimated wait: about {minutes} min.".to_string(),
+            idle_nudge: "Still there?".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_operator_display_name() {
+        let messages = messages();
+
+        assert_eq!(
+            operator_display_name(OperatorNameDisplay::RealName, "Anna", Some("Support"), &messages),
+            "Anna"
+        );
+        assert_eq!(
+            operator_display_name(OperatorNameDisplay::Alias, "Anna", Some("Support"), &messages),
+            "Support"
+        );
+        assert_eq!(
+            operator_display_name(OperatorNameDisplay::Alias, "Anna", Some("  "), &messages),
+            "An operator"
+        );
+        assert_eq!(
+            operator_display_name(OperatorNameDisplay::Anonymous, "Anna", Some("Support"), &messages),
+            "An operator"
+        );
+    }
+
+    #[test]
+    fn test_queue_position_text() {
+        let messages = messages();
+
+        assert_eq!(
+            queue_position_text(&messages, 3, Some(400)),
+            "You are number 3 in the queue. Estimated wait: about 7 min."
+        );
+        assert_eq!(
+            queue_position_text(&messages, 1, Some(10)),
+            "You are number 1 in the queue. Estimated wait: about 1 min."
+        );
+        assert_eq!(queue_position_text(&messages, 2, None), "You are number 2 in the queue.");
+    }
+}
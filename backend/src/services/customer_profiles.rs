@@ -0,0 +1,124 @@
+//! Customer profiles linking several Telegram accounts of one customer
+
+use anyhow::Result;
+use serde_json::json;
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::models::{CustomerProfile, TelegramUser};
+
+/// Whether two Telegram accounts belong to the same customer
+pub fn same_customer(a: &TelegramUser, b: &TelegramUser) -> bool {
+    a.id == b.id || (a.customer_profile_id.is_some() && a.customer_profile_id == b.customer_profile_id)
+}
+
+/// All accounts of the customer behind `telegram_user`, oldest first
+/// (just the user itself when it is not linked)
+pub async fn linked_accounts(storehaus: &StoreHaus, telegram_user: &TelegramUser) -> Result<Vec<TelegramUser>> {
+    let Some(profile_id) = telegram_user.customer_profile_id else {
+        return Ok(vec![telegram_user.clone()]);
+    };
+
+    let store = storehaus.get_store::<GenericStore<TelegramUser>>("telegram_users")?;
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("customer_profile_id", json!(profile_id)))
+        .order_by("__created_at__", SortOrder::Asc);
+
+    Ok(store.find(query).await?)
+}
+
+/// Link two accounts to one profile. An existing profile of either account
+/// is reused; when both already have different profiles, the accounts of
+/// `other` move over and its profile is removed. Returns the profile ID.
+pub async fn link(
+    storehaus: &StoreHaus,
+    telegram_user: &TelegramUser,
+    other: &TelegramUser,
+    linked_by: Option<Uuid>,
+) -> Result<Uuid> {
+    let user_store = storehaus.get_store::<GenericStore<TelegramUser>>("telegram_users")?;
+    let profile_store = storehaus.get_store::<GenericStore<CustomerProfile>>("customer_profiles")?;
+
+    let profile_id = match telegram_user.customer_profile_id.or(other.customer_profile_id) {
+        Some(profile_id) => profile_id,
+        None => {
+            let profile = CustomerProfile::create(Some(telegram_user.full_name()), linked_by);
+            profile_store.create(profile, None).await?.id
+        }
+    };
+
+    // Everything linked to either account ends up on `profile_id`
+    let mut accounts = linked_accounts(storehaus, telegram_user).await?;
+    accounts.extend(linked_accounts(storehaus, other).await?);
+
+    for account in accounts {
+        if account.customer_profile_id == Some(profile_id) {
+            continue;
+        }
+
+        let account_id = account.id;
+        let mut updated = account;
+        updated.customer_profile_id = Some(profile_id);
+        user_store.update(&account_id, updated, Some(vec!["linked".to_string()])).await?;
+    }
+
+    if let Some(old_profile_id) = other.customer_profile_id.filter(|id| *id != profile_id) {
+        profile_store.delete(&old_profile_id).await?;
+    }
+
+    Ok(profile_id)
+}
+
+/// Detach an account from its profile. The profile is removed once fewer
+/// than two accounts are left on it.
+pub async fn unlink(storehaus: &StoreHaus, telegram_user: &TelegramUser) -> Result<()> {
+    let Some(profile_id) = telegram_user.customer_profile_id else {
+        return Ok(());
+    };
+
+    let user_store = storehaus.get_store::<GenericStore<TelegramUser>>("telegram_users")?;
+    let profile_store = storehaus.get_store::<GenericStore<CustomerProfile>>("customer_profiles")?;
+
+    let mut updated = telegram_user.clone();
+    updated.customer_profile_id = None;
+    user_store.update(&telegram_user.id, updated, Some(vec!["unlinked".to_string()])).await?;
+
+    let query = QueryBuilder::new().filter(QueryFilter::eq("customer_profile_id", json!(profile_id)));
+    let remaining = user_store.find(query).await?;
+
+    if remaining.len() < 2 {
+        for account in remaining {
+            let account_id = account.id;
+            let mut updated = account;
+            updated.customer_profile_id = None;
+            user_store.update(&account_id, updated, Some(vec!["unlinked".to_string()])).await?;
+        }
+        profile_store.delete(&profile_id).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(id: i64, profile: Option<Uuid>) -> TelegramUser {
+        TelegramUser {
+            id,
+            first_name: format!("User {}", id),
+            customer_profile_id: profile,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_same_customer() {
+        let profile = Uuid::new_v4();
+
+        assert!(same_customer(&account(1, None), &account(1, None)));
+        assert!(same_customer(&account(1, Some(profile)), &account(2, Some(profile))));
+        assert!(!same_customer(&account(1, None), &account(2, None)));
+        assert!(!same_customer(&account(1, Some(profile)), &account(2, Some(Uuid::new_v4()))));
+    }
+}
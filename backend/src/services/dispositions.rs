@@ -0,0 +1,122 @@
+//! Disposition (wrap-up) codes
+//!
+//! Admins manage the codes; operators pick one and optionally write a
+//! resolution summary when closing a conversation. Whether either is
+//! required comes from `WrapUpSettings`. Conversations store the code
+//! itself, so closed conversations keep their disposition when a code is
+//! renamed or deactivated. Automatic closes (idle, merge) carry none.
+
+use anyhow::Result;
+use serde_json::json;
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::models::{DispositionCode, WrapUpSettings};
+
+/// Longest accepted resolution summary
+pub const MAX_SUMMARY_LENGTH: usize = 2000;
+
+/// How an operator wrapped up a conversation they closed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrapUp {
+    pub closed_by: Uuid,
+    pub disposition: Option<String>,
+    pub resolution_summary: Option<String>,
+}
+
+/// Whether `code` is a valid code: lowercase letters, digits and
+/// underscores, starting with a letter
+pub fn is_valid_code(code: &str) -> bool {
+    code.len() <= 32
+        && code.starts_with(|c: char| c.is_ascii_lowercase())
+        && code.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Check a wrap-up against the settings and the active codes and return the
+/// stored disposition code and trimmed summary
+pub fn validate(
+    settings: &WrapUpSettings,
+    codes: &[DispositionCode],
+    disposition: Option<&str>,
+    summary: Option<&str>,
+) -> std::result::Result<(Option<String>, Option<String>), String> {
+    let disposition = match disposition.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(value) => Some(
+            codes
+                .iter()
+                .find(|c| c.is_active && c.code.eq_ignore_ascii_case(value))
+                .map(|c| c.code.clone())
+                .ok_or_else(|| format!("Unknown disposition code '{}'", value))?,
+        ),
+        None if settings.disposition_required => return Err("A disposition code is required".to_string()),
+        None => None,
+    };
+
+    let summary = match summary.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(text) if text.chars().count() > MAX_SUMMARY_LENGTH => {
+            return Err(format!("Resolution summary must be at most {} characters", MAX_SUMMARY_LENGTH));
+        }
+        Some(text) => Some(text.to_string()),
+        None if settings.resolution_summary_required => return Err("A resolution summary is required".to_string()),
+        None => None,
+    };
+
+    Ok((disposition, summary))
+}
+
+/// Load all codes in display order
+pub async fn list_codes(storehaus: &StoreHaus) -> Result<Vec<DispositionCode>> {
+    let store = storehaus.get_store::<GenericStore<DispositionCode>>("disposition_codes")?;
+
+    let mut codes = store.find(QueryBuilder::new()).await?;
+    codes.sort_by(|a, b| a.position.cmp(&b.position).then_with(|| a.label.cmp(&b.label)));
+    Ok(codes)
+}
+
+/// Find a code by its code string
+pub async fn find_by_code(storehaus: &StoreHaus, code: &str) -> Result<Option<DispositionCode>> {
+    let store = storehaus.get_store::<GenericStore<DispositionCode>>("disposition_codes")?;
+
+    let query = QueryBuilder::new().filter(QueryFilter::eq("code", json!(code)));
+    Ok(store.find(query).await?.into_iter().next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes() -> Vec<DispositionCode> {
+        let mut spam = DispositionCode::create("spam".to_string(), "Spam".to_string(), 1);
+        spam.is_active = false;
+        vec![DispositionCode::create("resolved".to_string(), "Resolved".to_string(), 0), spam]
+    }
+
+    #[test]
+    fn test_validate() {
+        let optional = WrapUpSettings::default();
+        assert_eq!(validate(&optional, &codes(), None, None), Ok((None, None)));
+        assert_eq!(
+            validate(&optional, &codes(), Some("Resolved"), Some("  Refunded the order ")),
+            Ok((Some("resolved".to_string()), Some("Refunded the order".to_string())))
+        );
+        assert!(validate(&optional, &codes(), Some("spam"), None).is_err());
+        assert!(validate(&optional, &codes(), Some("unknown"), None).is_err());
+
+        let required = WrapUpSettings {
+            disposition_required: true,
+            resolution_summary_required: true,
+        };
+        assert!(validate(&required, &codes(), None, Some("Done")).is_err());
+        assert!(validate(&required, &codes(), Some("resolved"), Some("   ")).is_err());
+        assert!(validate(&required, &codes(), Some("resolved"), Some("Done")).is_ok());
+    }
+
+    #[test]
+    fn test_is_valid_code() {
+        assert!(is_valid_code("resolved"));
+        assert!(is_valid_code("no_response_2"));
+        assert!(!is_valid_code("Resolved"));
+        assert!(!is_valid_code("2nd_line"));
+        assert!(!is_valid_code("follow-up"));
+    }
+}
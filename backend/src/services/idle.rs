@@ -0,0 +1,229 @@
+//! Idle conversation handling
+//!
+//! An active conversation whose last chat message is an operator reply is
+//! waiting on the customer. After `idle_nudge_hours` without an answer the
+//! customer is asked whether they are still there; `idle_close_hours` after
+//! that nudge the conversation is closed like a manual close, with
+//! `closed_reason` set to "auto_closed". Any newer operator message restarts
+//! the clock, and a customer reply moves the conversation out of the idle
+//! state altogether.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use storehaus::prelude::*;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::models::{Conversation, ConversationStatus, IdleSettings, Message, MessageKind};
+use crate::services::settings::idle_settings;
+use crate::services::{conversations, customer_notifications};
+use crate::telegram::BotManager;
+use crate::websocket::WebSocketManager;
+
+/// How often the background worker looks for idle conversations
+pub const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// What to do with an idle conversation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleAction {
+    Nudge,
+    Close,
+}
+
+/// Decide what to do with a conversation whose latest chat message is
+/// `last_message` and whose customer was last nudged at `nudged_at`
+pub fn idle_action(
+    settings: &IdleSettings,
+    last_message: Option<&Message>,
+    nudged_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<IdleAction> {
+    if settings.idle_nudge_hours == 0 {
+        return None;
+    }
+
+    // Only conversations waiting on the customer are idle
+    let last_message = last_message.filter(|m| m.is_operator_reply())?;
+
+    match nudged_at {
+        Some(nudged_at) if nudged_at >= last_message.__created_at__ => {
+            let close_after = Duration::hours(settings.idle_close_hours as i64);
+            (settings.idle_close_hours > 0 && now - nudged_at >= close_after).then_some(IdleAction::Close)
+        }
+        _ => {
+            let nudge_after = Duration::hours(settings.idle_nudge_hours as i64);
+            (now - last_message.__created_at__ >= nudge_after).then_some(IdleAction::Nudge)
+        }
+    }
+}
+
+/// Latest chat message (notes excluded) of each conversation
+async fn last_chat_messages(storehaus: &StoreHaus, conversation_ids: &[Uuid]) -> Result<HashMap<Uuid, Message>> {
+    let messages = sqlx::query_as::<_, Message>(
+        "SELECT DISTINCT ON (conversation_id) * FROM messages \
+         WHERE conversation_id = ANY($1) AND kind <> $2 AND __deleted_at__ IS NULL \
+         ORDER BY conversation_id, __created_at__ DESC",
+    )
+    .bind(conversation_ids)
+    .bind(MessageKind::Note.as_str())
+    .fetch_all(storehaus.pool())
+    .await?;
+
+    Ok(messages.into_iter().map(|m| (m.conversation_id, m)).collect())
+}
+
+/// Nudge and close idle conversations. Returns (nudged, closed).
+/// A conversation that fails is logged and retried on the next pass.
+pub async fn check_idle(
+    storehaus: &Arc<StoreHaus>,
+    ws_manager: &Arc<WebSocketManager>,
+    bot_manager: &Arc<BotManager>,
+) -> Result<(usize, usize)> {
+    let settings = idle_settings(storehaus).await?;
+    if settings.idle_nudge_hours == 0 {
+        return Ok((0, 0));
+    }
+
+    let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("status", json!(ConversationStatus::Active.as_str())));
+    let active = conversation_store.find(query).await?;
+    if active.is_empty() {
+        return Ok((0, 0));
+    }
+
+    let ids: Vec<Uuid> = active.iter().map(|c| c.id).collect();
+    let last_messages = last_chat_messages(storehaus, &ids).await?;
+
+    let now = Utc::now();
+    let (mut nudged, mut closed) = (0, 0);
+
+    for conversation in active {
+        let conversation_id = conversation.id;
+
+        match idle_action(&settings, last_messages.get(&conversation_id), conversation.idle_nudged_at, now) {
+            Some(IdleAction::Nudge) => {
+                // Without a bot the customer cannot be asked, so the clock does not start
+                let Some(bot) = bot_manager.bot().await else {
+                    continue;
+                };
+
+                if let Err(e) = customer_notifications::notify_idle_nudge(storehaus, &bot, &conversation).await {
+                    warn!("Failed to nudge customer of idle conversation {}: {}", conversation_id, e);
+                    continue;
+                }
+
+                let mut updated = conversation;
+                updated.idle_nudged_at = Some(now);
+                if let Err(e) = conversation_store.update(&conversation_id, updated, None).await {
+                    error!("Failed to record idle nudge of conversation {}: {}", conversation_id, e);
+                    continue;
+                }
+                nudged += 1;
+            }
+            Some(IdleAction::Close) => {
+                let result = conversations::close(
+                    storehaus,
+                    ws_manager,
+                    bot_manager,
+                    conversation,
+                    Some(Conversation::AUTO_CLOSED_REASON),
+                    None,
+                )
+                .await;
+                if let Err(e) = result {
+                    error!("Failed to auto-close idle conversation {}: {}", conversation_id, e);
+                    continue;
+                }
+                info!("Auto-closed idle conversation {}", conversation_id);
+                closed += 1;
+            }
+            None => {}
+        }
+    }
+
+    Ok((nudged, closed))
+}
+
+/// Periodically nudge and close idle conversations
+pub fn spawn_idle_worker(
+    storehaus: Arc<StoreHaus>,
+    ws_manager: Arc<WebSocketManager>,
+    bot_manager: Arc<BotManager>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            match check_idle(&storehaus, &ws_manager, &bot_manager).await {
+                Ok((0, 0)) => {}
+                Ok((nudged, closed)) => info!("Idle check: {} nudged, {} closed", nudged, closed),
+                Err(e) => error!("Failed to check idle conversations: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> IdleSettings {
+        IdleSettings {
+            idle_nudge_hours: 4,
+            idle_close_hours: 20,
+        }
+    }
+
+    fn message(from_operator: bool, at: DateTime<Utc>) -> Message {
+        let mut message = if from_operator {
+            Message::from_user_message(Uuid::new_v4(), "Anything else?".to_string())
+        } else {
+            Message::from_telegram_user(Uuid::new_v4(), "Thanks".to_string(), 1)
+        };
+        message.__created_at__ = at;
+        message
+    }
+
+    #[test]
+    fn test_idle_action() {
+        let now = Utc::now();
+        let reply = message(true, now - Duration::hours(5));
+
+        assert_eq!(idle_action(&settings(), Some(&reply), None, now), Some(IdleAction::Nudge));
+        assert_eq!(idle_action(&settings(), Some(&message(true, now - Duration::hours(1))), None, now), None);
+        assert_eq!(idle_action(&settings(), Some(&message(false, now - Duration::hours(5))), None, now), None);
+        assert_eq!(idle_action(&settings(), None, None, now), None);
+
+        // Nudged, waiting for the close period
+        let nudged_at = now - Duration::hours(1);
+        assert_eq!(idle_action(&settings(), Some(&reply), Some(nudged_at), now), None);
+        assert_eq!(
+            idle_action(&settings(), Some(&reply), Some(nudged_at), now + Duration::hours(20)),
+            Some(IdleAction::Close)
+        );
+
+        // A newer operator message restarts the clock
+        let later_reply = message(true, now - Duration::minutes(30));
+        assert_eq!(
+            idle_action(&settings(), Some(&later_reply), Some(nudged_at), now + Duration::hours(20)),
+            Some(IdleAction::Nudge)
+        );
+    }
+
+    #[test]
+    fn test_idle_action_disabled() {
+        let now = Utc::now();
+        let reply = message(true, now - Duration::hours(48));
+        let disabled = IdleSettings { idle_nudge_hours: 0, ..settings() };
+        let nudge_only = IdleSettings { idle_close_hours: 0, ..settings() };
+
+        assert_eq!(idle_action(&disabled, Some(&reply), None, now), None);
+        assert_eq!(idle_action(&nudge_only, Some(&reply), Some(now - Duration::hours(40)), now), None);
+    }
+}
@@ -0,0 +1,196 @@
+//! Cursor-based message history
+//!
+//! Messages of a conversation are ordered by (created at, id). A window is
+//! taken before or after an anchor — a message or a point in time — or
+//! around a message, so clients can scroll back through long conversations,
+//! fill the gap after a reconnect and jump to a search hit without offsets
+//! that shift when new messages arrive.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::db::sql::{self, Bind, Conditions};
+use crate::models::MessageKind;
+
+/// Where a history window starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Message(Uuid),
+    Time(DateTime<Utc>),
+}
+
+impl Anchor {
+    /// A message ID or an RFC 3339 timestamp
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Ok(id) = Uuid::parse_str(value) {
+            return Some(Anchor::Message(id));
+        }
+        DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|at| Anchor::Time(at.with_timezone(&Utc)))
+    }
+}
+
+/// Which messages to return
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryWindow {
+    /// Older than the anchor
+    Before(Anchor),
+    /// Newer than the anchor
+    After(Anchor),
+    /// The message itself with older and newer messages on both sides
+    Around(Uuid),
+}
+
+/// Position of a message in history order; `id: None` for a point in time
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    created_at: DateTime<Utc>,
+    id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Older,
+    Newer,
+}
+
+/// Message IDs of a window, oldest first. Returns `None` when the anchor
+/// message is not part of the conversation.
+pub async fn window(
+    storehaus: &StoreHaus,
+    conversation_id: Uuid,
+    window: HistoryWindow,
+    include_notes: bool,
+    limit: i64,
+) -> Result<Option<Vec<Uuid>>> {
+    let ids = match window {
+        HistoryWindow::Before(anchor) => {
+            let Some(position) = resolve(storehaus, conversation_id, anchor).await? else {
+                return Ok(None);
+            };
+            fetch(storehaus, conversation_id, position, Direction::Older, false, include_notes, limit).await?
+        }
+        HistoryWindow::After(anchor) => {
+            let Some(position) = resolve(storehaus, conversation_id, anchor).await? else {
+                return Ok(None);
+            };
+            fetch(storehaus, conversation_id, position, Direction::Newer, false, include_notes, limit).await?
+        }
+        HistoryWindow::Around(message_id) => {
+            let Some(position) = resolve(storehaus, conversation_id, Anchor::Message(message_id)).await? else {
+                return Ok(None);
+            };
+            let older = limit / 2;
+            let mut ids =
+                fetch(storehaus, conversation_id, position, Direction::Older, false, include_notes, older).await?;
+            ids.extend(
+                fetch(storehaus, conversation_id, position, Direction::Newer, true, include_notes, limit - older)
+                    .await?,
+            );
+            ids
+        }
+    };
+
+    Ok(Some(ids))
+}
+
+/// Position of an anchor; `None` when the anchor message is not in the conversation
+async fn resolve(storehaus: &StoreHaus, conversation_id: Uuid, anchor: Anchor) -> Result<Option<Position>> {
+    match anchor {
+        Anchor::Time(created_at) => Ok(Some(Position { created_at, id: None })),
+        Anchor::Message(id) => {
+            let created_at: Option<DateTime<Utc>> = sqlx::query_scalar(
+                "SELECT __created_at__ FROM messages \
+                 WHERE id = $1 AND conversation_id = $2 AND __deleted_at__ IS NULL",
+            )
+            .bind(id)
+            .bind(conversation_id)
+            .fetch_optional(storehaus.pool())
+            .await?;
+
+            Ok(created_at.map(|created_at| Position { created_at, id: Some(id) }))
+        }
+    }
+}
+
+/// Up to `limit` message IDs on one side of `position`, oldest first
+async fn fetch(
+    storehaus: &StoreHaus,
+    conversation_id: Uuid,
+    position: Position,
+    direction: Direction,
+    inclusive: bool,
+    include_notes: bool,
+    limit: i64,
+) -> Result<Vec<Uuid>> {
+    if limit <= 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut conditions = Conditions::default();
+    let conversation = conditions.bind(Bind::Id(conversation_id));
+    conditions.push(format!("conversation_id = {}", conversation));
+    conditions.push("__deleted_at__ IS NULL".to_string());
+
+    if !include_notes {
+        conditions.push(format!("kind <> '{}'", MessageKind::Note.as_str()));
+    }
+
+    let (operator, order) = match (direction, inclusive) {
+        (Direction::Older, false) => ("<", "DESC"),
+        (Direction::Older, true) => ("<=", "DESC"),
+        (Direction::Newer, false) => (">", "ASC"),
+        (Direction::Newer, true) => (">=", "ASC"),
+    };
+    let created_at = conditions.bind(Bind::Time(position.created_at));
+    match position.id {
+        Some(id) => {
+            let id = conditions.bind(Bind::Id(id));
+            conditions.push(format!("(__created_at__, id) {} ({}, {})", operator, created_at, id));
+        }
+        None => {
+            // A point in time is never equal to a message, so it is exclusive
+            let operator = operator.trim_end_matches('=');
+            conditions.push(format!("__created_at__ {} {}", operator, created_at));
+        }
+    }
+
+    let window_sql = format!(
+        "SELECT id FROM messages WHERE {} ORDER BY __created_at__ {order}, id {order} LIMIT {}",
+        conditions.sql(),
+        limit,
+        order = order
+    );
+    let mut ids: Vec<Uuid> = sql::apply(sqlx::query(&window_sql), conditions.binds())
+        .fetch_all(storehaus.pool())
+        .await?
+        .into_iter()
+        .map(|row| row.try_get("id"))
+        .collect::<Result<_, _>>()?;
+
+    if matches!(direction, Direction::Older) {
+        ids.reverse();
+    }
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anchor_parse() {
+        let id = Uuid::new_v4();
+        assert_eq!(Anchor::parse(&id.to_string()), Some(Anchor::Message(id)));
+        assert_eq!(
+            Anchor::parse("2024-05-01T12:00:00+02:00"),
+            DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z")
+                .ok()
+                .map(|at| Anchor::Time(at.with_timezone(&Utc)))
+        );
+        assert_eq!(Anchor::parse("yesterday"), None);
+    }
+}
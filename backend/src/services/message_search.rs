@@ -0,0 +1,453 @@
+//! Message search
+//!
+//! Messages match when the query matches their content under any of the
+//! text search configurations in `SEARCH_CONFIGS` (stemmed full-text
+//! search, `websearch_to_tsquery` syntax), or when the query occurs in the
+//! content literally (trigram-indexed `ILIKE`, catching partial words,
+//! numbers and typos in identifiers). Results are ranked by the best of the
+//! full-text ranks and trigram similarity and paginated with a keyset
+//! cursor on (rank, created at, id).
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::db::sql::{self, like_pattern, search_terms, Bind, Conditions};
+use crate::models::MessageKind;
+
+/// Text search configurations messages are indexed and searched with
+pub const SEARCH_CONFIGS: [&str; 3] = ["english", "russian", "simple"];
+
+/// Highlight markers passed to `ts_headline`, turned into offsets by `Snippet`
+const MARK_START: char = '\u{27E6}';
+const MARK_END: char = '\u{27E7}';
+
+/// Characters of context on each side of a literal match
+const SNIPPET_CONTEXT: usize = 60;
+
+/// Who wrote a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SenderSide {
+    Customer,
+    Operator,
+    /// Internal notes
+    Note,
+}
+
+impl SenderSide {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "customer" => Some(SenderSide::Customer),
+            "operator" => Some(SenderSide::Operator),
+            "note" => Some(SenderSide::Note),
+            _ => None,
+        }
+    }
+}
+
+/// Media filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaFilter {
+    /// Text-only messages
+    None,
+    /// Messages with any media
+    Any,
+    /// Messages with this media type
+    Type(String),
+}
+
+impl MediaFilter {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "none" => MediaFilter::None,
+            "any" => MediaFilter::Any,
+            media_type => MediaFilter::Type(media_type.to_string()),
+        }
+    }
+}
+
+/// Message search parameters
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    pub query: String,
+    pub conversation_id: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub sender: Option<SenderSide>,
+    /// Operator who wrote the message or note
+    pub operator_id: Option<Uuid>,
+    pub media: Option<MediaFilter>,
+    /// Conversations with this tag
+    pub tag_id: Option<Uuid>,
+    /// Conversation status
+    pub status: Option<String>,
+    /// Only unassigned conversations and those assigned to these users
+    /// (the searcher's teammates), `None` when unrestricted
+    pub assignees: Option<Vec<Uuid>>,
+}
+
+/// Position of a result in the ranked order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchCursor {
+    pub rank: f32,
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl SearchCursor {
+    /// Encode as `<rank bits in hex>:<created at in microseconds>:<id>`.
+    /// The rank is kept bit-exact so the keyset comparison is stable.
+    pub fn encode(&self) -> String {
+        format!("{:08x}:{}:{}", self.rank.to_bits(), self.created_at.timestamp_micros(), self.id)
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.splitn(3, ':');
+        let rank = f32::from_bits(u32::from_str_radix(parts.next()?, 16).ok()?);
+        let created_at = DateTime::from_timestamp_micros(parts.next()?.parse().ok()?)?;
+        let id = Uuid::parse_str(parts.next()?).ok()?;
+        rank.is_finite().then_some(Self { rank, created_at, id })
+    }
+}
+
+/// Part of a message around the match. `highlights` are `[start, end)`
+/// character offsets into `text`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Snippet {
+    pub text: String,
+    pub highlights: Vec<[usize; 2]>,
+}
+
+impl Snippet {
+    /// Parse `ts_headline` output delimited with `MARK_START`/`MARK_END`
+    pub fn from_marked(marked: &str) -> Self {
+        let mut text = String::with_capacity(marked.len());
+        let mut highlights = Vec::new();
+        let mut position = 0;
+        let mut start = None;
+
+        for ch in marked.chars() {
+            match ch {
+                MARK_START => start = Some(position),
+                MARK_END => {
+                    if let Some(start) = start.take() {
+                        highlights.push([start, position]);
+                    }
+                }
+                _ => {
+                    text.push(ch);
+                    position += 1;
+                }
+            }
+        }
+
+        Self { text, highlights }
+    }
+
+    /// Window of `content` around the first literal occurrence of a term,
+    /// with every occurrence in the window highlighted
+    pub fn around_terms(content: &str, terms: &[String]) -> Self {
+        let chars: Vec<char> = content.chars().collect();
+        let lower: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+        let terms: Vec<Vec<char>> = terms
+            .iter()
+            .map(|t| t.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect::<Vec<_>>())
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        let mut matches = Vec::new();
+        let mut i = 0;
+        while i < lower.len() {
+            match terms.iter().find(|t| lower[i..].starts_with(t)) {
+                Some(term) => {
+                    matches.push([i, i + term.len()]);
+                    i += term.len();
+                }
+                None => i += 1,
+            }
+        }
+
+        let first = matches.first().map(|m| m[0]).unwrap_or(0);
+        let window_start = first.saturating_sub(SNIPPET_CONTEXT);
+        let window_end = (first + SNIPPET_CONTEXT * 2).min(chars.len());
+
+        let mut text: String = chars[window_start..window_end].iter().collect();
+        let mut offset = 0;
+        if window_start > 0 {
+            text.insert(0, '…');
+            offset = 1;
+        }
+        if window_end < chars.len() {
+            text.push('…');
+        }
+
+        let highlights = matches
+            .into_iter()
+            .filter(|m| m[0] >= window_start && m[1] <= window_end)
+            .map(|m| [m[0] - window_start + offset, m[1] - window_start + offset])
+            .collect();
+
+        Self { text, highlights }
+    }
+}
+
+/// A search hit with its conversation and customer
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub message_id: Uuid,
+    pub rank: f32,
+    pub snippet: Snippet,
+    pub conversation_status: String,
+    pub assignee_id: Option<Uuid>,
+    pub customer_id: i64,
+    pub customer_first_name: String,
+    pub customer_last_name: Option<String>,
+    pub customer_username: Option<String>,
+}
+
+/// One page of search hits in ranked order
+#[derive(Debug, Clone)]
+pub struct SearchPage {
+    pub hits: Vec<SearchHit>,
+    pub next_cursor: Option<SearchCursor>,
+}
+
+fn tsvector(config: &str) -> String {
+    format!("to_tsvector('{}', m.content)", config)
+}
+
+fn tsquery(config: &str, query: &str) -> String {
+    format!("websearch_to_tsquery('{}', {})", config, query)
+}
+
+/// Filters shared by the search query; `$1` is the query and `$2` its LIKE pattern
+fn conditions(filter: &SearchFilter) -> Conditions {
+    let mut conditions = Conditions::default();
+    let query = conditions.bind(Bind::Text(filter.query.clone()));
+    let pattern = conditions.bind(Bind::Text(like_pattern(filter.query.trim())));
+
+    conditions.push("m.__deleted_at__ IS NULL".to_string());
+    conditions.push("c.__deleted_at__ IS NULL".to_string());
+
+    let mut matches: Vec<String> = SEARCH_CONFIGS
+        .iter()
+        .map(|config| format!("{} @@ {}", tsvector(config), tsquery(config, &query)))
+        .collect();
+    matches.push(format!("m.content ILIKE {}", pattern));
+    conditions.push(format!("({})", matches.join(" OR ")));
+
+    if let Some(assignees) = &filter.assignees {
+        let assignees = conditions.bind(Bind::Ids(assignees.clone()));
+        conditions.push(format!("(c.user_id IS NULL OR c.user_id = ANY({}))", assignees));
+    }
+
+    if let Some(conversation_id) = filter.conversation_id {
+        let conversation_id = conditions.bind(Bind::Id(conversation_id));
+        conditions.push(format!("m.conversation_id = {}", conversation_id));
+    }
+
+    if let Some(from) = filter.from {
+        let from = conditions.bind(Bind::Time(from));
+        conditions.push(format!("m.__created_at__ >= {}", from));
+    }
+
+    if let Some(to) = filter.to {
+        let to = conditions.bind(Bind::Time(to));
+        conditions.push(format!("m.__created_at__ < {}", to));
+    }
+
+    match filter.sender {
+        Some(SenderSide::Customer) => {
+            conditions.push(format!("m.from_user = FALSE AND m.kind = '{}'", MessageKind::Chat.as_str()));
+        }
+        Some(SenderSide::Operator) => {
+            conditions.push(format!("m.from_user = TRUE AND m.kind = '{}'", MessageKind::Chat.as_str()));
+        }
+        Some(SenderSide::Note) => {
+            conditions.push(format!("m.kind = '{}'", MessageKind::Note.as_str()));
+        }
+        None => {}
+    }
+
+    if let Some(operator_id) = filter.operator_id {
+        let operator_id = conditions.bind(Bind::Id(operator_id));
+        conditions.push(format!("m.user_id = {}", operator_id));
+    }
+
+    match &filter.media {
+        Some(MediaFilter::None) => conditions.push("m.media_type IS NULL".to_string()),
+        Some(MediaFilter::Any) => conditions.push("m.media_type IS NOT NULL".to_string()),
+        Some(MediaFilter::Type(media_type)) => {
+            let media_type = conditions.bind(Bind::Text(media_type.clone()));
+            conditions.push(format!("m.media_type = {}", media_type));
+        }
+        None => {}
+    }
+
+    if let Some(tag_id) = filter.tag_id {
+        let tag_id = conditions.bind(Bind::Id(tag_id));
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM conversation_tags ct \
+             WHERE ct.conversation_id = c.id AND ct.tag_id = {} AND ct.__deleted_at__ IS NULL)",
+            tag_id
+        ));
+    }
+
+    if let Some(status) = &filter.status {
+        let status = conditions.bind(Bind::Text(status.clone()));
+        conditions.push(format!("c.status = {}", status));
+    }
+
+    conditions
+}
+
+/// Best full-text rank or trigram similarity of `m.content` against `$1`
+fn rank_sql() -> String {
+    let mut ranks: Vec<String> = SEARCH_CONFIGS
+        .iter()
+        .map(|config| format!("ts_rank({}, {})", tsvector(config), tsquery(config, "$1")))
+        .collect();
+    ranks.push("similarity(m.content, $1)".to_string());
+    format!("GREATEST({})", ranks.join(", "))
+}
+
+/// `ts_headline` under the first configuration the message matches, NULL
+/// for literal-only matches
+fn headline_sql(options: &str) -> String {
+    let branches: Vec<String> = SEARCH_CONFIGS
+        .iter()
+        .map(|config| {
+            format!(
+                "WHEN {v} @@ {q} THEN ts_headline('{c}', m.content, {q}, {o})",
+                v = tsvector(config),
+                q = tsquery(config, "$1"),
+                c = config,
+                o = options
+            )
+        })
+        .collect();
+    format!("CASE {} END", branches.join(" "))
+}
+
+/// Search messages, best matches first
+pub async fn search(
+    storehaus: &StoreHaus,
+    filter: &SearchFilter,
+    after: Option<SearchCursor>,
+    limit: i64,
+) -> Result<SearchPage> {
+    let mut conditions = conditions(filter);
+
+    let options = conditions.bind(Bind::Text(format!(
+        "StartSel={}, StopSel={}, MaxWords=35, MinWords=15, MaxFragments=2, FragmentDelimiter=\" … \"",
+        MARK_START, MARK_END
+    )));
+
+    let keyset = match after {
+        Some(cursor) => {
+            let rank = conditions.bind(Bind::Real(cursor.rank));
+            let created_at = conditions.bind(Bind::Time(cursor.created_at));
+            let id = conditions.bind(Bind::Id(cursor.id));
+            format!("WHERE (r.rank, r.created_at, r.id) < ({}, {}, {})", rank, created_at, id)
+        }
+        None => String::new(),
+    };
+
+    // Rank and page first, then build headlines and context for the page only
+    let search_sql = format!(
+        "SELECT p.id, p.rank, p.created_at, {headline} AS headline, m.content, \
+                c.status, c.user_id AS assignee_id, \
+                tu.id AS customer_id, tu.first_name, tu.last_name, tu.username \
+         FROM ( \
+             SELECT r.id, r.rank, r.created_at, r.conversation_id FROM ( \
+                 SELECT m.id, m.conversation_id, m.__created_at__ AS created_at, {rank}::real AS rank \
+                 FROM messages m JOIN conversations c ON c.id = m.conversation_id \
+                 WHERE {conditions} \
+             ) r {keyset} \
+             ORDER BY r.rank DESC, r.created_at DESC, r.id DESC \
+             LIMIT {limit} \
+         ) p \
+         JOIN messages m ON m.id = p.id \
+         JOIN conversations c ON c.id = p.conversation_id \
+         JOIN telegram_users tu ON tu.id = c.telegram_user_id \
+         ORDER BY p.rank DESC, p.created_at DESC, p.id DESC",
+        rank = rank_sql(),
+        headline = headline_sql(&options),
+        conditions = conditions.sql(),
+        keyset = keyset,
+        limit = limit.max(1),
+    );
+
+    let rows = sql::apply(sqlx::query(&search_sql), conditions.binds())
+        .fetch_all(storehaus.pool())
+        .await?;
+
+    let terms = search_terms(&filter.query);
+    let mut hits = Vec::with_capacity(rows.len());
+    let mut last = None;
+    for row in rows {
+        let message_id: Uuid = row.try_get("id")?;
+        let rank: f32 = row.try_get("rank")?;
+        let created_at: DateTime<Utc> = row.try_get("created_at")?;
+        let snippet = match row.try_get::<Option<String>, _>("headline")? {
+            Some(headline) => Snippet::from_marked(&headline),
+            None => Snippet::around_terms(&row.try_get::<String, _>("content")?, &terms),
+        };
+
+        last = Some(SearchCursor { rank, created_at, id: message_id });
+        hits.push(SearchHit {
+            message_id,
+            rank,
+            snippet,
+            conversation_status: row.try_get("status")?,
+            assignee_id: row.try_get("assignee_id")?,
+            customer_id: row.try_get("customer_id")?,
+            customer_first_name: row.try_get("first_name")?,
+            customer_last_name: row.try_get("last_name")?,
+            customer_username: row.try_get("username")?,
+        });
+    }
+
+    let next_cursor = if hits.len() as i64 == limit.max(1) { last } else { None };
+
+    Ok(SearchPage { hits, next_cursor })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = SearchCursor {
+            rank: 0.0607927,
+            created_at: DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+        assert_eq!(SearchCursor::parse(&cursor.encode()), Some(cursor));
+        assert_eq!(SearchCursor::parse("7fc00000:1:00000000-0000-0000-0000-000000000000"), None);
+        assert_eq!(SearchCursor::parse("garbage"), None);
+    }
+
+    #[test]
+    fn test_snippet_from_headline() {
+        let marked = format!("my {}order{} has not {}arrived{}", MARK_START, MARK_END, MARK_START, MARK_END);
+        let snippet = Snippet::from_marked(&marked);
+        assert_eq!(snippet.text, "my order has not arrived");
+        assert_eq!(snippet.highlights, vec![[3, 8], [17, 24]]);
+    }
+
+    #[test]
+    fn test_snippet_around_terms() {
+        let content = format!("{}Order #A-1042 was shipped, order #a-1042 again", "x".repeat(100));
+        let snippet = Snippet::around_terms(&content, &["a-1042".to_string()]);
+        assert!(snippet.text.starts_with('…'));
+        assert_eq!(snippet.highlights.len(), 2);
+
+        let chars: Vec<char> = snippet.text.chars().collect();
+        let [start, end] = snippet.highlights[0];
+        assert_eq!(chars[start..end].iter().collect::<String>(), "A-1042");
+    }
+}
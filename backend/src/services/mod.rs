@@ -1,2 +1,26 @@
 // Services module (business logic)
-// To be implemented as needed
\ No newline at end of file
+
+pub mod access;
+pub mod assignments;
+pub mod audit;
+pub mod bot_texts;
+pub mod conversation_list;
+pub mod conversations;
+pub mod custom_fields;
+pub mod customer_notifications;
+pub mod customer_profiles;
+pub mod dispositions;
+pub mod idle;
+pub mod message_history;
+pub mod message_search;
+pub mod notes;
+pub mod presence;
+pub mod priority;
+pub mod queue;
+pub mod read_state;
+pub mod routing;
+pub mod settings;
+pub mod sla;
+pub mod snooze;
+pub mod tags;
+pub mod trash;
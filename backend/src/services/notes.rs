@@ -0,0 +1,187 @@
+//! Internal notes and @mentions
+//!
+//! Notes are `Message` rows with `MessageKind::Note`. They live in the
+//! conversation timeline but are never sent to Telegram. Mentioning an
+//! operator (`@alias`, `@name` or `@email-login`) notifies them over the
+//! WebSocket and, if configured, via their Telegram notification chat.
+
+use anyhow::Result;
+use serde_json::json;
+use storehaus::prelude::*;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use tracing::{info, warn};
+
+use crate::models::{Message, User};
+use crate::telegram::BotManager;
+use crate::websocket::{WebSocketEvent, WebSocketManager};
+
+/// Maximum note preview length in Telegram notifications
+const PREVIEW_LEN: usize = 100;
+
+/// Extract lowercased `@handles` from note text, without duplicates.
+/// E-mail addresses (`a@b.com`) are not treated as mentions.
+pub fn extract_mentions(content: &str) -> Vec<String> {
+    let mut handles = Vec::new();
+    let chars: Vec<char> = content.chars().collect();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let preceded_by_word = i > 0 && (chars[i - 1].is_alphanumeric() || chars[i - 1] == '_');
+        if chars[i] == '@' && !preceded_by_word {
+            let start = i + 1;
+            let mut end = start;
+            while end < chars.len() && is_handle_char(chars[end]) {
+                end += 1;
+            }
+
+            let handle: String = chars[start..end].iter().collect();
+            let handle = handle.trim_end_matches(['.', '-']).to_lowercase();
+            if !handle.is_empty() && !handles.contains(&handle) {
+                handles.push(handle);
+            }
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+
+    handles
+}
+
+fn is_handle_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '-'
+}
+
+/// Normalize a name for handle matching: lowercase, no whitespace
+fn normalize(name: &str) -> String {
+    name.split_whitespace().collect::<String>().to_lowercase()
+}
+
+/// Check whether `handle` refers to an operator with the given name,
+/// e-mail and alias
+pub fn matches_handle(handle: &str, name: &str, email: &str, alias: Option<&str>) -> bool {
+    let login = email.split('@').next().unwrap_or_default().to_lowercase();
+    let first_name = name.split_whitespace().next().unwrap_or_default().to_lowercase();
+
+    handle == login
+        || handle == normalize(name)
+        || handle == first_name
+        || alias.map(|a| handle == normalize(a)).unwrap_or(false)
+}
+
+/// Resolve `@handles` to active users
+pub async fn resolve_mentions(storehaus: &StoreHaus, handles: &[String]) -> Result<Vec<User>> {
+    if handles.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let user_store = storehaus.get_store::<GenericStore<User>>("users")?;
+
+    let query = QueryBuilder::new().filter(QueryFilter::eq("is_active", json!(true)));
+    let users = user_store.find(query).await?;
+
+    Ok(users
+        .into_iter()
+        .filter(|user| {
+            let alias = user.parsed_settings().operator_alias;
+            handles
+                .iter()
+                .any(|h| matches_handle(h, &user.name, &user.email, alias.as_deref()))
+        })
+        .collect())
+}
+
+/// Notify mentioned operators about a note (WebSocket + Telegram)
+pub async fn notify_mentions(
+    ws_manager: &WebSocketManager,
+    bot_manager: &BotManager,
+    note: &Message,
+    author_name: &str,
+    mentioned: &[User],
+) {
+    for user in mentioned {
+        let ws_event = WebSocketEvent::NoteMention {
+            conversation_id: note.conversation_id,
+            message_id: note.id,
+            content: note.content.clone(),
+            author_id: note.user_id,
+            author_name: author_name.to_string(),
+        };
+
+        if let Err(e) = ws_manager.send_to_user(&user.id, ws_event).await {
+            // Not connected right now; the Telegram notification below still applies
+            info!("Could not deliver NoteMention to user {}: {}", user.id, e);
+        }
+
+        let settings = user.parsed_settings();
+        if !settings.notifications_enabled {
+            continue;
+        }
+
+        let Some(chat_id) = settings
+            .telegram_notifications_user_id
+            .as_deref()
+            .and_then(|id| id.parse::<i64>().ok())
+        else {
+            continue;
+        };
+
+        let Some(bot) = bot_manager.bot().await else {
+            continue;
+        };
+
+        let preview: String = note.content.chars().take(PREVIEW_LEN).collect();
+        let preview = if preview.len() < note.content.len() {
+            format!("{}...", preview)
+        } else {
+            preview
+        };
+
+        let notification = format!(
+            "📝 <b>You were mentioned in a note</b>\n\n\
+            By: {}\n\
+            Note: {}\n\n\
+            Please log in to the system to view the conversation.",
+            escape_html(author_name),
+            escape_html(&preview)
+        );
+
+        if let Err(e) = bot
+            .send_message(ChatId(chat_id), notification)
+            .parse_mode(ParseMode::Html)
+            .await
+        {
+            warn!("Failed to send mention notification to user {}: {}", user.email, e);
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_mentions() {
+        assert_eq!(
+            extract_mentions("@Anna approved the refund, cc @bob.smith and @anna."),
+            vec!["anna".to_string(), "bob.smith".to_string()]
+        );
+        assert!(extract_mentions("write to support@example.com").is_empty());
+        assert!(extract_mentions("just an @ sign").is_empty());
+    }
+
+    #[test]
+    fn test_matches_handle() {
+        assert!(matches_handle("anna", "Anna Petrova", "a.petrova@example.com", None));
+        assert!(matches_handle("annapetrova", "Anna Petrova", "a.petrova@example.com", None));
+        assert!(matches_handle("a.petrova", "Anna Petrova", "a.petrova@example.com", None));
+        assert!(!matches_handle("support_anna", "Anna Petrova", "a.petrova@example.com", Some("Support Anna")));
+        assert!(matches_handle("supportanna", "Anna Petrova", "a.petrova@example.com", Some("Support Anna")));
+        assert!(!matches_handle("bob", "Anna Petrova", "a.petrova@example.com", None));
+    }
+}
@@ -0,0 +1,384 @@
+//! Operator presence per conversation and the soft reply lock
+//!
+//! Clients report "viewing", "typing" or "idle" for the conversation they
+//! have open and repeat the report while it holds. Entries are cleared when
+//! the operator's last WebSocket connection closes and expire when the
+//! reports stop on a connection that stays open. The first operator who
+//! starts typing holds the reply lock until they stop typing, send or go
+//! quiet. State lives in memory: it is short-lived and only matters while
+//! operators are connected.
+
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::models::{Conversation, ReplyLockMode};
+use crate::websocket::{WebSocketEvent, WebSocketManager};
+
+/// A viewing report is valid this long without a refresh
+const VIEWING_TTL_SECONDS: i64 = 60;
+
+/// A typing report (and the reply lock) is valid this long without a refresh
+const TYPING_TTL_SECONDS: i64 = 15;
+
+/// What an operator is doing in a conversation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceState {
+    Viewing,
+    Typing,
+    /// Left the conversation
+    Idle,
+}
+
+impl PresenceState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PresenceState::Viewing => "viewing",
+            PresenceState::Typing => "typing",
+            PresenceState::Idle => "idle",
+        }
+    }
+
+    fn ttl(&self) -> Duration {
+        match self {
+            PresenceState::Typing => Duration::seconds(TYPING_TTL_SECONDS),
+            _ => Duration::seconds(VIEWING_TTL_SECONDS),
+        }
+    }
+}
+
+/// One operator present in a conversation
+#[derive(Debug, Clone, Serialize)]
+pub struct PresenceEntry {
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub state: PresenceState,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PresenceEntry {
+    fn is_live(&self, now: DateTime<Utc>) -> bool {
+        now - self.updated_at <= self.state.ttl()
+    }
+}
+
+/// Holder of a conversation's reply lock
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplyLock {
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub acquired_at: DateTime<Utc>,
+}
+
+/// Presence of a conversation
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationPresence {
+    pub conversation_id: Uuid,
+    pub operators: Vec<PresenceEntry>,
+    pub reply_lock: Option<ReplyLock>,
+}
+
+/// Presence and reply locks of all conversations
+#[derive(Debug, Default)]
+pub struct PresenceBoard {
+    entries: HashMap<Uuid, HashMap<Uuid, PresenceEntry>>,
+    locks: HashMap<Uuid, ReplyLock>,
+}
+
+impl PresenceBoard {
+    /// Record a report. Returns whether the visible state changed.
+    pub fn update(
+        &mut self,
+        conversation_id: Uuid,
+        user_id: Uuid,
+        user_name: &str,
+        state: PresenceState,
+        now: DateTime<Utc>,
+    ) -> bool {
+        self.expire(now);
+
+        let conversation = self.entries.entry(conversation_id).or_default();
+        let previous = conversation.get(&user_id).map(|e| e.state);
+
+        if state == PresenceState::Idle {
+            conversation.remove(&user_id);
+        } else {
+            conversation.insert(
+                user_id,
+                PresenceEntry {
+                    user_id,
+                    user_name: user_name.to_string(),
+                    state,
+                    updated_at: now,
+                },
+            );
+        }
+        if conversation.is_empty() {
+            self.entries.remove(&conversation_id);
+        }
+
+        match state {
+            PresenceState::Typing => {
+                self.locks.entry(conversation_id).or_insert_with(|| ReplyLock {
+                    user_id,
+                    user_name: user_name.to_string(),
+                    acquired_at: now,
+                });
+            }
+            _ => self.release(conversation_id, user_id),
+        }
+
+        previous != Some(state) && !(previous.is_none() && state == PresenceState::Idle)
+    }
+
+    /// Release the reply lock if `user_id` holds it
+    pub fn release(&mut self, conversation_id: Uuid, user_id: Uuid) {
+        if self.locks.get(&conversation_id).is_some_and(|lock| lock.user_id == user_id) {
+            self.locks.remove(&conversation_id);
+        }
+    }
+
+    /// Drop all of `user_id`'s entries and locks. Returns the conversations
+    /// they were in with their entry there.
+    pub fn remove_user(&mut self, user_id: Uuid) -> Vec<(Uuid, PresenceEntry)> {
+        let mut left = Vec::new();
+        self.entries.retain(|conversation_id, conversation| {
+            if let Some(entry) = conversation.remove(&user_id) {
+                left.push((*conversation_id, entry));
+            }
+            !conversation.is_empty()
+        });
+        self.locks.retain(|_, lock| lock.user_id != user_id);
+        left
+    }
+
+    /// Current reply lock holder
+    pub fn lock_holder(&self, conversation_id: Uuid, now: DateTime<Utc>) -> Option<&ReplyLock> {
+        let lock = self.locks.get(&conversation_id)?;
+        let typing = self
+            .entries
+            .get(&conversation_id)
+            .and_then(|c| c.get(&lock.user_id))
+            .is_some_and(|e| e.state == PresenceState::Typing && e.is_live(now));
+        typing.then_some(lock)
+    }
+
+    /// Live presence of a conversation
+    pub fn snapshot(&self, conversation_id: Uuid, now: DateTime<Utc>) -> ConversationPresence {
+        let mut operators: Vec<PresenceEntry> = self
+            .entries
+            .get(&conversation_id)
+            .map(|c| c.values().filter(|e| e.is_live(now)).cloned().collect())
+            .unwrap_or_default();
+        operators.sort_by_key(|e| e.updated_at);
+
+        ConversationPresence {
+            conversation_id,
+            operators,
+            reply_lock: self.lock_holder(conversation_id, now).cloned(),
+        }
+    }
+
+    /// Drop stale entries and locks
+    fn expire(&mut self, now: DateTime<Utc>) {
+        self.entries.retain(|_, conversation| {
+            conversation.retain(|_, e| e.is_live(now));
+            !conversation.is_empty()
+        });
+
+        let entries = &self.entries;
+        self.locks.retain(|conversation_id, lock| {
+            entries
+                .get(conversation_id)
+                .and_then(|c| c.get(&lock.user_id))
+                .is_some_and(|e| e.state == PresenceState::Typing)
+        });
+    }
+}
+
+/// Whether `sender`'s reply collides with another operator: the
+/// conversation is assigned to someone else and a third operator holds the
+/// reply lock. In `Warn` mode a confirmed (`force`) reply goes through.
+pub fn reply_collides(
+    mode: ReplyLockMode,
+    conversation: &Conversation,
+    sender: Uuid,
+    lock: Option<&ReplyLock>,
+    force: bool,
+) -> bool {
+    let Some(lock) = lock else {
+        return false;
+    };
+    if lock.user_id == sender || conversation.user_id.is_none_or(|assignee| assignee == sender) {
+        return false;
+    }
+
+    match mode {
+        ReplyLockMode::Off => false,
+        ReplyLockMode::Warn => !force,
+        ReplyLockMode::Enforce => true,
+    }
+}
+
+static BOARD: Lazy<RwLock<PresenceBoard>> = Lazy::new(|| RwLock::new(PresenceBoard::default()));
+
+/// Record a presence report and tell the other operators in the conversation
+pub async fn report(
+    ws_manager: &WebSocketManager,
+    conversation_id: Uuid,
+    user_id: Uuid,
+    user_name: &str,
+    state: PresenceState,
+) -> ConversationPresence {
+    let now = Utc::now();
+    let (changed, presence) = {
+        let mut board = BOARD.write().await;
+        let changed = board.update(conversation_id, user_id, user_name, state, now);
+        (changed, board.snapshot(conversation_id, now))
+    };
+
+    if changed {
+        let ws_event = WebSocketEvent::ConversationPresence {
+            conversation_id,
+            user_id,
+            user_name: user_name.to_string(),
+            state: state.as_str().to_string(),
+        };
+
+        for other in presence.operators.iter().filter(|e| e.user_id != user_id) {
+            if let Err(e) = ws_manager.send_to_user(&other.user_id, ws_event.clone()).await {
+                warn!("Failed to send presence event to user {}: {}", other.user_id, e);
+            }
+        }
+    }
+
+    presence
+}
+
+/// The user's last WebSocket connection closed: they left every conversation
+pub async fn disconnect(ws_manager: &WebSocketManager, user_id: Uuid) {
+    let now = Utc::now();
+    let left: Vec<(PresenceEntry, ConversationPresence)> = {
+        let mut board = BOARD.write().await;
+        let entries = board.remove_user(user_id);
+        entries.into_iter().map(|(id, entry)| (entry, board.snapshot(id, now))).collect()
+    };
+
+    for (entry, presence) in left {
+        let ws_event = WebSocketEvent::ConversationPresence {
+            conversation_id: presence.conversation_id,
+            user_id,
+            user_name: entry.user_name,
+            state: PresenceState::Idle.as_str().to_string(),
+        };
+        for other in &presence.operators {
+            if let Err(e) = ws_manager.send_to_user(&other.user_id, ws_event.clone()).await {
+                warn!("Failed to send presence event to user {}: {}", other.user_id, e);
+            }
+        }
+    }
+}
+
+/// Live presence of a conversation
+pub async fn presence(conversation_id: Uuid) -> ConversationPresence {
+    BOARD.read().await.snapshot(conversation_id, Utc::now())
+}
+
+/// Current reply lock of a conversation
+pub async fn lock_holder(conversation_id: Uuid) -> Option<ReplyLock> {
+    BOARD.read().await.lock_holder(conversation_id, Utc::now()).cloned()
+}
+
+/// The operator sent their reply: they stop composing
+pub async fn release(conversation_id: Uuid, user_id: Uuid) {
+    BOARD.write().await.release(conversation_id, user_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reply_lock() {
+        let mut board = PresenceBoard::default();
+        let conversation = Uuid::new_v4();
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+
+        assert!(board.update(conversation, alice, "Alice", PresenceState::Typing, now));
+        assert!(board.update(conversation, bob, "Bob", PresenceState::Typing, now));
+        assert_eq!(board.lock_holder(conversation, now).map(|l| l.user_id), Some(alice));
+
+        // Alice stops typing, the lock is free again
+        assert!(board.update(conversation, alice, "Alice", PresenceState::Viewing, now));
+        assert!(board.lock_holder(conversation, now).is_none());
+
+        // Bob's next report picks it up
+        assert!(!board.update(conversation, bob, "Bob", PresenceState::Typing, now));
+        assert_eq!(board.lock_holder(conversation, now).map(|l| l.user_id), Some(bob));
+    }
+
+    #[test]
+    fn test_reply_collides() {
+        let (assignee, sender, holder) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let conversation = Conversation {
+            user_id: Some(assignee),
+            ..Conversation::new_waiting(1, None)
+        };
+        let lock = ReplyLock {
+            user_id: holder,
+            user_name: "Holder".to_string(),
+            acquired_at: Utc::now(),
+        };
+
+        assert!(reply_collides(ReplyLockMode::Enforce, &conversation, sender, Some(&lock), true));
+        assert!(reply_collides(ReplyLockMode::Warn, &conversation, sender, Some(&lock), false));
+        assert!(!reply_collides(ReplyLockMode::Warn, &conversation, sender, Some(&lock), true));
+        assert!(!reply_collides(ReplyLockMode::Off, &conversation, sender, Some(&lock), false));
+        assert!(!reply_collides(ReplyLockMode::Enforce, &conversation, sender, None, false));
+        assert!(!reply_collides(ReplyLockMode::Enforce, &conversation, holder, Some(&lock), false));
+        assert!(!reply_collides(ReplyLockMode::Enforce, &conversation, assignee, Some(&lock), false));
+    }
+
+    #[test]
+    fn test_presence_expires() {
+        let mut board = PresenceBoard::default();
+        let conversation = Uuid::new_v4();
+        let alice = Uuid::new_v4();
+        let now = Utc::now();
+
+        board.update(conversation, alice, "Alice", PresenceState::Typing, now);
+        let later = now + Duration::seconds(TYPING_TTL_SECONDS + 1);
+
+        assert!(board.lock_holder(conversation, later).is_none());
+        assert!(board.snapshot(conversation, later).operators.is_empty());
+        assert!(!board.update(conversation, alice, "Alice", PresenceState::Idle, later));
+    }
+
+    #[test]
+    fn test_remove_user() {
+        let mut board = PresenceBoard::default();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+
+        board.update(first, alice, "Alice", PresenceState::Typing, now);
+        board.update(first, bob, "Bob", PresenceState::Viewing, now);
+        board.update(second, alice, "Alice", PresenceState::Viewing, now);
+
+        let mut left: Vec<Uuid> = board.remove_user(alice).into_iter().map(|(id, _)| id).collect();
+        left.sort();
+        let mut expected = vec![first, second];
+        expected.sort();
+        assert_eq!(left, expected);
+
+        assert!(board.lock_holder(first, now).is_none());
+        assert_eq!(board.snapshot(first, now).operators.len(), 1);
+        assert!(board.snapshot(second, now).operators.is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! Conversation priority and category
+//!
+//! Operators set priority and category by hand; admin-defined rules set
+//! them from the customer's tags (e.g. "VIP"), the deep-link topic the
+//! customer picked when starting the bot and keywords in customer
+//! messages. Rules run on every customer message and only ever raise the
+//! priority or fill an empty category, so they never undo an operator's
+//! choice. Every change is broadcast as `ConversationPriorityChanged`.
+
+use anyhow::Result;
+use std::collections::HashSet;
+use storehaus::prelude::*;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::models::{Conversation, ConversationPriority, PriorityRule};
+use crate::services::tags;
+use crate::websocket::{WebSocketEvent, WebSocketManager};
+
+/// Longest accepted category
+pub const MAX_CATEGORY_LENGTH: usize = 50;
+
+/// What the matching rules ask for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleOutcome {
+    /// Highest priority among the matching rules
+    pub priority: Option<ConversationPriority>,
+    /// Category of the first matching rule that has one
+    pub category: Option<String>,
+}
+
+/// Trimmed category, `None` when empty
+pub fn normalize_category(category: &str) -> Option<String> {
+    let category = category.trim();
+    (!category.is_empty()).then(|| category.to_string())
+}
+
+/// Whether `rule` matches a conversation with this source, customer tags
+/// and message text. Every condition the rule has must match.
+pub fn matches(rule: &PriorityRule, source: Option<&str>, tag_ids: &HashSet<Uuid>, text: &str) -> bool {
+    if rule.tag_id.is_some_and(|tag_id| !tag_ids.contains(&tag_id)) {
+        return false;
+    }
+    if rule.source.as_deref().is_some_and(|s| Some(s) != source) {
+        return false;
+    }
+
+    let keywords = rule.parsed_keywords();
+    if !keywords.is_empty() {
+        let text = text.to_lowercase();
+        if !keywords.iter().any(|k| text.contains(&k.to_lowercase())) {
+            return false;
+        }
+    }
+
+    rule.has_conditions()
+}
+
+/// Combine the active rules matching a conversation. `rules` must be in
+/// position order.
+pub fn evaluate(rules: &[PriorityRule], source: Option<&str>, tag_ids: &HashSet<Uuid>, text: &str) -> RuleOutcome {
+    let mut outcome = RuleOutcome::default();
+
+    for rule in rules.iter().filter(|r| r.is_active && matches(r, source, tag_ids, text)) {
+        if outcome.priority.is_none_or(|p| rule.priority.rank() > p.rank()) {
+            outcome.priority = Some(rule.priority);
+        }
+        if outcome.category.is_none() {
+            outcome.category = rule.category.clone();
+        }
+    }
+
+    outcome
+}
+
+/// Apply a rule outcome: raise the priority, fill an empty category.
+/// Returns whether the conversation changed.
+pub fn apply_outcome(conversation: &mut Conversation, outcome: RuleOutcome) -> bool {
+    let mut changed = false;
+
+    if let Some(priority) = outcome.priority {
+        if priority.rank() > conversation.priority.rank() {
+            conversation.priority = priority;
+            changed = true;
+        }
+    }
+
+    if conversation.category.is_none() && outcome.category.is_some() {
+        conversation.category = outcome.category;
+        changed = true;
+    }
+
+    changed
+}
+
+/// Active rules in evaluation order
+pub async fn active_rules(storehaus: &StoreHaus) -> Result<Vec<PriorityRule>> {
+    let store = storehaus.get_store::<GenericStore<PriorityRule>>("priority_rules")?;
+
+    let query = QueryBuilder::new().filter(QueryFilter::eq("is_active", serde_json::json!(true)));
+    let mut rules = store.find(query).await?;
+    rules.sort_by_key(|r| (r.position, r.__created_at__));
+    Ok(rules)
+}
+
+/// Run the rules against a customer message. Changes the conversation in
+/// place (the caller saves it) and returns whether anything changed.
+pub async fn apply_rules(storehaus: &StoreHaus, conversation: &mut Conversation, text: &str) -> Result<bool> {
+    let rules = active_rules(storehaus).await?;
+    if rules.is_empty() {
+        return Ok(false);
+    }
+
+    let tag_ids: HashSet<Uuid> = tags::telegram_user_tags(storehaus, conversation.telegram_user_id)
+        .await?
+        .into_iter()
+        .map(|t| t.id)
+        .collect();
+
+    let outcome = evaluate(&rules, conversation.source.as_deref(), &tag_ids, text);
+    Ok(apply_outcome(conversation, outcome))
+}
+
+/// Tell the console about a new priority or category
+pub async fn broadcast_change(
+    ws_manager: &WebSocketManager,
+    conversation: &Conversation,
+    changed_by: Option<Uuid>,
+) {
+    let event = WebSocketEvent::ConversationPriorityChanged {
+        conversation_id: conversation.id,
+        priority: conversation.priority.to_string(),
+        category: conversation.category.clone(),
+        changed_by,
+    };
+
+    if let Err(e) = ws_manager.broadcast_event(event).await {
+        warn!("Failed to broadcast ConversationPriorityChanged event: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(
+        tag_id: Option<Uuid>,
+        source: Option<&str>,
+        keywords: &[&str],
+        priority: ConversationPriority,
+    ) -> PriorityRule {
+        PriorityRule::create(
+            "rule".to_string(),
+            tag_id,
+            source.map(str::to_string),
+            keywords.iter().map(|k| k.to_string()).collect(),
+            priority,
+            None,
+            0,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_matches() {
+        let vip = Uuid::new_v4();
+        let tags = HashSet::from([vip]);
+
+        let vip_rule = rule(Some(vip), None, &[], ConversationPriority::High);
+        assert!(matches(&vip_rule, None, &tags, "hello"));
+        assert!(!matches(&vip_rule, None, &HashSet::new(), "hello"));
+
+        let outage = rule(None, Some("billing"), &["Refund", "charged twice"], ConversationPriority::Urgent);
+        assert!(matches(&outage, Some("billing"), &tags, "I was CHARGED TWICE"));
+        assert!(!matches(&outage, Some("billing"), &tags, "hello"));
+        assert!(!matches(&outage, Some("sales"), &tags, "refund please"));
+
+        let empty = rule(None, None, &[], ConversationPriority::Urgent);
+        assert!(!matches(&empty, None, &tags, "anything"));
+    }
+
+    #[test]
+    fn test_evaluate_and_apply() {
+        let vip = Uuid::new_v4();
+        let mut billing = rule(Some(vip), None, &[], ConversationPriority::High);
+        billing.category = Some("billing".to_string());
+        let urgent = rule(None, None, &["down"], ConversationPriority::Urgent);
+        let rules = vec![billing, urgent];
+
+        let outcome = evaluate(&rules, None, &HashSet::from([vip]), "site is down");
+        assert_eq!(outcome.priority, Some(ConversationPriority::Urgent));
+        assert_eq!(outcome.category.as_deref(), Some("billing"));
+
+        let mut conversation = Conversation::new_waiting(1, None);
+        assert!(apply_outcome(&mut conversation, outcome.clone()));
+        assert_eq!(conversation.priority, ConversationPriority::Urgent);
+        assert!(!apply_outcome(&mut conversation, outcome));
+
+        // Rules never lower a priority or replace a category
+        let lower = RuleOutcome {
+            priority: Some(ConversationPriority::Low),
+            category: Some("sales".to_string()),
+        };
+        assert!(!apply_outcome(&mut conversation, lower));
+        assert_eq!(conversation.priority, ConversationPriority::Urgent);
+        assert_eq!(conversation.category.as_deref(), Some("billing"));
+    }
+}
@@ -0,0 +1,276 @@
+//! Waiting queue positions and wait estimates
+//!
+//! The queue is every `Waiting` conversation without an operator, highest
+//! priority first and oldest first within a priority; routing hands
+//! conversations out in the same order. Estimates use the average first-response time of recent
+//! conversations spread over the operators that are online. Customers are
+//! told their position when they join the queue and again when it improves
+//! noticeably; the console gets a `QueueUpdated` event on every refresh.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::cmp::Reverse;
+use storehaus::prelude::*;
+use teloxide::Bot;
+use tokio::sync::Mutex;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::models::{Conversation, ConversationStatus, Message, OperatorStatus, User};
+use crate::services::access::ReadScope;
+use crate::services::customer_notifications;
+use crate::websocket::{WebSocketEvent, WebSocketManager};
+
+/// Number of recent answered conversations used for the estimate
+const ESTIMATE_SAMPLE_SIZE: usize = 50;
+
+/// A position improvement of at least this many places is worth a message
+const NOTIFY_STEP: i32 = 5;
+
+/// Serializes refreshes so customers are not notified twice
+static QUEUE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Queue position of a waiting conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueEntry {
+    pub conversation_id: Uuid,
+    pub telegram_user_id: i64,
+    /// 1-based position
+    pub position: usize,
+    pub waiting_since: DateTime<Utc>,
+    pub estimated_wait_seconds: Option<i64>,
+}
+
+/// Current queue state
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueSnapshot {
+    pub entries: Vec<QueueEntry>,
+    pub average_first_response_seconds: Option<f64>,
+    pub online_operators: usize,
+}
+
+/// Seconds from conversation start to the first operator reply.
+/// `messages` must be sorted oldest first.
+pub fn first_response_seconds(conversation: &Conversation, messages: &[Message]) -> Option<f64> {
+    let reply = messages.iter().find(|m| m.is_operator_reply())?;
+    let seconds = (reply.__created_at__ - conversation.__created_at__).num_seconds();
+    (seconds > 0).then_some(seconds as f64)
+}
+
+/// Estimated wait for a queue position: one average first response per
+/// "round" of online operators (at least one operator is assumed)
+pub fn estimate_wait(position: usize, average_first_response: Option<f64>, online_operators: usize) -> Option<i64> {
+    let average = average_first_response?;
+    let rounds = position.div_ceil(online_operators.max(1));
+    Some((average * rounds as f64).round() as i64)
+}
+
+/// Whether the customer should hear about their new position.
+/// Always on joining the queue, then when it improved by `NOTIFY_STEP`
+/// places or at least halved.
+pub fn should_notify(last_notified: Option<i32>, position: usize) -> bool {
+    let position = position as i32;
+    match last_notified {
+        None => true,
+        Some(last) => position < last && (last - position >= NOTIFY_STEP || position * 2 <= last),
+    }
+}
+
+/// Waiting conversations without an operator in queue order
+pub async fn waiting_conversations(storehaus: &StoreHaus) -> Result<Vec<Conversation>> {
+    let store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("status", json!(ConversationStatus::Waiting.as_str())))
+        .order_by("__created_at__", SortOrder::Asc);
+
+    let mut waiting: Vec<Conversation> =
+        store.find(query).await?.into_iter().filter(|c| c.user_id.is_none()).collect();
+    sort_queue(&mut waiting);
+    Ok(waiting)
+}
+
+/// Queue order: highest priority first, then longest waiting
+pub fn sort_queue(conversations: &mut [Conversation]) {
+    conversations.sort_by_key(|c| (Reverse(c.priority.rank()), c.__created_at__, c.id));
+}
+
+/// Average first-response time of recently answered conversations
+pub async fn recent_first_response_average(storehaus: &StoreHaus) -> Result<Option<f64>> {
+    let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+    let message_store = storehaus.get_store::<GenericStore<Message>>("messages")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::ne("status", json!(ConversationStatus::Waiting.as_str())))
+        .order_by("__created_at__", SortOrder::Desc)
+        .limit(ESTIMATE_SAMPLE_SIZE as i64);
+    let conversations = conversation_store.find(query).await?;
+
+    let mut times = Vec::new();
+    for conversation in conversations {
+        let query = QueryBuilder::new()
+            .filter(QueryFilter::eq("conversation_id", json!(conversation.id)))
+            .order_by("__created_at__", SortOrder::Asc);
+        let messages = message_store.find(query).await?;
+
+        if let Some(seconds) = first_response_seconds(&conversation, &messages) {
+            times.push(seconds);
+        }
+    }
+
+    if times.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(times.iter().sum::<f64>() / times.len() as f64))
+    }
+}
+
+/// Number of operators currently online
+async fn online_operators(storehaus: &StoreHaus) -> Result<usize> {
+    let store = storehaus.get_store::<GenericStore<User>>("users")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("is_active", json!(true)))
+        .filter(QueryFilter::eq("is_operator", json!(true)));
+    let users = store.find(query).await?;
+
+    Ok(users
+        .iter()
+        .filter(|u| u.parsed_settings().status == OperatorStatus::Online)
+        .count())
+}
+
+/// Build entries for `waiting` (already in queue order)
+fn build_entries(waiting: &[Conversation], average: Option<f64>, online_operators: usize) -> Vec<QueueEntry> {
+    waiting
+        .iter()
+        .enumerate()
+        .map(|(i, conversation)| QueueEntry {
+            conversation_id: conversation.id,
+            telegram_user_id: conversation.telegram_user_id,
+            position: i + 1,
+            waiting_since: conversation.__created_at__,
+            estimated_wait_seconds: estimate_wait(i + 1, average, online_operators),
+        })
+        .collect()
+}
+
+/// Current queue state as seen by a user with read `scope`
+pub async fn snapshot(storehaus: &StoreHaus, scope: &ReadScope) -> Result<QueueSnapshot> {
+    let waiting = waiting_conversations(storehaus).await?;
+    let average = recent_first_response_average(storehaus).await?;
+    let online = online_operators(storehaus).await?;
+
+    // Positions stay global; only the visible entries are returned
+    let entries = build_entries(&waiting, average, online)
+        .into_iter()
+        .zip(&waiting)
+        .filter(|(_, conversation)| scope.allows(conversation.user_id))
+        .map(|(entry, _)| entry)
+        .collect();
+
+    Ok(QueueSnapshot {
+        entries,
+        average_first_response_seconds: average,
+        online_operators: online,
+    })
+}
+
+/// Recompute the queue, broadcast it and tell customers about notable
+/// position changes (when `bot` is given)
+pub async fn refresh(storehaus: &StoreHaus, ws_manager: &WebSocketManager, bot: Option<&Bot>) -> Result<QueueSnapshot> {
+    let _guard = QUEUE_LOCK.lock().await;
+
+    let waiting = waiting_conversations(storehaus).await?;
+    let average = recent_first_response_average(storehaus).await?;
+    let online = online_operators(storehaus).await?;
+    let entries = build_entries(&waiting, average, online);
+
+    let ws_event = WebSocketEvent::QueueUpdated {
+        entries: entries.clone(),
+        average_first_response_seconds: average,
+    };
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast QueueUpdated event: {}", e);
+    }
+
+    if let Some(bot) = bot {
+        let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+
+        for (conversation, entry) in waiting.into_iter().zip(&entries) {
+            if !should_notify(conversation.queue_position_notified, entry.position) {
+                continue;
+            }
+
+            if let Err(e) = customer_notifications::notify_queue_position(
+                storehaus,
+                bot,
+                &conversation,
+                entry.position,
+                entry.estimated_wait_seconds,
+            )
+            .await
+            {
+                warn!("Failed to notify customer about queue position of {}: {}", conversation.id, e);
+                continue;
+            }
+
+            let conversation_id = conversation.id;
+            let mut updated = conversation;
+            updated.queue_position_notified = Some(entry.position as i32);
+            conversation_store.update(&conversation_id, updated, None).await?;
+        }
+    }
+
+    Ok(QueueSnapshot {
+        entries,
+        average_first_response_seconds: average,
+        online_operators: online,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ConversationPriority;
+
+    #[test]
+    fn test_estimate_wait() {
+        assert_eq!(estimate_wait(1, Some(300.0), 2), Some(300));
+        assert_eq!(estimate_wait(3, Some(300.0), 2), Some(600));
+        assert_eq!(estimate_wait(2, Some(300.0), 0), Some(600));
+        assert_eq!(estimate_wait(1, None, 2), None);
+    }
+
+    #[test]
+    fn test_should_notify() {
+        assert!(should_notify(None, 7));
+        assert!(should_notify(Some(12), 7));
+        assert!(should_notify(Some(3), 1));
+        assert!(!should_notify(Some(4), 3));
+        assert!(!should_notify(Some(2), 3));
+    }
+
+    #[test]
+    fn test_sort_queue() {
+        let now = Utc::now();
+        let waiting = |minutes_ago: i64, priority: ConversationPriority| Conversation {
+            priority,
+            __created_at__: now - chrono::Duration::minutes(minutes_ago),
+            ..Conversation::new_waiting(1, None)
+        };
+
+        let mut queue = vec![
+            waiting(30, ConversationPriority::Normal),
+            waiting(5, ConversationPriority::Urgent),
+            waiting(60, ConversationPriority::Low),
+            waiting(10, ConversationPriority::Urgent),
+        ];
+        let expected = vec![queue[3].id, queue[1].id, queue[0].id, queue[2].id];
+
+        sort_queue(&mut queue);
+        assert_eq!(queue.iter().map(|c| c.id).collect::<Vec<_>>(), expected);
+    }
+}
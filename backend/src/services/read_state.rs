@@ -0,0 +1,194 @@
+//! Per-operator read state
+//!
+//! Each operator has a read cursor per conversation: the last message they
+//! have read. A message is unread for an operator when it is a customer
+//! message after their cursor (or any customer message when they have no
+//! cursor yet). Cursors only move forward, so reading an old message from a
+//! search hit does not bring back unread counts.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::models::{ConversationStatus, Message, MessageKind, ReadCursor};
+
+/// Unread counts of one side of an operator's inbox
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BadgeCount {
+    /// Conversations with at least one unread message
+    pub conversations: i64,
+    pub messages: i64,
+}
+
+/// Badge totals of an operator's inbox
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InboxBadge {
+    /// Open conversations assigned to the operator
+    pub assigned: BadgeCount,
+    /// Unassigned open conversations (the shared queue)
+    pub queue: BadgeCount,
+}
+
+/// SQL condition: message `m` is an unread customer message given cursor `rc`
+fn unread_condition() -> String {
+    format!(
+        "m.__deleted_at__ IS NULL AND m.from_user = FALSE AND m.kind = '{}' \
+         AND (rc.id IS NULL OR (m.__created_at__, m.id) > (rc.last_read_at, rc.last_read_message_id))",
+        MessageKind::Chat.as_str()
+    )
+}
+
+/// Read cursor of `user_id` in a conversation
+pub async fn cursor(storehaus: &StoreHaus, user_id: Uuid, conversation_id: Uuid) -> Result<Option<ReadCursor>> {
+    let store = storehaus.get_store::<GenericStore<ReadCursor>>("read_cursors")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("user_id", json!(user_id)))
+        .filter(QueryFilter::eq("conversation_id", json!(conversation_id)));
+
+    Ok(store.find(query).await?.into_iter().next())
+}
+
+/// Move `user_id`'s cursor up to `message`. Returns `false` when the cursor
+/// was already at or past it. A single upsert on the unique
+/// (user, conversation) index, so concurrent calls cannot create duplicates
+/// or move the cursor back.
+pub async fn mark_read(storehaus: &StoreHaus, user_id: Uuid, message: &Message) -> Result<bool> {
+    let result = sqlx::query(
+        "INSERT INTO read_cursors (id, user_id, conversation_id, last_read_message_id, last_read_at) \
+         VALUES ($1, $2, $3, $4, $5) \
+         ON CONFLICT (user_id, conversation_id) DO UPDATE \
+             SET last_read_message_id = EXCLUDED.last_read_message_id, \
+                 last_read_at = EXCLUDED.last_read_at, \
+                 __updated_at__ = NOW() \
+             WHERE (read_cursors.last_read_at, read_cursors.last_read_message_id) \
+                 < (EXCLUDED.last_read_at, EXCLUDED.last_read_message_id)",
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(message.conversation_id)
+    .bind(message.id)
+    .bind(message.__created_at__)
+    .execute(storehaus.pool())
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Move `user_id`'s cursor to the latest message of a conversation.
+/// Returns that message, `None` when the conversation has no messages.
+pub async fn mark_conversation_read(
+    storehaus: &StoreHaus,
+    user_id: Uuid,
+    conversation_id: Uuid,
+) -> Result<Option<Message>> {
+    let latest: Option<Uuid> = sqlx::query_scalar(
+        "SELECT id FROM messages WHERE conversation_id = $1 AND __deleted_at__ IS NULL \
+         ORDER BY __created_at__ DESC, id DESC LIMIT 1",
+    )
+    .bind(conversation_id)
+    .fetch_optional(storehaus.pool())
+    .await?;
+
+    let Some(latest) = latest else {
+        return Ok(None);
+    };
+
+    let message_store = storehaus.get_store::<GenericStore<Message>>("messages")?;
+    let Some(message) = message_store.get_by_id(&latest).await? else {
+        return Ok(None);
+    };
+
+    mark_read(storehaus, user_id, &message).await?;
+    Ok(Some(message))
+}
+
+/// Unread message counts of `user_id` per conversation; conversations
+/// without unread messages are left out
+pub async fn unread_counts(
+    storehaus: &StoreHaus,
+    user_id: Uuid,
+    conversation_ids: &[Uuid],
+) -> Result<HashMap<Uuid, i64>> {
+    if conversation_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let sql = format!(
+        "SELECT m.conversation_id, COUNT(*) AS unread FROM messages m \
+         LEFT JOIN read_cursors rc \
+             ON rc.conversation_id = m.conversation_id AND rc.user_id = $1 AND rc.__deleted_at__ IS NULL \
+         WHERE m.conversation_id = ANY($2) AND {} \
+         GROUP BY m.conversation_id",
+        unread_condition()
+    );
+    let rows = sqlx::query(&sql)
+        .bind(user_id)
+        .bind(conversation_ids)
+        .fetch_all(storehaus.pool())
+        .await?;
+
+    let mut counts = HashMap::with_capacity(rows.len());
+    for row in rows {
+        counts.insert(row.try_get("conversation_id")?, row.try_get("unread")?);
+    }
+    Ok(counts)
+}
+
+/// Unread message count of `user_id` in one conversation
+pub async fn unread_count(storehaus: &StoreHaus, user_id: Uuid, conversation_id: Uuid) -> Result<i64> {
+    Ok(unread_counts(storehaus, user_id, &[conversation_id])
+        .await?
+        .remove(&conversation_id)
+        .unwrap_or(0))
+}
+
+/// Unread counts of the open conversations in `user_id`'s inbox: assigned
+/// to them or waiting in the shared queue
+pub async fn inbox_unread(storehaus: &StoreHaus, user_id: Uuid) -> Result<(HashMap<Uuid, i64>, InboxBadge)> {
+    let sql = format!(
+        "SELECT c.id, c.user_id IS NULL AS queued, COUNT(*) AS unread FROM conversations c \
+         JOIN messages m ON m.conversation_id = c.id \
+         LEFT JOIN read_cursors rc \
+             ON rc.conversation_id = c.id AND rc.user_id = $1 AND rc.__deleted_at__ IS NULL \
+         WHERE c.__deleted_at__ IS NULL AND c.status NOT IN ('{}', '{}') \
+             AND (c.user_id = $1 OR c.user_id IS NULL) AND {} \
+         GROUP BY c.id, c.user_id",
+        ConversationStatus::Closed.as_str(),
+        ConversationStatus::Snoozed.as_str(),
+        unread_condition()
+    );
+    let rows = sqlx::query(&sql).bind(user_id).fetch_all(storehaus.pool()).await?;
+
+    let mut counts = HashMap::with_capacity(rows.len());
+    let mut badge = InboxBadge::default();
+    for row in rows {
+        let unread: i64 = row.try_get("unread")?;
+        let side = if row.try_get("queued")? { &mut badge.queue } else { &mut badge.assigned };
+        side.conversations += 1;
+        side.messages += unread;
+        counts.insert(row.try_get("id")?, unread);
+    }
+
+    Ok((counts, badge))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_cursor_order() {
+        let now = Utc::now();
+        let cursor = ReadCursor::create(Uuid::new_v4(), Uuid::new_v4(), Uuid::from_u128(5), now);
+
+        assert!(cursor.is_before(now + Duration::seconds(1), Uuid::from_u128(1)));
+        assert!(cursor.is_before(now, Uuid::from_u128(6)));
+        assert!(!cursor.is_before(now, Uuid::from_u128(5)));
+        assert!(!cursor.is_before(now - Duration::seconds(1), Uuid::from_u128(9)));
+    }
+}
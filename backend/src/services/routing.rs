@@ -0,0 +1,437 @@
+//! Automatic conversation routing
+//!
+//! When a routing strategy is configured, waiting conversations are assigned
+//! to online operators with free capacity. Conversations that find nobody
+//! stay in the `Waiting` queue; `drain_queue` runs again whenever capacity
+//! frees up (a conversation is closed or transferred, an operator comes
+//! online) and periodically from a background worker. Operators who close
+//! their last WebSocket connection and do not come back are marked offline.
+
+use anyhow::Result;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use storehaus::prelude::*;
+use teloxide::Bot;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::models::{
+    Conversation, ConversationStatus, OperatorStatus, RoutingStrategy, Setting, TelegramUser, User,
+};
+use crate::services::{assignments, customer_notifications, queue, settings};
+use crate::telegram::BotManager;
+use crate::websocket::{WebSocketEvent, WebSocketManager};
+
+/// How often the background worker re-checks the queue
+pub const QUEUE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long an operator may stay disconnected before being marked offline
+pub const DISCONNECT_GRACE: Duration = Duration::from_secs(30);
+
+/// Serializes routing passes so a conversation is never assigned twice
+static ROUTING_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Operator that can receive conversations
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub user_id: Uuid,
+    /// Lowercase skills (languages and topics)
+    pub skills: Vec<String>,
+    /// Open (waiting or active) conversations assigned to the operator
+    pub open_conversations: usize,
+    /// Maximum concurrent conversations
+    pub capacity: usize,
+}
+
+impl Candidate {
+    fn has_capacity(&self) -> bool {
+        self.open_conversations < self.capacity
+    }
+
+    fn skill_score(&self, requirements: &[String]) -> usize {
+        requirements.iter().filter(|r| self.skills.contains(r)).count()
+    }
+}
+
+/// Routing requirements of a conversation: the customer's language
+/// (`country_code`) and intake topic (deep-link `source`), lowercased
+pub fn requirements(conversation: &Conversation, telegram_user: Option<&TelegramUser>) -> Vec<String> {
+    telegram_user
+        .and_then(|u| u.country_code.as_deref())
+        .into_iter()
+        .chain(conversation.source.as_deref())
+        .map(|r| r.trim().to_lowercase())
+        .filter(|r| !r.is_empty())
+        .collect()
+}
+
+/// Normalize operator skills: trimmed, lowercase, without duplicates
+pub fn normalize_skills(skills: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for skill in skills {
+        let skill = skill.trim().to_lowercase();
+        if !skill.is_empty() && !result.contains(&skill) {
+            result.push(skill);
+        }
+    }
+    result
+}
+
+/// Pick an operator for a conversation.
+///
+/// `candidates` must be in a stable order; round-robin continues after
+/// `last_assigned`. The skills strategy prefers the best skill match and
+/// falls back to every available operator when nobody matches.
+pub fn pick_operator(
+    strategy: RoutingStrategy,
+    candidates: &[Candidate],
+    requirements: &[String],
+    last_assigned: Option<Uuid>,
+) -> Option<Uuid> {
+    let available: Vec<&Candidate> = candidates.iter().filter(|c| c.has_capacity()).collect();
+    if available.is_empty() {
+        return None;
+    }
+
+    match strategy {
+        RoutingStrategy::Manual => None,
+        RoutingStrategy::RoundRobin => {
+            let start = last_assigned
+                .and_then(|last| candidates.iter().position(|c| c.user_id == last))
+                .map(|i| i + 1)
+                .unwrap_or(0);
+
+            (0..candidates.len())
+                .map(|offset| &candidates[(start + offset) % candidates.len()])
+                .find(|c| c.has_capacity())
+                .map(|c| c.user_id)
+        }
+        RoutingStrategy::LeastLoaded => least_loaded(&available),
+        RoutingStrategy::Skills => {
+            let best = available.iter().map(|c| c.skill_score(requirements)).max().unwrap_or(0);
+            let matching: Vec<&Candidate> = available
+                .into_iter()
+                .filter(|c| c.skill_score(requirements) == best)
+                .collect();
+            least_loaded(&matching)
+        }
+    }
+}
+
+fn least_loaded(candidates: &[&Candidate]) -> Option<Uuid> {
+    candidates
+        .iter()
+        .min_by_key(|c| c.open_conversations)
+        .map(|c| c.user_id)
+}
+
+/// Online operators with their current load, oldest accounts first
+async fn load_candidates(storehaus: &StoreHaus, default_capacity: u32) -> Result<Vec<Candidate>> {
+    let user_store = storehaus.get_store::<GenericStore<User>>("users")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("is_active", json!(true)))
+        .filter(QueryFilter::eq("is_operator", json!(true)))
+        .order_by("__created_at__", SortOrder::Asc);
+    let operators: Vec<(User, _)> = user_store
+        .find(query)
+        .await?
+        .into_iter()
+        .map(|user| {
+            let settings = user.parsed_settings();
+            (user, settings)
+        })
+        .filter(|(_, settings)| settings.status == OperatorStatus::Online)
+        .collect();
+
+    if operators.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::ne("status", json!(ConversationStatus::Closed.as_str())))
+        .filter(QueryFilter::or(
+            operators
+                .iter()
+                .map(|(user, _)| QueryFilter::eq("user_id", json!(user.id)))
+                .collect(),
+        ));
+
+    let mut load: HashMap<Uuid, usize> = HashMap::new();
+    for conversation in conversation_store.find(query).await? {
+        if let Some(user_id) = conversation.user_id {
+            *load.entry(user_id).or_default() += 1;
+        }
+    }
+
+    Ok(operators
+        .into_iter()
+        .map(|(user, settings)| Candidate {
+            user_id: user.id,
+            skills: settings.skills,
+            open_conversations: load.get(&user.id).copied().unwrap_or(0),
+            capacity: settings.max_concurrent_chats.unwrap_or(default_capacity) as usize,
+        })
+        .collect())
+}
+
+/// Assign waiting, unassigned conversations in queue order (highest
+/// priority first, then arrival).
+///
+/// Returns `(conversation_id, operator_id)` for every assignment made.
+/// Customers are told about their operator when `bot` is given.
+pub async fn drain_queue(
+    storehaus: &StoreHaus,
+    ws_manager: &WebSocketManager,
+    bot: Option<&Bot>,
+) -> Result<Vec<(Uuid, Uuid)>> {
+    let _guard = ROUTING_LOCK.lock().await;
+
+    let routing = settings::routing_settings(storehaus).await?;
+    if routing.routing_strategy == RoutingStrategy::Manual {
+        return Ok(Vec::new());
+    }
+
+    let waiting = queue::waiting_conversations(storehaus).await?;
+    if waiting.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut candidates = load_candidates(storehaus, routing.routing_max_concurrent_chats).await?;
+    if candidates.is_empty() {
+        debug!("No operators online, {} conversations stay queued", waiting.len());
+        return Ok(Vec::new());
+    }
+
+    let telegram_user_store = storehaus.get_store::<GenericStore<TelegramUser>>("telegram_users")?;
+    let mut last_assigned = settings::get_setting(storehaus, Setting::ROUTING_LAST_ASSIGNED)
+        .await?
+        .and_then(|v| Uuid::parse_str(&v).ok());
+
+    let mut assigned = Vec::new();
+
+    for conversation in waiting {
+        let telegram_user = telegram_user_store.get_by_id(&conversation.telegram_user_id).await?;
+        let requirements = requirements(&conversation, telegram_user.as_ref());
+
+        let Some(operator_id) = pick_operator(
+            routing.routing_strategy,
+            &candidates,
+            &requirements,
+            last_assigned,
+        ) else {
+            // Everyone is at capacity, the rest of the queue has to wait
+            break;
+        };
+
+        let conversation_id = conversation.id;
+        assign(storehaus, ws_manager, bot, conversation, operator_id, routing.routing_strategy).await?;
+
+        if let Some(candidate) = candidates.iter_mut().find(|c| c.user_id == operator_id) {
+            candidate.open_conversations += 1;
+        }
+        last_assigned = Some(operator_id);
+        assigned.push((conversation_id, operator_id));
+    }
+
+    if let Some(last) = last_assigned {
+        settings::set_setting(storehaus, Setting::ROUTING_LAST_ASSIGNED, last.to_string()).await?;
+    }
+
+    Ok(assigned)
+}
+
+/// Assign a queued conversation to `operator_id`
+async fn assign(
+    storehaus: &StoreHaus,
+    ws_manager: &WebSocketManager,
+    bot: Option<&Bot>,
+    mut conversation: Conversation,
+    operator_id: Uuid,
+    strategy: RoutingStrategy,
+) -> Result<()> {
+    let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+    let user_store = storehaus.get_store::<GenericStore<User>>("users")?;
+
+    let conversation_id = conversation.id;
+    conversation.user_id = Some(operator_id);
+    conversation.status = ConversationStatus::Active;
+
+    let conversation = conversation_store
+        .update(&conversation_id, conversation, Some(vec!["auto_assigned".to_string()]))
+        .await?;
+
+    assignments::record_assignment(
+        storehaus,
+        conversation_id,
+        None,
+        Some(operator_id),
+        None,
+        Some(format!("Auto-routed ({})", strategy.as_str())),
+    )
+    .await?;
+
+    info!("Routed conversation {} to operator {} ({})", conversation_id, operator_id, strategy.as_str());
+
+    let operator_email = user_store
+        .get_by_id(&operator_id)
+        .await?
+        .map(|u| u.email)
+        .unwrap_or_default();
+
+    let ws_event = WebSocketEvent::ConversationAssigned {
+        conversation_id,
+        user_id: operator_id,
+        user_name: operator_email,
+    };
+
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast ConversationAssigned event: {}", e);
+    }
+
+    if let Some(bot) = bot {
+        if let Err(e) =
+            customer_notifications::notify_operator_assigned_with_bot(storehaus, bot, &conversation, operator_id).await
+        {
+            warn!("Failed to notify customer about assignment of conversation {}: {}", conversation_id, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Drain the queue and refresh queue positions in the background
+/// (after capacity was freed or the queue changed)
+pub fn spawn_drain_queue(storehaus: Arc<StoreHaus>, ws_manager: Arc<WebSocketManager>, bot_manager: Arc<BotManager>) {
+    tokio::spawn(async move {
+        let bot = bot_manager.bot().await;
+        if let Err(e) = drain_queue(&storehaus, &ws_manager, bot.as_ref()).await {
+            error!("Failed to route queued conversations: {}", e);
+        }
+        if let Err(e) = queue::refresh(&storehaus, &ws_manager, bot.as_ref()).await {
+            error!("Failed to refresh queue: {}", e);
+        }
+    });
+}
+
+/// The operator's last WebSocket connection closed. Unless they reconnect
+/// within `DISCONNECT_GRACE` (a page reload), they are no longer there to
+/// take conversations: mark them offline.
+pub fn spawn_operator_disconnected(storehaus: Arc<StoreHaus>, ws_manager: Arc<WebSocketManager>, user_id: Uuid) {
+    tokio::spawn(async move {
+        tokio::time::sleep(DISCONNECT_GRACE).await;
+        if ws_manager.is_connected(user_id) {
+            return;
+        }
+        if let Err(e) = mark_offline(&storehaus, &ws_manager, user_id).await {
+            error!("Failed to mark operator {} offline: {}", user_id, e);
+        }
+    });
+}
+
+async fn mark_offline(storehaus: &StoreHaus, ws_manager: &WebSocketManager, user_id: Uuid) -> Result<()> {
+    let user_store = storehaus.get_store::<GenericStore<User>>("users")?;
+    let Some(mut user) = user_store.get_by_id(&user_id).await? else {
+        return Ok(());
+    };
+
+    let mut settings = user.parsed_settings();
+    if settings.status == OperatorStatus::Offline {
+        return Ok(());
+    }
+    settings.status = OperatorStatus::Offline;
+    user.settings = Some(serde_json::to_string(&settings)?);
+    user_store.update(&user_id, user, None).await?;
+
+    if let Err(e) = ws_manager.broadcast_event(WebSocketEvent::UserOffline { user_id }).await {
+        warn!("Failed to broadcast user status event: {}", e);
+    }
+    info!("Operator {} disconnected, marked offline", user_id);
+
+    Ok(())
+}
+
+/// Periodically drain the queue, covering capacity changes without an
+/// explicit trigger (e.g. admin raising an operator's limit)
+pub fn spawn_queue_worker(
+    storehaus: Arc<StoreHaus>,
+    ws_manager: Arc<WebSocketManager>,
+    bot_manager: Arc<BotManager>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(QUEUE_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let bot = bot_manager.bot().await;
+            match drain_queue(&storehaus, &ws_manager, bot.as_ref()).await {
+                Ok(assigned) if !assigned.is_empty() => {
+                    info!("Routed {} queued conversations", assigned.len());
+                    if let Err(e) = queue::refresh(&storehaus, &ws_manager, bot.as_ref()).await {
+                        error!("Failed to refresh queue: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => error!("Failed to route queued conversations: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(open_conversations: usize, capacity: usize, skills: &[&str]) -> Candidate {
+        Candidate {
+            user_id: Uuid::new_v4(),
+            skills: skills.iter().map(|s| s.to_string()).collect(),
+            open_conversations,
+            capacity,
+        }
+    }
+
+    #[test]
+    fn test_round_robin_skips_full_operators() {
+        let candidates = vec![candidate(0, 2, &[]), candidate(2, 2, &[]), candidate(1, 2, &[])];
+        let ids: Vec<Uuid> = candidates.iter().map(|c| c.user_id).collect();
+
+        assert_eq!(pick_operator(RoutingStrategy::RoundRobin, &candidates, &[], None), Some(ids[0]));
+        assert_eq!(pick_operator(RoutingStrategy::RoundRobin, &candidates, &[], Some(ids[0])), Some(ids[2]));
+        assert_eq!(pick_operator(RoutingStrategy::RoundRobin, &candidates, &[], Some(ids[2])), Some(ids[0]));
+    }
+
+    #[test]
+    fn test_least_loaded_and_skills() {
+        let candidates = vec![candidate(3, 5, &["en"]), candidate(1, 5, &[]), candidate(2, 5, &["ru", "billing"])];
+        let ids: Vec<Uuid> = candidates.iter().map(|c| c.user_id).collect();
+        let ru_billing = vec!["ru".to_string(), "billing".to_string()];
+
+        assert_eq!(pick_operator(RoutingStrategy::LeastLoaded, &candidates, &ru_billing, None), Some(ids[1]));
+        assert_eq!(pick_operator(RoutingStrategy::Skills, &candidates, &ru_billing, None), Some(ids[2]));
+        assert_eq!(pick_operator(RoutingStrategy::Skills, &candidates, &["en".to_string()], None), Some(ids[0]));
+        // Nobody speaks German: fall back to the least loaded operator
+        assert_eq!(pick_operator(RoutingStrategy::Skills, &candidates, &["de".to_string()], None), Some(ids[1]));
+    }
+
+    #[test]
+    fn test_no_capacity_or_manual() {
+        let candidates = vec![candidate(2, 2, &[])];
+
+        assert_eq!(pick_operator(RoutingStrategy::LeastLoaded, &candidates, &[], None), None);
+        assert_eq!(pick_operator(RoutingStrategy::Manual, &[candidate(0, 2, &[])], &[], None), None);
+    }
+
+    #[test]
+    fn test_normalize_skills() {
+        assert_eq!(
+            normalize_skills(vec![" RU ".to_string(), "ru".to_string(), "".to_string(), "Billing".to_string()]),
+            vec!["ru".to_string(), "billing".to_string()]
+        );
+    }
+}
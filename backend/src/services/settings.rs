@@ -0,0 +1,167 @@
+//! System settings helpers
+//!
+//! Settings are plain key-value rows in the `settings` table; these helpers
+//! handle lookup, upsert and typed reads with defaults.
+
+use anyhow::Result;
+use serde_json::json;
+use storehaus::prelude::*;
+
+use crate::models::{
+    CollisionSettings, IdleSettings, NotificationSettings, OperatorNameDisplay, ReopenSettings, ReplyLockMode,
+    RoutingSettings, RoutingStrategy, Setting, WrapUpSettings,
+};
+
+/// Get a raw setting value
+pub async fn get_setting(storehaus: &StoreHaus, key: &str) -> Result<Option<String>> {
+    let store = storehaus.get_store::<GenericStore<Setting>>("settings")?;
+
+    let query = QueryBuilder::new().filter(QueryFilter::eq("id", json!(key)));
+    let setting = store.find_one(query).await?;
+
+    Ok(setting.map(|s| s.value))
+}
+
+/// Create or update a setting value
+pub async fn set_setting(storehaus: &StoreHaus, key: &str, value: String) -> Result<()> {
+    let store = storehaus.get_store::<GenericStore<Setting>>("settings")?;
+
+    let query = QueryBuilder::new().filter(QueryFilter::eq("id", json!(key)));
+
+    if let Some(mut setting) = store.find_one(query).await? {
+        setting.value = value;
+        let query = QueryBuilder::new().filter(QueryFilter::eq("id", json!(key)));
+        store.update_where(query, Some(setting)).await?;
+    } else {
+        let setting = Setting {
+            id: key.to_string(),
+            value,
+            ..Default::default()
+        };
+        store.create(setting, None).await?;
+    }
+
+    Ok(())
+}
+
+/// Get a boolean setting, falling back to `default` when unset or invalid
+pub async fn get_bool(storehaus: &StoreHaus, key: &str, default: bool) -> Result<bool> {
+    let value = get_setting(storehaus, key).await?;
+    Ok(value.and_then(|v| v.parse().ok()).unwrap_or(default))
+}
+
+/// Load customer notification settings
+pub async fn notification_settings(storehaus: &StoreHaus) -> Result<NotificationSettings> {
+    let defaults = NotificationSettings::default();
+
+    let operator_name_display = get_setting(storehaus, Setting::OPERATOR_NAME_DISPLAY)
+        .await?
+        .and_then(|v| OperatorNameDisplay::parse(&v))
+        .unwrap_or(defaults.operator_name_display);
+
+    Ok(NotificationSettings {
+        notify_operator_assigned: get_bool(
+            storehaus,
+            Setting::NOTIFY_OPERATOR_ASSIGNED,
+            defaults.notify_operator_assigned,
+        )
+        .await?,
+        notify_conversation_closed: get_bool(
+            storehaus,
+            Setting::NOTIFY_CONVERSATION_CLOSED,
+            defaults.notify_conversation_closed,
+        )
+        .await?,
+        notify_queue_position: get_bool(
+            storehaus,
+            Setting::NOTIFY_QUEUE_POSITION,
+            defaults.notify_queue_position,
+        )
+        .await?,
+        operator_name_display,
+    })
+}
+
+/// Load automatic routing settings
+pub async fn routing_settings(storehaus: &StoreHaus) -> Result<RoutingSettings> {
+    let defaults = RoutingSettings::default();
+
+    let routing_strategy = get_setting(storehaus, Setting::ROUTING_STRATEGY)
+        .await?
+        .and_then(|v| RoutingStrategy::parse(&v))
+        .unwrap_or(defaults.routing_strategy);
+
+    let routing_max_concurrent_chats = get_setting(storehaus, Setting::ROUTING_MAX_CONCURRENT_CHATS)
+        .await?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(defaults.routing_max_concurrent_chats);
+
+    Ok(RoutingSettings {
+        routing_strategy,
+        routing_max_concurrent_chats,
+    })
+}
+
+/// Load idle conversation settings
+pub async fn idle_settings(storehaus: &StoreHaus) -> Result<IdleSettings> {
+    let defaults = IdleSettings::default();
+
+    let idle_nudge_hours = get_setting(storehaus, Setting::IDLE_NUDGE_HOURS)
+        .await?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(defaults.idle_nudge_hours);
+
+    let idle_close_hours = get_setting(storehaus, Setting::IDLE_CLOSE_HOURS)
+        .await?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(defaults.idle_close_hours);
+
+    Ok(IdleSettings {
+        idle_nudge_hours,
+        idle_close_hours,
+    })
+}
+
+/// Load conversation reopen settings
+pub async fn reopen_settings(storehaus: &StoreHaus) -> Result<ReopenSettings> {
+    let defaults = ReopenSettings::default();
+
+    let reopen_window_minutes = get_setting(storehaus, Setting::REOPEN_WINDOW_MINUTES)
+        .await?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(defaults.reopen_window_minutes);
+
+    Ok(ReopenSettings { reopen_window_minutes })
+}
+
+/// Load operator collision settings
+pub async fn collision_settings(storehaus: &StoreHaus) -> Result<CollisionSettings> {
+    let defaults = CollisionSettings::default();
+
+    let reply_lock_mode = get_setting(storehaus, Setting::REPLY_LOCK_MODE)
+        .await?
+        .and_then(|v| ReplyLockMode::parse(&v))
+        .unwrap_or(defaults.reply_lock_mode);
+
+    Ok(CollisionSettings { reply_lock_mode })
+}
+
+/// Load wrap-up settings for closing conversations
+pub async fn wrap_up_settings(storehaus: &StoreHaus) -> Result<WrapUpSettings> {
+    let defaults = WrapUpSettings::default();
+
+    Ok(WrapUpSettings {
+        disposition_required: get_bool(
+            storehaus,
+            Setting::DISPOSITION_REQUIRED,
+            defaults.disposition_required,
+        )
+        .await?,
+        resolution_summary_required: get_bool(
+            storehaus,
+            Setting::RESOLUTION_SUMMARY_REQUIRED,
+            defaults.resolution_summary_required,
+        )
+        .await?,
+    })
+}
@@ -0,0 +1,539 @@
+//! SLA timers
+//!
+//! A conversation picks the most specific active `SlaPolicy` (tag and/or
+//! source) when it is created or its tags change. The policy's targets
+//! become deadlines on the conversation:
+//!
+//! - first response: from creation until the first operator reply
+//! - next response: from each customer message after that until answered
+//! - resolution: from creation until the conversation is closed
+//!
+//! Deadlines count only business hours when the policy defines them. A
+//! background worker emits warning and breach events (stored in
+//! `sla_events`, broadcast over the WebSocket and sent to operators'
+//! Telegram notification chats).
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveTime, TimeZone, Utc};
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::Arc;
+use storehaus::prelude::*;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::models::{
+    BusinessHours, Conversation, ConversationStatus, SlaEvent, SlaEventKind, SlaPolicy, SlaTimer,
+    TelegramUser, User,
+};
+use crate::services::tags;
+use crate::telegram::BotManager;
+use crate::websocket::{WebSocketEvent, WebSocketManager};
+
+/// How often the background worker checks running timers
+pub const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Active policies
+pub async fn active_policies(storehaus: &StoreHaus) -> Result<Vec<SlaPolicy>> {
+    let store = storehaus.get_store::<GenericStore<SlaPolicy>>("sla_policies")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("is_active", json!(true)))
+        .order_by("__created_at__", SortOrder::Asc);
+
+    Ok(store.find(query).await?)
+}
+
+/// Pick the policy for a conversation.
+///
+/// A policy applies when its tag and source filters (if any) match. The
+/// most specific one wins: tag + source, then tag, then source, then a
+/// catch-all policy. Ties go to the oldest policy.
+pub fn select_policy<'a>(policies: &'a [SlaPolicy], source: Option<&str>, tag_ids: &[Uuid]) -> Option<&'a SlaPolicy> {
+    policies
+        .iter()
+        .filter_map(|policy| {
+            let tag_score = match policy.tag_id {
+                Some(tag_id) if tag_ids.contains(&tag_id) => 2,
+                Some(_) => return None,
+                None => 0,
+            };
+            let source_score = match policy.source.as_deref() {
+                Some(s) if Some(s) == source => 1,
+                Some(_) => return None,
+                None => 0,
+            };
+            Some((tag_score + source_score, policy))
+        })
+        .fold(None, |best: Option<(i32, &SlaPolicy)>, (score, policy)| match best {
+            Some((best_score, _)) if best_score >= score => best,
+            _ => Some((score, policy)),
+        })
+        .map(|(_, policy)| policy)
+}
+
+/// Add `minutes` of working time to `from`
+pub fn add_business_minutes(from: DateTime<Utc>, minutes: i64, hours: Option<&BusinessHours>) -> DateTime<Utc> {
+    let Some(hours) = hours.filter(|h| h.is_valid()) else {
+        return from + Duration::minutes(minutes);
+    };
+    let Some(offset) = FixedOffset::east_opt(hours.utc_offset_minutes * 60) else {
+        return from + Duration::minutes(minutes);
+    };
+
+    let mut local = from.with_timezone(&offset).naive_local();
+    let mut remaining = Duration::minutes(minutes);
+
+    // A year is plenty for any realistic target
+    for _ in 0..366 {
+        let date = local.date();
+
+        if hours.days.contains(&date.weekday().number_from_monday()) {
+            let day_start = date.and_time(hours.start);
+            let day_end = date.and_time(hours.end);
+            let begin = local.max(day_start);
+
+            if begin < day_end {
+                let available = day_end - begin;
+                if remaining <= available {
+                    if let Some(due) = offset.from_local_datetime(&(begin + remaining)).single() {
+                        return due.with_timezone(&Utc);
+                    }
+                }
+                remaining -= available;
+            }
+        }
+
+        local = (date + Duration::days(1)).and_time(NaiveTime::default());
+    }
+
+    from + Duration::minutes(minutes)
+}
+
+/// Deadline of `timer` started at `from`, if the policy has a target for it
+pub fn due_at(policy: &SlaPolicy, timer: SlaTimer, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let minutes = policy.target_minutes(timer)?;
+    let hours = policy.parsed_business_hours();
+
+    Some(add_business_minutes(from, minutes as i64, hours.as_ref()))
+}
+
+/// Apply `policy` to a conversation, starting timers from its creation
+/// (or `now` for new conversations). Timers that were already met stay met.
+pub fn start_timers(conversation: &mut Conversation, policy: Option<&SlaPolicy>, started_at: DateTime<Utc>) {
+    let Some(policy) = policy else {
+        conversation.sla_policy_id = None;
+        stop_timers(conversation);
+        return;
+    };
+
+    conversation.sla_policy_id = Some(policy.id);
+    conversation.first_response_due_at = if conversation.first_responded_at.is_none() {
+        due_at(policy, SlaTimer::FirstResponse, started_at)
+    } else {
+        None
+    };
+    conversation.resolution_due_at = if conversation.is_closed() {
+        None
+    } else {
+        due_at(policy, SlaTimer::Resolution, started_at)
+    };
+}
+
+/// A customer wrote: start the next-response timer once the first reply was given
+pub fn on_customer_message(conversation: &mut Conversation, policy: Option<&SlaPolicy>, now: DateTime<Utc>) {
+    let Some(policy) = policy else {
+        return;
+    };
+
+    if conversation.first_responded_at.is_some() && conversation.next_response_due_at.is_none() {
+        conversation.next_response_due_at = due_at(policy, SlaTimer::NextResponse, now);
+    }
+}
+
+/// An operator replied: response timers are met
+pub fn on_operator_reply(conversation: &mut Conversation, now: DateTime<Utc>) {
+    conversation.first_responded_at.get_or_insert(now);
+    conversation.first_response_due_at = None;
+    conversation.next_response_due_at = None;
+}
+
+/// The conversation was closed: nothing is due any more
+pub fn stop_timers(conversation: &mut Conversation) {
+    conversation.first_response_due_at = None;
+    conversation.next_response_due_at = None;
+    conversation.resolution_due_at = None;
+}
+
+/// State of a running timer at `now`
+pub fn timer_state(due_at: DateTime<Utc>, warning_minutes: i32, now: DateTime<Utc>) -> Option<SlaEventKind> {
+    if now >= due_at {
+        Some(SlaEventKind::Breach)
+    } else if now >= due_at - Duration::minutes(warning_minutes.max(0) as i64) {
+        Some(SlaEventKind::Warning)
+    } else {
+        None
+    }
+}
+
+/// Running deadlines of a conversation
+fn running_timers(conversation: &Conversation) -> Vec<(SlaTimer, DateTime<Utc>)> {
+    [
+        (SlaTimer::FirstResponse, conversation.first_response_due_at),
+        (SlaTimer::NextResponse, conversation.next_response_due_at),
+        (SlaTimer::Resolution, conversation.resolution_due_at),
+    ]
+    .into_iter()
+    .filter_map(|(timer, due)| due.map(|d| (timer, d)))
+    .collect()
+}
+
+/// Load a conversation's policy
+pub async fn policy_for(storehaus: &StoreHaus, conversation: &Conversation) -> Result<Option<SlaPolicy>> {
+    let Some(policy_id) = conversation.sla_policy_id else {
+        return Ok(None);
+    };
+
+    let store = storehaus.get_store::<GenericStore<SlaPolicy>>("sla_policies")?;
+    Ok(store.get_by_id(&policy_id).await?)
+}
+
+/// Re-select the policy of an existing conversation (after its tags changed)
+pub async fn reapply_policy(storehaus: &StoreHaus, conversation_id: Uuid) -> Result<()> {
+    let store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+    let Some(mut conversation) = store.get_by_id(&conversation_id).await? else {
+        return Ok(());
+    };
+
+    let tag_ids: Vec<Uuid> = tags::conversation_tags(storehaus, &[conversation_id])
+        .await?
+        .remove(&conversation_id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|t| t.id)
+        .collect();
+
+    let policies = active_policies(storehaus).await?;
+    let policy = select_policy(&policies, conversation.source.as_deref(), &tag_ids);
+
+    if policy.map(|p| p.id) == conversation.sla_policy_id {
+        return Ok(());
+    }
+
+    let created_at = conversation.__created_at__;
+    start_timers(&mut conversation, policy, created_at);
+    store.update(&conversation_id, conversation, None).await?;
+
+    Ok(())
+}
+
+/// Emit warnings and breaches for running timers. Snoozed conversations are
+/// skipped: the operator parked them on purpose and they are checked again
+/// once they wake.
+pub async fn check_timers(storehaus: &StoreHaus, ws_manager: &WebSocketManager, bot: Option<&Bot>) -> Result<usize> {
+    let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+    let policy_store = storehaus.get_store::<GenericStore<SlaPolicy>>("sla_policies")?;
+    let event_store = storehaus.get_store::<GenericStore<SlaEvent>>("sla_events")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::ne("status", json!(ConversationStatus::Closed.as_str())))
+        .filter(QueryFilter::ne("status", json!(ConversationStatus::Snoozed.as_str())));
+    let conversations: Vec<Conversation> = conversation_store
+        .find(query)
+        .await?
+        .into_iter()
+        .filter(|c| c.sla_due_at().is_some())
+        .collect();
+
+    let now = Utc::now();
+    let mut emitted = 0;
+
+    for conversation in conversations {
+        let Some(policy_id) = conversation.sla_policy_id else {
+            continue;
+        };
+        let Some(policy) = policy_store.get_by_id(&policy_id).await? else {
+            continue;
+        };
+
+        let due: Vec<(SlaTimer, DateTime<Utc>, SlaEventKind)> = running_timers(&conversation)
+            .into_iter()
+            .filter_map(|(timer, due_at)| {
+                timer_state(due_at, policy.warning_minutes, now).map(|kind| (timer, due_at, kind))
+            })
+            .collect();
+        if due.is_empty() {
+            continue;
+        }
+
+        let query = QueryBuilder::new().filter(QueryFilter::eq("conversation_id", json!(conversation.id)));
+        let recorded: HashSet<(String, String, DateTime<Utc>)> = event_store
+            .find(query)
+            .await?
+            .into_iter()
+            .map(|e| (e.timer, e.kind, e.due_at))
+            .collect();
+
+        for (timer, due_at, kind) in due {
+            let key = (timer.as_str().to_string(), kind.as_str().to_string(), due_at);
+            if recorded.contains(&key) {
+                continue;
+            }
+
+            let event = SlaEvent::create(conversation.id, policy.id, timer, kind, due_at, conversation.user_id);
+            event_store.create(event, Some(vec![format!("sla_{}", kind.as_str())])).await?;
+
+            notify(storehaus, ws_manager, bot, &conversation, timer, kind, due_at).await;
+            emitted += 1;
+        }
+    }
+
+    Ok(emitted)
+}
+
+/// Broadcast an SLA event and notify operators via Telegram
+async fn notify(
+    storehaus: &StoreHaus,
+    ws_manager: &WebSocketManager,
+    bot: Option<&Bot>,
+    conversation: &Conversation,
+    timer: SlaTimer,
+    kind: SlaEventKind,
+    due_at: DateTime<Utc>,
+) {
+    let ws_event = match kind {
+        SlaEventKind::Warning => WebSocketEvent::SlaWarning {
+            conversation_id: conversation.id,
+            timer: timer.as_str().to_string(),
+            due_at,
+            user_id: conversation.user_id,
+        },
+        SlaEventKind::Breach => WebSocketEvent::SlaBreached {
+            conversation_id: conversation.id,
+            timer: timer.as_str().to_string(),
+            due_at,
+            user_id: conversation.user_id,
+        },
+    };
+
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast SLA event: {}", e);
+    }
+
+    let Some(bot) = bot else {
+        return;
+    };
+
+    if let Err(e) = send_telegram_notifications(storehaus, bot, conversation, timer, kind, due_at).await {
+        warn!("Failed to send SLA notifications for conversation {}: {}", conversation.id, e);
+    }
+}
+
+/// Telegram notification to the assigned operator, or to every operator
+/// with notifications set up while the conversation is unassigned
+async fn send_telegram_notifications(
+    storehaus: &StoreHaus,
+    bot: &Bot,
+    conversation: &Conversation,
+    timer: SlaTimer,
+    kind: SlaEventKind,
+    due_at: DateTime<Utc>,
+) -> Result<()> {
+    let user_store = storehaus.get_store::<GenericStore<User>>("users")?;
+    let telegram_user_store = storehaus.get_store::<GenericStore<TelegramUser>>("telegram_users")?;
+
+    let recipients: Vec<User> = match conversation.user_id {
+        Some(user_id) => user_store.get_by_id(&user_id).await?.into_iter().collect(),
+        None => {
+            let query = QueryBuilder::new().filter(QueryFilter::eq("is_active", json!(true)));
+            user_store.find(query).await?
+        }
+    };
+
+    let customer = telegram_user_store
+        .get_by_id(&conversation.telegram_user_id)
+        .await?
+        .map(|u| u.full_name())
+        .unwrap_or_else(|| format!("User {}", conversation.telegram_user_id));
+
+    let timer_label = match timer {
+        SlaTimer::FirstResponse => "First response",
+        SlaTimer::NextResponse => "Next response",
+        SlaTimer::Resolution => "Resolution",
+    };
+    let title = match kind {
+        SlaEventKind::Warning => "⏰ <b>SLA deadline approaching</b>",
+        SlaEventKind::Breach => "🚨 <b>SLA breached</b>",
+    };
+
+    let notification = format!(
+        "{}\n\n\
+        Customer: {}\n\
+        Target: {}\n\
+        Due: {} UTC\n\n\
+        Please log in to the system to respond.",
+        title,
+        escape_html(&customer),
+        timer_label,
+        due_at.format("%Y-%m-%d %H:%M")
+    );
+
+    for user in recipients {
+        let settings = user.parsed_settings();
+        if !settings.notifications_enabled {
+            continue;
+        }
+
+        let Some(chat_id) = settings
+            .telegram_notifications_user_id
+            .as_deref()
+            .and_then(|id| id.parse::<i64>().ok())
+        else {
+            continue;
+        };
+
+        if let Err(e) = bot
+            .send_message(ChatId(chat_id), notification.clone())
+            .parse_mode(ParseMode::Html)
+            .await
+        {
+            warn!("Failed to send SLA notification to user {}: {}", user.email, e);
+        }
+    }
+
+    Ok(())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Periodically check SLA timers
+pub fn spawn_sla_worker(
+    storehaus: Arc<StoreHaus>,
+    ws_manager: Arc<WebSocketManager>,
+    bot_manager: Arc<BotManager>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let bot = bot_manager.bot().await;
+            match check_timers(&storehaus, &ws_manager, bot.as_ref()).await {
+                Ok(emitted) if emitted > 0 => info!("Emitted {} SLA events", emitted),
+                Ok(_) => {}
+                Err(e) => error!("Failed to check SLA timers: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn policy(tag_id: Option<Uuid>, source: Option<&str>) -> SlaPolicy {
+        SlaPolicy {
+            id: Uuid::new_v4(),
+            first_response_minutes: Some(15),
+            tag_id,
+            source: source.map(str::to_string),
+            is_active: true,
+            ..Default::default()
+        }
+    }
+
+    fn utc(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        // March 2024: the 4th is a Monday
+        NaiveDate::from_ymd_opt(2024, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+            .and_utc()
+    }
+
+    #[test]
+    fn test_select_policy() {
+        let vip = Uuid::new_v4();
+        let policies = vec![
+            policy(None, None),
+            policy(None, Some("ads")),
+            policy(Some(vip), None),
+            policy(Some(vip), Some("ads")),
+        ];
+
+        assert_eq!(select_policy(&policies, None, &[]).map(|p| p.id), Some(policies[0].id));
+        assert_eq!(select_policy(&policies, Some("ads"), &[]).map(|p| p.id), Some(policies[1].id));
+        assert_eq!(select_policy(&policies, Some("site"), &[vip]).map(|p| p.id), Some(policies[2].id));
+        assert_eq!(select_policy(&policies, Some("ads"), &[vip]).map(|p| p.id), Some(policies[3].id));
+        assert!(select_policy(&policies[1..2], None, &[]).is_none());
+    }
+
+    #[test]
+    fn test_add_business_minutes() {
+        let hours = BusinessHours {
+            utc_offset_minutes: 0,
+            days: vec![1, 2, 3, 4, 5],
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+        };
+
+        // Within the working day
+        assert_eq!(add_business_minutes(utc(4, 10, 0), 30, Some(&hours)), utc(4, 10, 30));
+        // Before opening
+        assert_eq!(add_business_minutes(utc(4, 7, 0), 15, Some(&hours)), utc(4, 9, 15));
+        // Spills over into the next day
+        assert_eq!(add_business_minutes(utc(4, 17, 50), 20, Some(&hours)), utc(5, 9, 10));
+        // Friday evening to Monday morning
+        assert_eq!(add_business_minutes(utc(8, 17, 0), 120, Some(&hours)), utc(11, 10, 0));
+        // No business hours: plain clock time
+        assert_eq!(add_business_minutes(utc(9, 23, 0), 120, None), utc(10, 1, 0));
+
+        // UTC+3: 09:00 local is 06:00 UTC
+        let moscow = BusinessHours { utc_offset_minutes: 180, ..hours };
+        assert_eq!(add_business_minutes(utc(4, 5, 0), 15, Some(&moscow)), utc(4, 6, 15));
+    }
+
+    #[test]
+    fn test_timers_lifecycle() {
+        let mut policy = policy(None, None);
+        policy.next_response_minutes = Some(30);
+        policy.resolution_minutes = Some(240);
+
+        let start = utc(4, 10, 0);
+        let mut conversation = Conversation::new_waiting(1, None);
+
+        start_timers(&mut conversation, Some(&policy), start);
+        assert_eq!(conversation.first_response_due_at, Some(utc(4, 10, 15)));
+        assert_eq!(conversation.resolution_due_at, Some(utc(4, 14, 0)));
+        assert_eq!(conversation.sla_due_at(), Some(utc(4, 10, 15)));
+
+        // Follow-ups before the first reply don't start the next-response timer
+        on_customer_message(&mut conversation, Some(&policy), utc(4, 10, 5));
+        assert_eq!(conversation.next_response_due_at, None);
+
+        on_operator_reply(&mut conversation, utc(4, 10, 10));
+        assert_eq!(conversation.first_response_due_at, None);
+        assert_eq!(conversation.first_responded_at, Some(utc(4, 10, 10)));
+
+        on_customer_message(&mut conversation, Some(&policy), utc(4, 11, 0));
+        assert_eq!(conversation.next_response_due_at, Some(utc(4, 11, 30)));
+
+        stop_timers(&mut conversation);
+        assert_eq!(conversation.sla_due_at(), None);
+    }
+
+    #[test]
+    fn test_timer_state() {
+        let due = utc(4, 10, 15);
+
+        assert_eq!(timer_state(due, 5, utc(4, 10, 0)), None);
+        assert_eq!(timer_state(due, 5, utc(4, 10, 11)), Some(SlaEventKind::Warning));
+        assert_eq!(timer_state(due, 5, utc(4, 10, 15)), Some(SlaEventKind::Breach));
+    }
+}
@@ -0,0 +1,248 @@
+//! Snoozed conversations
+//!
+//! An operator can put a conversation aside until a given time. The
+//! background worker wakes it when that time comes, and a customer message
+//! wakes it early. Woken conversations go back to `Active` (or `Waiting`
+//! when nobody is assigned) and the assigned operator is notified.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::sync::Arc;
+use storehaus::prelude::*;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::models::{Conversation, ConversationStatus, TelegramUser, User};
+use crate::telegram::BotManager;
+use crate::websocket::{WebSocketEvent, WebSocketManager};
+
+/// How often the background worker wakes due conversations
+pub const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Why a snoozed conversation woke up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WakeReason {
+    /// The snooze time passed
+    Scheduled,
+    /// The customer wrote
+    CustomerReply,
+    /// An operator woke it by hand
+    Manual,
+}
+
+impl WakeReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WakeReason::Scheduled => "scheduled",
+            WakeReason::CustomerReply => "customer_reply",
+            WakeReason::Manual => "manual",
+        }
+    }
+}
+
+/// Whether a snoozed conversation is due to wake at `now`
+pub fn is_due(conversation: &Conversation, now: DateTime<Utc>) -> bool {
+    conversation.is_snoozed() && conversation.snoozed_until.is_none_or(|until| until <= now)
+}
+
+/// Snooze a conversation until `until`
+pub async fn snooze(
+    storehaus: &StoreHaus,
+    ws_manager: &WebSocketManager,
+    mut conversation: Conversation,
+    until: DateTime<Utc>,
+    snoozed_by: Uuid,
+) -> Result<Conversation> {
+    if conversation.is_closed() {
+        bail!("Closed conversations cannot be snoozed");
+    }
+
+    let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+
+    conversation.status = ConversationStatus::Snoozed;
+    conversation.snoozed_until = Some(until);
+    conversation.snoozed_by = Some(snoozed_by);
+
+    let id = conversation.id;
+    let conversation = conversation_store
+        .update(&id, conversation, Some(vec!["snoozed".to_string()]))
+        .await?;
+
+    let ws_event = WebSocketEvent::ConversationSnoozed {
+        conversation_id: conversation.id,
+        snoozed_until: until,
+        snoozed_by,
+    };
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast ConversationSnoozed event: {}", e);
+    }
+
+    Ok(conversation)
+}
+
+/// Wake a snoozed conversation and notify the assigned operator
+/// (via Telegram when `bot` is given)
+pub async fn wake(
+    storehaus: &StoreHaus,
+    ws_manager: &WebSocketManager,
+    bot: Option<&Bot>,
+    mut conversation: Conversation,
+    reason: WakeReason,
+) -> Result<Conversation> {
+    if !conversation.is_snoozed() {
+        return Ok(conversation);
+    }
+
+    let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+
+    conversation.status = if conversation.user_id.is_some() {
+        ConversationStatus::Active
+    } else {
+        ConversationStatus::Waiting
+    };
+    conversation.snoozed_until = None;
+    conversation.snoozed_by = None;
+
+    let id = conversation.id;
+    let conversation = conversation_store
+        .update(&id, conversation, Some(vec!["woken".to_string()]))
+        .await?;
+
+    let ws_event = WebSocketEvent::ConversationWoken {
+        conversation_id: conversation.id,
+        status: conversation.status.as_str().to_string(),
+        user_id: conversation.user_id,
+        reason: reason.as_str().to_string(),
+    };
+    if let Err(e) = ws_manager.broadcast_event(ws_event).await {
+        warn!("Failed to broadcast ConversationWoken event: {}", e);
+    }
+
+    if let Some(bot) = bot {
+        if let Err(e) = notify_operator(storehaus, bot, &conversation, reason).await {
+            warn!("Failed to notify operator about woken conversation {}: {}", conversation.id, e);
+        }
+    }
+
+    Ok(conversation)
+}
+
+/// Wake every conversation whose snooze time passed
+pub async fn wake_due(storehaus: &StoreHaus, ws_manager: &WebSocketManager, bot: Option<&Bot>) -> Result<usize> {
+    let conversation_store = storehaus.get_store::<GenericStore<Conversation>>("conversations")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("status", json!(ConversationStatus::Snoozed.as_str())));
+    let snoozed = conversation_store.find(query).await?;
+
+    let now = Utc::now();
+    let mut woken = 0;
+    for conversation in snoozed.into_iter().filter(|c| is_due(c, now)) {
+        wake(storehaus, ws_manager, bot, conversation, WakeReason::Scheduled).await?;
+        woken += 1;
+    }
+
+    Ok(woken)
+}
+
+/// Telegram notification to the assigned operator
+async fn notify_operator(storehaus: &StoreHaus, bot: &Bot, conversation: &Conversation, reason: WakeReason) -> Result<()> {
+    let Some(user_id) = conversation.user_id else {
+        return Ok(());
+    };
+
+    let user_store = storehaus.get_store::<GenericStore<User>>("users")?;
+    let Some(user) = user_store.get_by_id(&user_id).await? else {
+        return Ok(());
+    };
+
+    let settings = user.parsed_settings();
+    if !settings.notifications_enabled {
+        return Ok(());
+    }
+    let Some(chat_id) = settings
+        .telegram_notifications_user_id
+        .as_deref()
+        .and_then(|id| id.parse::<i64>().ok())
+    else {
+        return Ok(());
+    };
+
+    let telegram_user_store = storehaus.get_store::<GenericStore<TelegramUser>>("telegram_users")?;
+    let customer = telegram_user_store
+        .get_by_id(&conversation.telegram_user_id)
+        .await?
+        .map(|u| u.full_name())
+        .unwrap_or_else(|| format!("User {}", conversation.telegram_user_id));
+
+    let cause = match reason {
+        WakeReason::Scheduled => "Snooze time is over.",
+        WakeReason::CustomerReply => "The customer replied.",
+        WakeReason::Manual => "Woken by an operator.",
+    };
+
+    let notification = format!(
+        "🔔 <b>Snoozed conversation is back</b>\n\n\
+        Customer: {}\n\
+        {}\n\n\
+        Please log in to the system to respond.",
+        escape_html(&customer),
+        cause
+    );
+
+    bot.send_message(ChatId(chat_id), notification)
+        .parse_mode(ParseMode::Html)
+        .await?;
+
+    Ok(())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Periodically wake snoozed conversations
+pub fn spawn_snooze_worker(
+    storehaus: Arc<StoreHaus>,
+    ws_manager: Arc<WebSocketManager>,
+    bot_manager: Arc<BotManager>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let bot = bot_manager.bot().await;
+            match wake_due(&storehaus, &ws_manager, bot.as_ref()).await {
+                Ok(woken) if woken > 0 => info!("Woke {} snoozed conversations", woken),
+                Ok(_) => {}
+                Err(e) => error!("Failed to wake snoozed conversations: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_is_due() {
+        let now = Utc::now();
+        let snoozed = Conversation {
+            status: ConversationStatus::Snoozed,
+            snoozed_until: Some(now + Duration::hours(1)),
+            ..Conversation::new_waiting(1, None)
+        };
+
+        assert!(!is_due(&snoozed, now));
+        assert!(is_due(&snoozed, now + Duration::hours(1)));
+        assert!(is_due(&Conversation { snoozed_until: None, ..snoozed.clone() }, now));
+        assert!(!is_due(&Conversation { status: ConversationStatus::Active, ..snoozed }, now + Duration::hours(2)));
+    }
+}
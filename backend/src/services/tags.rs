@@ -0,0 +1,200 @@
+//! Tag helpers
+//!
+//! Tags are linked to conversations and telegram users through the
+//! `conversation_tags` and `telegram_user_tags` tables. The tag catalog is
+//! small, so lookups load it once and resolve links in memory.
+
+use anyhow::Result;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::models::{ConversationTag, Tag, TelegramUserTag};
+
+/// Load all tags ordered by name
+pub async fn list_tags(storehaus: &StoreHaus) -> Result<Vec<Tag>> {
+    let store = storehaus.get_store::<GenericStore<Tag>>("tags")?;
+
+    let query = QueryBuilder::new().order_by("name", SortOrder::Asc);
+    Ok(store.find(query).await?)
+}
+
+/// Find a tag by name, ignoring case
+pub async fn find_by_name(storehaus: &StoreHaus, name: &str) -> Result<Option<Tag>> {
+    let name = name.to_lowercase();
+    let tags = list_tags(storehaus).await?;
+
+    Ok(tags.into_iter().find(|t| t.name.to_lowercase() == name))
+}
+
+/// Tags of each conversation in `conversation_ids`
+pub async fn conversation_tags(
+    storehaus: &StoreHaus,
+    conversation_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<Tag>>> {
+    let mut result: HashMap<Uuid, Vec<Tag>> = HashMap::new();
+    if conversation_ids.is_empty() {
+        return Ok(result);
+    }
+
+    let store = storehaus.get_store::<GenericStore<ConversationTag>>("conversation_tags")?;
+
+    let query = QueryBuilder::new().filter(QueryFilter::or(
+        conversation_ids
+            .iter()
+            .map(|id| QueryFilter::eq("conversation_id", json!(id)))
+            .collect(),
+    ));
+    let links = store.find(query).await?;
+    if links.is_empty() {
+        return Ok(result);
+    }
+
+    let tags: HashMap<Uuid, Tag> = list_tags(storehaus)
+        .await?
+        .into_iter()
+        .map(|t| (t.id, t))
+        .collect();
+
+    for link in links {
+        if let Some(tag) = tags.get(&link.tag_id) {
+            result.entry(link.conversation_id).or_default().push(tag.clone());
+        }
+    }
+
+    for tags in result.values_mut() {
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    Ok(result)
+}
+
+/// Tags of a single telegram user
+pub async fn telegram_user_tags(storehaus: &StoreHaus, telegram_user_id: i64) -> Result<Vec<Tag>> {
+    let store = storehaus.get_store::<GenericStore<TelegramUserTag>>("telegram_user_tags")?;
+
+    let query = QueryBuilder::new().filter(QueryFilter::eq("telegram_user_id", json!(telegram_user_id)));
+    let tag_ids: HashSet<Uuid> = store.find(query).await?.into_iter().map(|l| l.tag_id).collect();
+
+    let tags = list_tags(storehaus).await?;
+    Ok(tags.into_iter().filter(|t| tag_ids.contains(&t.id)).collect())
+}
+
+/// Tag a conversation. Returns `false` if the tag was already present.
+pub async fn add_conversation_tag(
+    storehaus: &StoreHaus,
+    conversation_id: Uuid,
+    tag_id: Uuid,
+    added_by: Option<Uuid>,
+) -> Result<bool> {
+    let store = storehaus.get_store::<GenericStore<ConversationTag>>("conversation_tags")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("conversation_id", json!(conversation_id)))
+        .filter(QueryFilter::eq("tag_id", json!(tag_id)));
+    if store.find_one(query).await?.is_some() {
+        return Ok(false);
+    }
+
+    let link = ConversationTag::create(conversation_id, tag_id, added_by);
+    store.create(link, Some(vec!["tagged".to_string()])).await?;
+
+    Ok(true)
+}
+
+/// Remove a tag from a conversation. Returns `false` if it was not present.
+pub async fn remove_conversation_tag(storehaus: &StoreHaus, conversation_id: Uuid, tag_id: Uuid) -> Result<bool> {
+    let store = storehaus.get_store::<GenericStore<ConversationTag>>("conversation_tags")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("conversation_id", json!(conversation_id)))
+        .filter(QueryFilter::eq("tag_id", json!(tag_id)));
+    let links = store.find(query).await?;
+
+    for link in &links {
+        store.delete(&link.id).await?;
+    }
+
+    Ok(!links.is_empty())
+}
+
+/// Tag a telegram user. Returns `false` if the tag was already present.
+pub async fn add_telegram_user_tag(
+    storehaus: &StoreHaus,
+    telegram_user_id: i64,
+    tag_id: Uuid,
+    added_by: Option<Uuid>,
+) -> Result<bool> {
+    let store = storehaus.get_store::<GenericStore<TelegramUserTag>>("telegram_user_tags")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("telegram_user_id", json!(telegram_user_id)))
+        .filter(QueryFilter::eq("tag_id", json!(tag_id)));
+    if store.find_one(query).await?.is_some() {
+        return Ok(false);
+    }
+
+    let link = TelegramUserTag::create(telegram_user_id, tag_id, added_by);
+    store.create(link, Some(vec!["tagged".to_string()])).await?;
+
+    Ok(true)
+}
+
+/// Remove a tag from a telegram user. Returns `false` if it was not present.
+pub async fn remove_telegram_user_tag(storehaus: &StoreHaus, telegram_user_id: i64, tag_id: Uuid) -> Result<bool> {
+    let store = storehaus.get_store::<GenericStore<TelegramUserTag>>("telegram_user_tags")?;
+
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("telegram_user_id", json!(telegram_user_id)))
+        .filter(QueryFilter::eq("tag_id", json!(tag_id)));
+    let links = store.find(query).await?;
+
+    for link in &links {
+        store.delete(&link.id).await?;
+    }
+
+    Ok(!links.is_empty())
+}
+
+/// Remove every link to a tag (before deleting it)
+pub async fn remove_tag_links(storehaus: &StoreHaus, tag_id: Uuid) -> Result<()> {
+    let conversation_store = storehaus.get_store::<GenericStore<ConversationTag>>("conversation_tags")?;
+    let query = QueryBuilder::new().filter(QueryFilter::eq("tag_id", json!(tag_id)));
+    for link in conversation_store.find(query).await? {
+        conversation_store.delete(&link.id).await?;
+    }
+
+    let user_store = storehaus.get_store::<GenericStore<TelegramUserTag>>("telegram_user_tags")?;
+    let query = QueryBuilder::new().filter(QueryFilter::eq("tag_id", json!(tag_id)));
+    for link in user_store.find(query).await? {
+        user_store.delete(&link.id).await?;
+    }
+
+    Ok(())
+}
+
+/// Parse a comma-separated list of tag IDs
+pub fn parse_tag_ids(value: &str) -> Option<Vec<Uuid>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| Uuid::parse_str(s).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tag_ids() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+
+        assert_eq!(parse_tag_ids(&format!("{}, {},", a, b)), Some(vec![a, b]));
+        assert_eq!(parse_tag_ids(""), Some(vec![]));
+        assert_eq!(parse_tag_ids("not-a-uuid"), None);
+    }
+}
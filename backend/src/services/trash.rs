@@ -0,0 +1,231 @@
+//! Conversation trash
+//!
+//! Deleting a conversation only sets its `__deleted_at__` (and `deleted_by`),
+//! which hides it from every store query while keeping its messages, edits
+//! and tags. Admins can list the trash, restore from it, or purge a trashed
+//! conversation, which removes it and everything attached to it for good.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+use storehaus::prelude::*;
+use uuid::Uuid;
+
+use crate::services::audit;
+
+/// Conversation in the trash
+#[derive(Debug, Clone, Serialize)]
+pub struct DeletedConversation {
+    pub id: Uuid,
+    pub telegram_user_id: i64,
+    pub customer_name: String,
+    pub user_id: Option<Uuid>,
+    pub status: String,
+    pub message_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: DateTime<Utc>,
+    pub deleted_by: Option<Uuid>,
+}
+
+/// What a purge removed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PurgeSummary {
+    pub messages: u64,
+    pub message_edits: u64,
+    /// Messages that referenced media (Telegram file IDs; nothing is stored locally)
+    pub media_files: u64,
+    pub tags: u64,
+    pub assignments: u64,
+    pub sla_events: u64,
+    pub merges: u64,
+    pub read_cursors: u64,
+}
+
+const DELETED_SELECT: &str = "\
+    SELECT c.id, c.telegram_user_id, c.user_id, c.status, c.deleted_by, \
+           c.__created_at__ AS created_at, c.__deleted_at__ AS deleted_at, \
+           TRIM(CONCAT(tu.first_name, ' ', COALESCE(tu.last_name, ''))) AS customer_name, \
+           (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id AND m.__deleted_at__ IS NULL) AS message_count \
+    FROM conversations c \
+    LEFT JOIN telegram_users tu ON tu.id = c.telegram_user_id \
+    WHERE c.__deleted_at__ IS NOT NULL";
+
+fn deleted_from_row(row: &sqlx::postgres::PgRow) -> Result<DeletedConversation> {
+    Ok(DeletedConversation {
+        id: row.try_get("id")?,
+        telegram_user_id: row.try_get("telegram_user_id")?,
+        customer_name: row.try_get::<Option<String>, _>("customer_name")?.unwrap_or_default(),
+        user_id: row.try_get("user_id")?,
+        status: row.try_get("status")?,
+        message_count: row.try_get("message_count").unwrap_or(0),
+        created_at: row.try_get("created_at")?,
+        deleted_at: row.try_get("deleted_at")?,
+        deleted_by: row.try_get("deleted_by")?,
+    })
+}
+
+/// Move a conversation to the trash. Returns false when it was not found
+/// or already trashed.
+pub async fn soft_delete(storehaus: &StoreHaus, conversation_id: Uuid, deleted_by: Uuid) -> Result<bool> {
+    let updated = sqlx::query(
+        "UPDATE conversations SET __deleted_at__ = NOW(), deleted_by = $2 \
+         WHERE id = $1 AND __deleted_at__ IS NULL",
+    )
+    .bind(conversation_id)
+    .bind(deleted_by)
+    .execute(storehaus.pool())
+    .await?
+    .rows_affected();
+
+    Ok(updated > 0)
+}
+
+/// Trashed conversations, most recently deleted first
+pub async fn list(storehaus: &StoreHaus, limit: i64, offset: i64) -> Result<(Vec<DeletedConversation>, i64)> {
+    let sql = format!("{} ORDER BY c.__deleted_at__ DESC LIMIT $1 OFFSET $2", DELETED_SELECT);
+    let rows = sqlx::query(&sql)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(storehaus.pool())
+        .await?;
+
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM conversations WHERE __deleted_at__ IS NOT NULL")
+        .fetch_one(storehaus.pool())
+        .await?;
+
+    let conversations = rows.iter().map(deleted_from_row).collect::<Result<Vec<_>>>()?;
+
+    Ok((conversations, total))
+}
+
+/// A trashed conversation
+pub async fn find(storehaus: &StoreHaus, conversation_id: Uuid) -> Result<Option<DeletedConversation>> {
+    let sql = format!("{} AND c.id = $1", DELETED_SELECT);
+    let row = sqlx::query(&sql)
+        .bind(conversation_id)
+        .fetch_optional(storehaus.pool())
+        .await?;
+
+    row.as_ref().map(deleted_from_row).transpose()
+}
+
+/// Take a conversation out of the trash. Returns false when it was not trashed.
+pub async fn restore(storehaus: &StoreHaus, conversation_id: Uuid) -> Result<bool> {
+    let updated = sqlx::query(
+        "UPDATE conversations SET __deleted_at__ = NULL, deleted_by = NULL \
+         WHERE id = $1 AND __deleted_at__ IS NOT NULL",
+    )
+    .bind(conversation_id)
+    .execute(storehaus.pool())
+    .await?
+    .rows_affected();
+
+    Ok(updated > 0)
+}
+
+/// Remove a trashed conversation with its messages, message edits, media
+/// references, tags, assignment history, SLA events, read cursors and merge
+/// records, and record the purge by `purged_by` in the audit log in the same
+/// transaction. Returns `None` when the conversation is not in the trash.
+pub async fn purge(storehaus: &StoreHaus, conversation_id: Uuid, purged_by: Uuid) -> Result<Option<PurgeSummary>> {
+    let mut tx = storehaus.pool().begin().await?;
+
+    let trashed = sqlx::query(
+        "SELECT telegram_user_id, deleted_by, __deleted_at__ AS deleted_at FROM conversations \
+         WHERE id = $1 AND __deleted_at__ IS NOT NULL FOR UPDATE",
+    )
+    .bind(conversation_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(trashed) = trashed else {
+        return Ok(None);
+    };
+    let telegram_user_id: i64 = trashed.try_get("telegram_user_id")?;
+    let deleted_by: Option<Uuid> = trashed.try_get("deleted_by")?;
+    let deleted_at: DateTime<Utc> = trashed.try_get("deleted_at")?;
+
+    let mut summary = PurgeSummary {
+        media_files: sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM messages WHERE conversation_id = $1 AND media_url IS NOT NULL",
+        )
+        .bind(conversation_id)
+        .fetch_one(&mut *tx)
+        .await? as u64,
+        ..Default::default()
+    };
+
+    summary.message_edits = sqlx::query(
+        "DELETE FROM message_edits WHERE message_id IN (SELECT id FROM messages WHERE conversation_id = $1)",
+    )
+    .bind(conversation_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let delete_by_conversation = |table: &str| format!("DELETE FROM {} WHERE conversation_id = $1", table);
+
+    summary.messages = sqlx::query(&delete_by_conversation("messages"))
+        .bind(conversation_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    summary.tags = sqlx::query(&delete_by_conversation("conversation_tags"))
+        .bind(conversation_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    summary.assignments = sqlx::query(&delete_by_conversation("conversation_assignments"))
+        .bind(conversation_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    summary.sla_events = sqlx::query(&delete_by_conversation("sla_events"))
+        .bind(conversation_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    summary.read_cursors = sqlx::query(&delete_by_conversation("read_cursors"))
+        .bind(conversation_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    summary.merges = sqlx::query(
+        "DELETE FROM conversation_merges WHERE source_conversation_id = $1 OR target_conversation_id = $1",
+    )
+    .bind(conversation_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    // Later conversations of the customer lose the link, not their history
+    sqlx::query("UPDATE conversations SET previous_conversation_id = NULL WHERE previous_conversation_id = $1")
+        .bind(conversation_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM conversations WHERE id = $1")
+        .bind(conversation_id)
+        .execute(&mut *tx)
+        .await?;
+
+    // The conversation is gone, so the audit entry is the only trace left
+    let details = json!({
+        "telegram_user_id": telegram_user_id,
+        "deleted_at": deleted_at,
+        "deleted_by": deleted_by,
+        "removed": summary,
+    });
+    audit::record_conversation_in(&mut *tx, audit::CONVERSATION_PURGED, conversation_id, Some(purged_by), Some(details))
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(Some(summary))
+}
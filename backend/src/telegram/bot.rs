@@ -1,53 +1,96 @@
 use anyhow::Result;
 use storehaus::StoreHaus;
+use std::fmt;
 use std::sync::Arc;
-use teloxide::prelude::*;
+use teloxide::{dispatching::ShutdownToken, prelude::*, types::Me, ApiError, RequestError};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tracing::info;
 
 use crate::websocket::WebSocketManager;
+use super::bot_manager::BotHealth;
 use super::handlers::handle_message;
 
+/// Timeout for the initial `getMe` request
+const CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Telegram bot state
 #[derive(Clone)]
 pub struct BotState {
     pub storehaus: Arc<StoreHaus>,
     pub ws_manager: Arc<WebSocketManager>,
+    pub health: Arc<RwLock<BotHealth>>,
+    /// Secret for verifying signed deep-link payloads
+    pub deep_link_secret: Option<String>,
 }
 
-/// Initialize and run the Telegram bot
-pub async fn run_bot(bot_token: String, storehaus: Arc<StoreHaus>, ws_manager: Arc<WebSocketManager>) -> Result<()> {
-    info!("Initializing Telegram bot...");
-
-    let bot = Bot::new(bot_token);
+/// Error returned when connecting to Telegram
+#[derive(Debug, Clone)]
+pub enum ConnectError {
+    /// Token was rejected by Telegram, retrying will not help
+    InvalidToken(String),
+    /// Telegram API is unreachable or slow, worth retrying
+    Unavailable(String),
+}
 
-    // Get bot info (skip if token is invalid to not block the application)
-    // Use timeout to avoid blocking if Telegram API is slow
-    match tokio::time::timeout(std::time::Duration::from_secs(5), bot.get_me()).await {
-        Ok(Ok(me)) => {
-            info!("Bot started: @{}", me.username());
-        }
-        Ok(Err(e)) => {
-            info!("Telegram bot initialization skipped (invalid token): {}", e);
-            return Ok(()); // Return early but don't fail the application
-        }
-        Err(_) => {
-            info!("Telegram bot initialization skipped (timeout waiting for Telegram API)");
-            return Ok(()); // Return early but don't fail the application
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectError::InvalidToken(e) => write!(f, "Invalid bot token: {}", e),
+            ConnectError::Unavailable(e) => write!(f, "Telegram API unavailable: {}", e),
         }
     }
+}
 
-    let state = BotState { storehaus, ws_manager };
+impl std::error::Error for ConnectError {}
+
+/// Check the token with `getMe`
+pub async fn connect(bot: &Bot) -> Result<Me, ConnectError> {
+    match tokio::time::timeout(CONNECT_TIMEOUT, bot.get_me()).await {
+        Ok(Ok(me)) => Ok(me),
+        Ok(Err(RequestError::Api(ApiError::InvalidToken))) => {
+            Err(ConnectError::InvalidToken(ApiError::InvalidToken.to_string()))
+        }
+        Ok(Err(e)) => Err(ConnectError::Unavailable(e.to_string())),
+        Err(_) => Err(ConnectError::Unavailable("timeout waiting for Telegram API".to_string())),
+    }
+}
 
+/// Spawn the update dispatcher, returning its shutdown token and task handle
+pub fn spawn_dispatcher(bot: Bot, state: BotState) -> (ShutdownToken, JoinHandle<()>) {
     // Setup message handler
     let handler = Update::filter_message().endpoint(handle_message);
 
-    // Run the dispatcher
-    Dispatcher::builder(bot, handler)
+    // Shutdown is driven by BotManager::stop, not by a Ctrl+C handler of its own
+    let mut dispatcher = Dispatcher::builder(bot, handler)
         .dependencies(dptree::deps![state])
-        .enable_ctrlc_handler()
-        .build()
-        .dispatch()
-        .await;
+        .build();
+
+    let shutdown_token = dispatcher.shutdown_token();
+    let handle = tokio::spawn(async move {
+        dispatcher.dispatch().await;
+    });
+
+    (shutdown_token, handle)
+}
+
+/// Initialize and run the Telegram bot until the dispatcher stops
+pub async fn run_bot(bot_token: String, storehaus: Arc<StoreHaus>, ws_manager: Arc<WebSocketManager>) -> Result<()> {
+    info!("Initializing Telegram bot...");
+
+    let bot = Bot::new(bot_token);
+    let me = connect(&bot).await?;
+    info!("Bot started: @{}", me.username());
+
+    let state = BotState {
+        storehaus,
+        ws_manager,
+        health: Arc::new(RwLock::new(BotHealth::default())),
+        deep_link_secret: None,
+    };
+
+    let (_shutdown_token, handle) = spawn_dispatcher(bot, state);
+    handle.await?;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,13 +1,28 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use storehaus::StoreHaus;
 use std::sync::Arc;
-use teloxide::prelude::*;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use teloxide::{dispatching::ShutdownToken, prelude::*, types::Me};
+use tokio::sync::{watch, RwLock};
 use tokio::task::JoinHandle;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::websocket::{WebSocketManager, WebSocketEvent};
-use super::bot::run_bot;
+use super::bot::{connect, spawn_dispatcher, BotState, ConnectError};
+
+/// First restart delay, doubled after each consecutive failure
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Upper bound for the restart delay
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A connection that lived this long resets the backoff
+const STABLE_CONNECTION: Duration = Duration::from_secs(60);
+
+/// Time to wait for in-flight updates on shutdown
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Status of the bot connection
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,19 +37,93 @@ pub enum BotStatus {
     Error,
 }
 
+impl BotStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BotStatus::Disconnected => "disconnected",
+            BotStatus::Connecting => "connecting",
+            BotStatus::Connected => "connected",
+            BotStatus::Error => "error",
+        }
+    }
+}
+
+/// Health details reported by the supervisor
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BotHealth {
+    /// Bot username from `getMe`
+    pub bot_username: Option<String>,
+    /// When the current connection was established
+    pub connected_since: Option<DateTime<Utc>>,
+    /// When the last update from Telegram was handled
+    pub last_update_at: Option<DateTime<Utc>>,
+    /// Last connection or dispatcher error
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    /// Number of automatic restarts since the bot was started
+    pub restart_count: u32,
+    /// When the next restart attempt is scheduled
+    pub next_retry_at: Option<DateTime<Utc>>,
+}
+
+impl BotHealth {
+    /// Seconds since the current connection was established
+    pub fn uptime_seconds(&self) -> Option<i64> {
+        self.connected_since
+            .map(|since| Utc::now().signed_duration_since(since).num_seconds())
+    }
+}
+
+/// Delay before restart attempt number `attempt` (starting at 0)
+pub fn backoff_delay(attempt: u32) -> Duration {
+    INITIAL_BACKOFF
+        .checked_mul(2u32.saturating_pow(attempt))
+        .unwrap_or(MAX_BACKOFF)
+        .min(MAX_BACKOFF)
+}
+
+/// Running supervisor task
+struct Supervisor {
+    handle: JoinHandle<()>,
+    stop_tx: watch::Sender<bool>,
+}
+
+/// Aborts the wrapped task when dropped, so an aborted supervisor
+/// never leaves an orphaned dispatcher behind
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 /// Manages the Telegram bot lifecycle
+#[derive(Clone)]
 pub struct BotManager {
     storehaus: Arc<StoreHaus>,
     ws_manager: Arc<WebSocketManager>,
 
-    /// Current bot task handle
-    bot_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
+    /// Current supervisor task
+    supervisor: Arc<RwLock<Option<Supervisor>>>,
+
+    /// Shutdown token of the running dispatcher
+    shutdown_token: Arc<RwLock<Option<ShutdownToken>>>,
 
     /// Current bot status
     status: Arc<RwLock<BotStatus>>,
 
+    /// Health details for status reporting
+    health: Arc<RwLock<BotHealth>>,
+
     /// Current bot instance (for API calls)
     bot: Arc<RwLock<Option<Bot>>>,
+
+    /// Custom Bot API base URL (local Bot API server or test mock)
+    api_url: Option<reqwest::Url>,
+
+    /// Secret for verifying signed deep-link payloads
+    deep_link_secret: Option<String>,
 }
 
 impl BotManager {
@@ -43,81 +132,86 @@ impl BotManager {
         Self {
             storehaus,
             ws_manager,
-            bot_handle: Arc::new(RwLock::new(None)),
+            supervisor: Arc::new(RwLock::new(None)),
+            shutdown_token: Arc::new(RwLock::new(None)),
             status: Arc::new(RwLock::new(BotStatus::Disconnected)),
+            health: Arc::new(RwLock::new(BotHealth::default())),
             bot: Arc::new(RwLock::new(None)),
+            api_url: None,
+            deep_link_secret: None,
         }
     }
 
+    /// Use a custom Bot API base URL instead of https://api.telegram.org
+    pub fn with_api_url(mut self, api_url: Option<reqwest::Url>) -> Self {
+        self.api_url = api_url;
+        self
+    }
+
+    /// Verify signed `/start` payloads with this secret
+    pub fn with_deep_link_secret(mut self, secret: Option<String>) -> Self {
+        self.deep_link_secret = secret;
+        self
+    }
+
     /// Get current bot status
     pub async fn status(&self) -> BotStatus {
         *self.status.read().await
     }
 
+    /// Get current health details
+    pub async fn health(&self) -> BotHealth {
+        self.health.read().await.clone()
+    }
+
     /// Get bot instance for API calls (if connected)
     pub async fn bot(&self) -> Option<Bot> {
         self.bot.read().await.clone()
     }
 
     /// Start the bot with given token
+    ///
+    /// Fails only when Telegram rejects the token. If Telegram is unreachable
+    /// the supervisor keeps retrying in the background.
     pub async fn start(&self, token: String) -> Result<()> {
         info!("[BOT_MANAGER] Starting bot with token: {}...", &token[..10.min(token.len())]);
 
         // Stop existing bot if running
         self.stop().await?;
 
+        *self.health.write().await = BotHealth::default();
+
         // Update status to connecting
-        *self.status.write().await = BotStatus::Connecting;
-        self.broadcast_status_change(BotStatus::Connecting).await;
-
-        // Create bot instance
-        let bot = Bot::new(token.clone());
-
-        // Test bot connection with timeout
-        match tokio::time::timeout(std::time::Duration::from_secs(5), bot.get_me()).await {
-            Ok(Ok(me)) => {
-                info!("[BOT_MANAGER] Bot connected successfully: @{}", me.username());
-                *self.bot.write().await = Some(bot.clone());
-                *self.status.write().await = BotStatus::Connected;
-                self.broadcast_status_change(BotStatus::Connected).await;
-            }
-            Ok(Err(e)) => {
+        self.set_status(BotStatus::Connecting).await;
+
+        let bot = match &self.api_url {
+            Some(url) => Bot::new(token).set_api_url(url.clone()),
+            None => Bot::new(token),
+        };
+
+        // Test bot connection before handing over to the supervisor
+        let connected = match connect(&bot).await {
+            Ok(me) => Some(me),
+            Err(ConnectError::InvalidToken(e)) => {
                 error!("[BOT_MANAGER] Failed to connect bot: {}", e);
-                *self.status.write().await = BotStatus::Error;
-                self.broadcast_status_change(BotStatus::Error).await;
+                self.record_failure(e.clone(), None).await;
                 return Err(anyhow::anyhow!("Failed to connect to Telegram: {}", e));
             }
-            Err(_) => {
-                error!("[BOT_MANAGER] Bot connection timeout");
-                *self.status.write().await = BotStatus::Error;
-                self.broadcast_status_change(BotStatus::Error).await;
-                return Err(anyhow::anyhow!("Telegram connection timeout"));
+            Err(ConnectError::Unavailable(e)) => {
+                warn!("[BOT_MANAGER] Telegram unavailable, will keep retrying: {}", e);
+                None
             }
-        }
-
-        // Spawn bot task
-        let storehaus = self.storehaus.clone();
-        let ws_manager = self.ws_manager.clone();
-        let status = self.status.clone();
-        let bot_ref = self.bot.clone();
+        };
 
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let manager = self.clone();
         let handle = tokio::spawn(async move {
-            info!("[BOT_MANAGER] Bot task started");
-
-            if let Err(e) = run_bot(token, storehaus, ws_manager).await {
-                error!("[BOT_MANAGER] Bot task error: {}", e);
-                *status.write().await = BotStatus::Error;
-                *bot_ref.write().await = None;
-            } else {
-                info!("[BOT_MANAGER] Bot task ended gracefully");
-                *status.write().await = BotStatus::Disconnected;
-                *bot_ref.write().await = None;
-            }
+            manager.supervise(bot, connected, stop_rx).await;
         });
 
-        *self.bot_handle.write().await = Some(handle);
+        *self.supervisor.write().await = Some(Supervisor { handle, stop_tx });
 
-        info!("[BOT_MANAGER] Bot started successfully");
+        info!("[BOT_MANAGER] Bot supervisor started");
         Ok(())
     }
 
@@ -125,19 +219,38 @@ impl BotManager {
     pub async fn stop(&self) -> Result<()> {
         info!("[BOT_MANAGER] Stopping bot");
 
-        // Abort existing task if running
-        let mut handle = self.bot_handle.write().await;
-        if let Some(h) = handle.take() {
-            h.abort();
-            info!("[BOT_MANAGER] Bot task aborted");
+        let supervisor = self.supervisor.write().await.take();
+        if let Some(supervisor) = supervisor {
+            // Tell the supervisor not to restart, then let the dispatcher finish in-flight updates
+            let _ = supervisor.stop_tx.send(true);
+
+            let shutdown_token = self.shutdown_token.write().await.take();
+            if let Some(token) = shutdown_token {
+                if let Ok(shutdown) = token.shutdown() {
+                    if tokio::time::timeout(SHUTDOWN_TIMEOUT, shutdown).await.is_err() {
+                        warn!("[BOT_MANAGER] Dispatcher did not stop in time");
+                    }
+                }
+            }
+
+            let mut handle = supervisor.handle;
+            if tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut handle).await.is_err() {
+                handle.abort();
+                info!("[BOT_MANAGER] Bot supervisor aborted");
+            }
         }
 
         // Clear bot instance
         *self.bot.write().await = None;
 
+        {
+            let mut health = self.health.write().await;
+            health.connected_since = None;
+            health.next_retry_at = None;
+        }
+
         // Update status
-        *self.status.write().await = BotStatus::Disconnected;
-        self.broadcast_status_change(BotStatus::Disconnected).await;
+        self.set_status(BotStatus::Disconnected).await;
 
         info!("[BOT_MANAGER] Bot stopped");
         Ok(())
@@ -154,21 +267,162 @@ impl BotManager {
         self.start(token).await
     }
 
+    /// Keep the dispatcher running, restarting it with exponential backoff
+    async fn supervise(&self, bot: Bot, mut connected: Option<Me>, mut stop_rx: watch::Receiver<bool>) {
+        let mut attempt: u32 = 0;
+
+        loop {
+            let me = match connected.take() {
+                Some(me) => me,
+                None => match connect(&bot).await {
+                    Ok(me) => me,
+                    Err(ConnectError::InvalidToken(e)) => {
+                        error!("[BOT_MANAGER] Bot token rejected, giving up: {}", e);
+                        self.record_failure(e, None).await;
+                        return;
+                    }
+                    Err(ConnectError::Unavailable(e)) => {
+                        if !self.wait_before_restart(e, &mut attempt, &mut stop_rx).await {
+                            return;
+                        }
+                        continue;
+                    }
+                },
+            };
+
+            info!("[BOT_MANAGER] Bot connected successfully: @{}", me.username());
+            self.mark_connected(&bot, &me).await;
+
+            let state = BotState {
+                storehaus: self.storehaus.clone(),
+                ws_manager: self.ws_manager.clone(),
+                health: self.health.clone(),
+                deep_link_secret: self.deep_link_secret.clone(),
+            };
+
+            let (shutdown_token, handle) = spawn_dispatcher(bot.clone(), state);
+            *self.shutdown_token.write().await = Some(shutdown_token);
+
+            let started = Instant::now();
+            let mut dispatcher = AbortOnDrop(handle);
+            let reason = match (&mut dispatcher.0).await {
+                Ok(()) => "Dispatcher stopped unexpectedly".to_string(),
+                Err(e) if e.is_panic() => "Dispatcher panicked".to_string(),
+                Err(e) => format!("Dispatcher task failed: {}", e),
+            };
+            *self.shutdown_token.write().await = None;
+
+            if *stop_rx.borrow() {
+                info!("[BOT_MANAGER] Bot task ended gracefully");
+                return;
+            }
+
+            error!("[BOT_MANAGER] {}", reason);
+
+            if started.elapsed() >= STABLE_CONNECTION {
+                attempt = 0;
+            }
+
+            if !self.wait_before_restart(reason, &mut attempt, &mut stop_rx).await {
+                return;
+            }
+        }
+    }
+
+    /// Record the failure and sleep for the backoff delay.
+    /// Returns false if the bot was stopped while waiting.
+    async fn wait_before_restart(
+        &self,
+        reason: String,
+        attempt: &mut u32,
+        stop_rx: &mut watch::Receiver<bool>,
+    ) -> bool {
+        let delay = backoff_delay(*attempt);
+        *attempt = attempt.saturating_add(1);
+
+        warn!("[BOT_MANAGER] Restarting bot in {:?}: {}", delay, reason);
+        self.record_failure(reason, Some(delay)).await;
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = stop_rx.changed() => return false,
+        }
+
+        if *stop_rx.borrow() {
+            return false;
+        }
+
+        self.health.write().await.restart_count += 1;
+        self.set_status(BotStatus::Connecting).await;
+        true
+    }
+
+    /// Mark the bot as connected
+    async fn mark_connected(&self, bot: &Bot, me: &Me) {
+        *self.bot.write().await = Some(bot.clone());
+
+        {
+            let mut health = self.health.write().await;
+            health.bot_username = Some(me.username().to_string());
+            health.connected_since = Some(Utc::now());
+            health.next_retry_at = None;
+        }
+
+        self.set_status(BotStatus::Connected).await;
+    }
+
+    /// Record an error and the next retry time, if any
+    async fn record_failure(&self, reason: String, retry_in: Option<Duration>) {
+        *self.bot.write().await = None;
+
+        {
+            let mut health = self.health.write().await;
+            health.connected_since = None;
+            health.last_error = Some(reason);
+            health.last_error_at = Some(Utc::now());
+            health.next_retry_at = retry_in
+                .and_then(|d| chrono::Duration::from_std(d).ok())
+                .map(|d| Utc::now() + d);
+        }
+
+        self.set_status(BotStatus::Error).await;
+    }
+
+    /// Update status and notify clients
+    async fn set_status(&self, status: BotStatus) {
+        *self.status.write().await = status;
+        self.broadcast_status_change(status).await;
+    }
+
     /// Broadcast status change to all WebSocket clients
     async fn broadcast_status_change(&self, status: BotStatus) {
-        let status_str = match status {
-            BotStatus::Disconnected => "disconnected",
-            BotStatus::Connecting => "connecting",
-            BotStatus::Connected => "connected",
-            BotStatus::Error => "error",
-        };
+        let health = self.health().await;
 
         let event = WebSocketEvent::BotStatus {
-            status: status_str.to_string(),
+            status: status.as_str().to_string(),
+            bot_username: health.bot_username,
+            connected_since: health.connected_since,
+            last_error: health.last_error,
+            restart_count: health.restart_count,
+            next_retry_at: health.next_retry_at,
         };
 
         if let Err(e) = self.ws_manager.broadcast_event(event).await {
             error!("[BOT_MANAGER] Failed to broadcast status: {}", e);
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(0), Duration::from_secs(1));
+        assert_eq!(backoff_delay(1), Duration::from_secs(2));
+        assert_eq!(backoff_delay(5), Duration::from_secs(32));
+        assert_eq!(backoff_delay(6), MAX_BACKOFF);
+        assert_eq!(backoff_delay(100), MAX_BACKOFF);
+    }
+}
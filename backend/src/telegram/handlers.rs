@@ -5,10 +5,12 @@ use std::error::Error;
 use storehaus::prelude::*;
 use teloxide::{prelude::*, types::{Message as TgMessage, UserId}, ApiError, RequestError};
 use tracing::{error, info, warn};
-use uuid::Uuid;
 
-use crate::l10n::{format_message, get_locale};
+use crate::l10n::bot_messages;
 use crate::models::{Conversation, ConversationStatus, Message, TelegramUser};
+use crate::services::settings::reopen_settings;
+use crate::services::{conversations, custom_fields, priority, queue, routing, sla, snooze};
+use crate::utils::deep_link;
 use crate::websocket::WebSocketEvent;
 
 use super::bot::BotState;
@@ -27,11 +29,26 @@ pub enum SendMessageResult {
 
 /// Main message handler
 pub async fn handle_message(bot: Bot, msg: TgMessage, state: BotState) -> ResponseResult<()> {
+    state.health.write().await.last_update_at = Some(Utc::now());
+
     // Handle commands
     if let Some(text) = msg.text() {
         if text.starts_with('/') {
-            return match text {
-                "/start" => handle_start_command(bot, msg).await,
+            let (command, payload) = match text.split_once(' ') {
+                Some((command, payload)) => (command, Some(payload.trim())),
+                None => (text, None),
+            };
+
+            return match command {
+                "/start" => {
+                    // Deep link: t.me/<bot>?start=<payload>
+                    if let Some(payload) = payload.filter(|p| !p.is_empty()) {
+                        if let Err(e) = record_start_source(&msg, &state, payload).await {
+                            error!("Failed to record deep link source: {}", e);
+                        }
+                    }
+                    handle_start_command(bot, msg).await
+                }
                 "/help" => handle_help_command(bot, msg).await,
                 _ => {
                     bot.send_message(msg.chat.id, "Unknown command. Use /help")
@@ -158,35 +175,7 @@ async fn process_user_message(bot: &Bot, msg: &TgMessage, state: &BotState) -> a
         .storehaus
         .get_store::<GenericStore<TelegramUser>>("telegram_users")?;
 
-    // Extract country code from language_code (e.g., "ru" -> "RU", "en-US" -> "US")
-    let country_code = user.language_code.as_ref().and_then(|lang| {
-        if lang.contains('-') {
-            // Format: "en-US" -> "US"
-            lang.split('-').nth(1).map(|s| s.to_uppercase())
-        } else {
-            // Format: "ru" -> "RU"
-            Some(lang.to_uppercase())
-        }
-    });
-
-    let telegram_user = match user_store.get_by_id(&(user.id.0 as i64)).await {
-        Ok(Some(u)) => u,
-        Ok(None) | Err(_) => {
-            // Create new user
-            let new_user = TelegramUser::new(
-                user.id.0 as i64,
-                user.username.clone(),
-                user.first_name.clone(),
-                user.last_name.clone(),
-                None, // photo_url - will be fetched separately
-                country_code.clone(),
-                false,
-            );
-            user_store.create(new_user.clone(), Some(vec!["new_user".to_string()])).await?;
-            info!("Created new Telegram user: {} with country_code: {:?}", user.id, country_code);
-            new_user
-        }
-    };
+    let telegram_user = get_or_create_telegram_user(user, &user_store).await?;
 
     // Fetch and update profile photo if not already set
     if telegram_user.photo_url.is_none() {
@@ -197,12 +186,12 @@ async fn process_user_message(bot: &Bot, msg: &TgMessage, state: &BotState) -> a
         }
     }
 
-    // Get user's locale
-    let locale = get_locale(telegram_user.country_code.as_deref());
+    // Get user's localized bot messages (with admin overrides)
+    let bot_texts = bot_messages(telegram_user.country_code.as_deref());
 
     // Check if user is blocked
     if telegram_user.is_blocked {
-        bot.send_message(msg.chat.id, &locale.bot.error)
+        bot.send_message(msg.chat.id, &bot_texts.error)
             .await?;
         return Ok(());
     }
@@ -212,40 +201,32 @@ async fn process_user_message(bot: &Bot, msg: &TgMessage, state: &BotState) -> a
         .storehaus
         .get_store::<GenericStore<Conversation>>("conversations")?;
 
-    // Try to find active or waiting conversation
-    let query = QueryBuilder::new()
-        .filter(QueryFilter::eq("telegram_user_id", json!(telegram_user.id)))
-        .filter(QueryFilter::or(vec![
-            QueryFilter::eq("status", json!(ConversationStatus::Waiting.as_str())),
-            QueryFilter::eq("status", json!(ConversationStatus::Active.as_str())),
-        ]));
+    // Continue the open conversation, reopen a recently closed one or start a new one
+    let (conversation, origin) = get_or_open_conversation(state, &conversation_store, &telegram_user).await?;
+    let is_new_conversation = origin == ConversationOrigin::New;
 
-    let (conversation, is_new_conversation) = match conversation_store.find_one(query).await {
-        Ok(Some(conv)) => (conv, false),
-        Ok(None) | Err(_) => {
-            // Create new conversation
-            let new_conv = Conversation::new(
-                Uuid::new_v4(),
-                telegram_user.id,
-                None,
-                ConversationStatus::Waiting,
-                Some(Utc::now()),
-                0,
-            );
-            conversation_store
-                .create(new_conv.clone(), Some(vec!["new_conversation".to_string()]))
-                .await?;
-            info!("Created new conversation for user {}", telegram_user.id);
-            (new_conv, true)
-        }
+    // A customer reply wakes a snoozed conversation early
+    let conversation = if conversation.is_snoozed() {
+        snooze::wake(&state.storehaus, &state.ws_manager, Some(bot), conversation, snooze::WakeReason::CustomerReply).await?
+    } else {
+        conversation
     };
+    let joins_queue = is_new_conversation || (origin == ConversationOrigin::Reopened && conversation.is_waiting());
 
     // Send WebSocket event for new conversation
     if is_new_conversation {
+        let custom_fields = match custom_fields::values_for(&state.storehaus, telegram_user.id).await {
+            Ok(values) => values,
+            Err(e) => {
+                warn!("Failed to load custom fields of user {}: {}", telegram_user.id, e);
+                Default::default()
+            }
+        };
         let ws_event = WebSocketEvent::ConversationCreated {
             conversation_id: conversation.id,
             telegram_user_id: telegram_user.id,
             telegram_user_name: telegram_user.full_name(),
+            custom_fields,
         };
         info!("Broadcasting ConversationCreated event: conversation_id={}, user_id={}, user_name={}",
               conversation.id, telegram_user.id, telegram_user.full_name());
@@ -298,10 +279,30 @@ async fn process_user_message(bot: &Bot, msg: &TgMessage, state: &BotState) -> a
     updated_conv.last_message_at = Some(Utc::now());
     updated_conv.unread_count += 1;
 
-    conversation_store
+    if !is_new_conversation {
+        match sla::policy_for(&state.storehaus, &updated_conv).await {
+            Ok(policy) => sla::on_customer_message(&mut updated_conv, policy.as_ref(), Utc::now()),
+            Err(e) => warn!("Failed to load SLA policy for conversation {}: {}", conversation_id, e),
+        }
+    }
+
+    // Priority rules (VIP tags, intake topic, keywords) may raise the priority
+    let priority_changed = match priority::apply_rules(&state.storehaus, &mut updated_conv, &text).await {
+        Ok(changed) => changed,
+        Err(e) => {
+            warn!("Failed to apply priority rules to conversation {}: {}", conversation_id, e);
+            false
+        }
+    };
+
+    let updated_conv = conversation_store
         .update(&conversation_id, updated_conv, None)
         .await?;
 
+    if priority_changed {
+        priority::broadcast_change(&state.ws_manager, &updated_conv, None).await;
+    }
+
     info!(
         "Message saved: conversation_id={}, message_id={}",
         conversation_id, message.id
@@ -309,10 +310,21 @@ async fn process_user_message(bot: &Bot, msg: &TgMessage, state: &BotState) -> a
 
     // Send acknowledgment only for new conversations
     if is_new_conversation {
-        bot.send_message(msg.chat.id, &locale.bot.welcome)
+        bot.send_message(msg.chat.id, &bot_texts.welcome)
             .await?;
     }
 
+    // Hand new conversations to an available operator (no-op with manual routing),
+    // otherwise tell the customer where they are in the queue
+    if joins_queue {
+        if let Err(e) = routing::drain_queue(&state.storehaus, &state.ws_manager, Some(bot)).await {
+            error!("Failed to route conversation {}: {}", conversation_id, e);
+        }
+        if let Err(e) = queue::refresh(&state.storehaus, &state.ws_manager, Some(bot)).await {
+            error!("Failed to refresh queue: {}", e);
+        }
+    }
+
     // Broadcast MessageReceived event to all connected users
     let telegram_user_name = telegram_user.username
         .clone()
@@ -340,6 +352,197 @@ async fn process_user_message(bot: &Bot, msg: &TgMessage, state: &BotState) -> a
     Ok(())
 }
 
+/// Get or create the Telegram user who sent a message
+async fn get_or_create_telegram_user(
+    user: &teloxide::types::User,
+    user_store: &GenericStore<TelegramUser>,
+) -> anyhow::Result<TelegramUser> {
+    if let Ok(Some(existing)) = user_store.get_by_id(&(user.id.0 as i64)).await {
+        return Ok(existing);
+    }
+
+    // Extract country code from language_code (e.g., "ru" -> "RU", "en-US" -> "US")
+    let country_code = user.language_code.as_ref().and_then(|lang| {
+        if lang.contains('-') {
+            // Format: "en-US" -> "US"
+            lang.split('-').nth(1).map(|s| s.to_uppercase())
+        } else {
+            // Format: "ru" -> "RU"
+            Some(lang.to_uppercase())
+        }
+    });
+
+    // Create new user
+    let new_user = TelegramUser::new(
+        user.id.0 as i64,
+        user.username.clone(),
+        user.first_name.clone(),
+        user.last_name.clone(),
+        None, // photo_url - will be fetched separately
+        country_code.clone(),
+        false,
+        None,
+        None,
+        None,
+        None,
+    );
+    user_store.create(new_user.clone(), Some(vec!["new_user".to_string()])).await?;
+    info!("Created new Telegram user: {} with country_code: {:?}", user.id, country_code);
+
+    Ok(new_user)
+}
+
+/// Where the conversation for an incoming customer message came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConversationOrigin {
+    /// Waiting, active or snoozed conversation
+    Existing,
+    /// Closed conversation reopened within the reopen window
+    Reopened,
+    /// Newly created conversation
+    New,
+}
+
+async fn get_or_open_conversation(
+    state: &BotState,
+    conversation_store: &GenericStore<Conversation>,
+    telegram_user: &TelegramUser,
+) -> anyhow::Result<(Conversation, ConversationOrigin)> {
+    if let Ok(Some(conv)) = find_open_conversation(conversation_store, telegram_user.id).await {
+        return Ok((conv, ConversationOrigin::Existing));
+    }
+
+    let last_closed = match find_last_closed_conversation(&state.storehaus, telegram_user.id).await {
+        Ok(conv) => conv,
+        Err(e) => {
+            warn!("Failed to load last closed conversation of user {}: {}", telegram_user.id, e);
+            None
+        }
+    };
+
+    let reopen_window = match reopen_settings(&state.storehaus).await {
+        Ok(settings) => settings.reopen_window_minutes,
+        Err(e) => {
+            warn!("Failed to load reopen settings: {}", e);
+            0
+        }
+    };
+
+    if let Some(closed) = last_closed.as_ref() {
+        if conversations::can_reopen(closed, reopen_window, Utc::now()) {
+            let conv = conversations::reopen(&state.storehaus, &state.ws_manager, closed.clone(), Utc::now()).await?;
+            info!("Reopened conversation {} for user {}", conv.id, telegram_user.id);
+            return Ok((conv, ConversationOrigin::Reopened));
+        }
+    }
+
+    // Create new conversation, attributed to the latest deep link source
+    let mut new_conv = Conversation::new_waiting(telegram_user.id, telegram_user.last_source.clone());
+    new_conv.previous_conversation_id = last_closed.map(|c| c.id);
+
+    // Start SLA timers (tag-specific policies apply once tags are added)
+    match sla::active_policies(&state.storehaus).await {
+        Ok(policies) => {
+            let policy = sla::select_policy(&policies, new_conv.source.as_deref(), &[]);
+            sla::start_timers(&mut new_conv, policy, Utc::now());
+        }
+        Err(e) => warn!("Failed to load SLA policies: {}", e),
+    }
+
+    conversation_store
+        .create(new_conv.clone(), Some(vec!["new_conversation".to_string()]))
+        .await?;
+    info!("Created new conversation for user {}", telegram_user.id);
+
+    Ok((new_conv, ConversationOrigin::New))
+}
+
+/// Find the waiting, active or snoozed conversation of a Telegram user
+async fn find_open_conversation(
+    conversation_store: &GenericStore<Conversation>,
+    telegram_user_id: i64,
+) -> anyhow::Result<Option<Conversation>> {
+    let query = QueryBuilder::new()
+        .filter(QueryFilter::eq("telegram_user_id", json!(telegram_user_id)))
+        .filter(QueryFilter::or(vec![
+            QueryFilter::eq("status", json!(ConversationStatus::Waiting.as_str())),
+            QueryFilter::eq("status", json!(ConversationStatus::Active.as_str())),
+            QueryFilter::eq("status", json!(ConversationStatus::Snoozed.as_str())),
+        ]));
+
+    Ok(conversation_store.find_one(query).await?)
+}
+
+/// Most recently closed conversation of a telegram user. Conversations
+/// closed by a merge live on in their target and are never reopened.
+async fn find_last_closed_conversation(
+    storehaus: &StoreHaus,
+    telegram_user_id: i64,
+) -> anyhow::Result<Option<Conversation>> {
+    let conversation = sqlx::query_as::<_, Conversation>(
+        "SELECT * FROM conversations
+         WHERE telegram_user_id = $1 AND status = $2 AND __deleted_at__ IS NULL
+           AND closed_reason IS DISTINCT FROM $3
+         ORDER BY closed_at DESC NULLS LAST
+         LIMIT 1",
+    )
+    .bind(telegram_user_id)
+    .bind(ConversationStatus::Closed.as_str())
+    .bind(Conversation::MERGED_REASON)
+    .fetch_optional(storehaus.pool())
+    .await?;
+
+    Ok(conversation)
+}
+
+/// Store the deep link source from `/start <payload>`
+///
+/// The first source is kept on the user (first touch), the latest one is
+/// recorded as last touch and attributed to the open or next conversation.
+async fn record_start_source(msg: &TgMessage, state: &BotState, payload: &str) -> anyhow::Result<()> {
+    let user = msg.from.as_ref().ok_or_else(|| anyhow::anyhow!("No user in message"))?;
+
+    let Some(link) = deep_link::parse_payload(payload, state.deep_link_secret.as_deref()) else {
+        warn!("Ignoring invalid deep link payload from user {}", user.id);
+        return Ok(());
+    };
+
+    let user_store = state
+        .storehaus
+        .get_store::<GenericStore<TelegramUser>>("telegram_users")?;
+
+    let mut telegram_user = get_or_create_telegram_user(user, &user_store).await?;
+
+    if telegram_user.first_source.is_none() {
+        telegram_user.first_source = Some(link.source.clone());
+    }
+    telegram_user.last_source = Some(link.source.clone());
+    if let Some(customer_id) = link.customer_id {
+        telegram_user.external_customer_id = Some(customer_id);
+    }
+
+    let telegram_user_id = telegram_user.id;
+    user_store
+        .update(&telegram_user_id, telegram_user, Some(vec!["deep_link".to_string()]))
+        .await?;
+
+    // Attribute an already open conversation that has no source yet
+    let conversation_store = state
+        .storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")?;
+
+    if let Some(mut conv) = find_open_conversation(&conversation_store, telegram_user_id).await? {
+        if conv.source.is_none() {
+            conv.source = Some(link.source.clone());
+            let conv_id = conv.id;
+            conversation_store.update(&conv_id, conv, None).await?;
+        }
+    }
+
+    info!("Recorded deep link source '{}' for user {}", link.source, telegram_user_id);
+    Ok(())
+}
+
 /// Send message to Telegram user (called by users)
 pub async fn send_message_to_telegram_user(
     bot: &Bot,
@@ -376,6 +579,16 @@ pub async fn send_message_to_telegram_user(
     }
 }
 
+/// Build download URL for a file on the bot's API server
+fn file_url(bot: &Bot, file_path: &str) -> String {
+    format!(
+        "{}/file/bot{}/{}",
+        bot.api_url().as_str().trim_end_matches('/'),
+        bot.token(),
+        file_path
+    )
+}
+
 /// Fetch and update user's profile photo
 async fn update_user_profile_photo(
     bot: &Bot,
@@ -402,8 +615,7 @@ async fn update_user_profile_photo(
                 };
 
                 // Construct photo URL
-                let token = bot.token();
-                let photo_url = format!("https://api.telegram.org/file/bot{}/{}", token, file.path);
+                let photo_url = file_url(bot, &file.path);
                 info!("Constructed photo URL from chat for user {}: {}", user_id, photo_url);
 
                 // Update user with photo URL
@@ -454,8 +666,7 @@ async fn update_user_profile_photo(
             };
 
             // Construct photo URL
-            let token = bot.token();
-            let photo_url = format!("https://api.telegram.org/file/bot{}/{}", token, file.path);
+            let photo_url = file_url(bot, &file.path);
             info!("Constructed photo URL for user {}: {}", user_id, photo_url);
 
             // Update user with photo URL
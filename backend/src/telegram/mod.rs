@@ -5,6 +5,6 @@ mod bot_manager;
 mod commands;
 mod handlers;
 
-pub use bot::{run_bot, BotState};
-pub use bot_manager::{BotManager, BotStatus};
-pub use handlers::{send_message_to_telegram_user, SendMessageResult};
\ No newline at end of file
+pub use bot::{connect, run_bot, spawn_dispatcher, BotState, ConnectError};
+pub use bot_manager::{BotHealth, BotManager, BotStatus};
+pub use handlers::{handle_message, send_message_to_telegram_user, SendMessageResult};
\ No newline at end of file
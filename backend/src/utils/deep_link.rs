@@ -0,0 +1,192 @@
+//! Deep-link `/start` payloads
+//!
+//! Links like `https://t.me/<bot>?start=<payload>` reach the bot as
+//! `/start <payload>`. Telegram limits payloads to 64 characters from
+//! `A-Z a-z 0-9 _ -`.
+//!
+//! Supported formats:
+//! * `<source>` - plain source tag, e.g. `website_pricing`
+//! * `<source>-<customer_id>-<signature>` - source plus our own customer ID,
+//!   signed with `DEEP_LINK_SECRET`. The signature is the first 16 characters
+//!   of the base64url HMAC-SHA256 of `<source>-<customer_id>`.
+
+use jsonwebtoken::{crypto, Algorithm, EncodingKey};
+
+use crate::errors::AppError;
+
+/// Maximum payload length accepted by Telegram
+pub const MAX_PAYLOAD_LEN: usize = 64;
+
+/// Length of the truncated signature
+const SIGNATURE_LEN: usize = 16;
+
+/// Parsed `/start` payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeepLink {
+    /// Where the customer came from
+    pub source: String,
+    /// Verified customer ID from our systems
+    pub customer_id: Option<String>,
+}
+
+/// Parse a `/start` payload. Signed customer IDs are only accepted when the
+/// signature matches `secret`; otherwise the whole payload is the source.
+pub fn parse_payload(payload: &str, secret: Option<&str>) -> Option<DeepLink> {
+    let payload = payload.trim();
+    if payload.is_empty() || payload.len() > MAX_PAYLOAD_LEN || !payload.chars().all(is_payload_char) {
+        return None;
+    }
+
+    if let Some((source, customer_id, signature)) = split_signed(payload) {
+        let verified = secret
+            .and_then(|secret| sign_message(&format!("{}-{}", source, customer_id), secret))
+            .is_some_and(|expected| constant_time_eq(expected.as_bytes(), signature.as_bytes()));
+
+        if verified {
+            return Some(DeepLink {
+                source: source.to_string(),
+                customer_id: Some(customer_id.to_string()),
+            });
+        }
+    }
+
+    // Unsigned, or a plain hyphenated source that merely looks signed
+    Some(DeepLink {
+        source: payload.to_string(),
+        customer_id: None,
+    })
+}
+
+/// Build a signed payload for `source` and `customer_id`
+pub fn sign_payload(source: &str, customer_id: &str, secret: &str) -> Result<String, AppError> {
+    let is_part_valid = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if !is_part_valid(source) || !is_part_valid(customer_id) {
+        return Err(AppError::Validation(
+            "Source and customer ID may only contain letters, digits and '_'".to_string(),
+        ));
+    }
+
+    let message = format!("{}-{}", source, customer_id);
+    let signature = sign_message(&message, secret)
+        .ok_or_else(|| AppError::Internal("Failed to sign deep link".to_string()))?;
+
+    let payload = format!("{}-{}", message, signature);
+    if payload.len() > MAX_PAYLOAD_LEN {
+        return Err(AppError::Validation(format!(
+            "Deep link payload must be at most {} characters",
+            MAX_PAYLOAD_LEN
+        )));
+    }
+
+    Ok(payload)
+}
+
+/// Split `<source>-<customer_id>-<signature>`. The signature is base64url and
+/// may itself contain `-`, so it is taken as the fixed-length tail.
+fn split_signed(payload: &str) -> Option<(&str, &str, &str)> {
+    let split_at = payload.len().checked_sub(SIGNATURE_LEN + 1)?;
+    let (message, signature) = payload.split_at(split_at);
+    let signature = signature.strip_prefix('-')?;
+
+    let (source, customer_id) = message.split_once('-')?;
+    if source.is_empty() || customer_id.is_empty() || customer_id.contains('-') {
+        return None;
+    }
+
+    Some((source, customer_id, signature))
+}
+
+fn is_payload_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+fn sign_message(message: &str, secret: &str) -> Option<String> {
+    let signature = crypto::sign(
+        message.as_bytes(),
+        &EncodingKey::from_secret(secret.as_bytes()),
+        Algorithm::HS256,
+    )
+    .ok()?;
+
+    Some(signature[..SIGNATURE_LEN].to_string())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "test_secret";
+
+    #[test]
+    fn test_parse_plain_source() {
+        let link = parse_payload("website_pricing", Some(SECRET)).unwrap();
+        assert_eq!(link.source, "website_pricing");
+        assert_eq!(link.customer_id, None);
+
+        let link = parse_payload("black-friday", None).unwrap();
+        assert_eq!(link.source, "black-friday");
+    }
+
+    #[test]
+    fn test_parse_signed_payload() {
+        let payload = sign_payload("website", "cust_42", SECRET).unwrap();
+
+        let link = parse_payload(&payload, Some(SECRET)).unwrap();
+        assert_eq!(link.source, "website");
+        assert_eq!(link.customer_id.as_deref(), Some("cust_42"));
+    }
+
+    #[test]
+    fn test_parse_signed_payload_round_trip() {
+        // Roughly a fifth of signatures contain '-'
+        for i in 0..500 {
+            let customer_id = format!("cust_{}", i);
+            let payload = sign_payload("website", &customer_id, SECRET).unwrap();
+
+            let link = parse_payload(&payload, Some(SECRET)).unwrap();
+            assert_eq!(link.source, "website", "payload {}", payload);
+            assert_eq!(link.customer_id.as_deref(), Some(customer_id.as_str()), "payload {}", payload);
+        }
+    }
+
+    #[test]
+    fn test_parse_signed_payload_rejects_bad_signature() {
+        let payload = sign_payload("website", "cust_42", SECRET).unwrap();
+        let tampered = payload.replace("cust_42", "cust_43");
+
+        let link = parse_payload(&tampered, Some(SECRET)).unwrap();
+        assert_eq!(link.source, tampered);
+        assert_eq!(link.customer_id, None);
+
+        let link = parse_payload(&payload, None).unwrap();
+        assert_eq!(link.source, payload);
+        assert_eq!(link.customer_id, None);
+    }
+
+    #[test]
+    fn test_parse_hyphenated_source_with_signature_sized_tail() {
+        for secret in [Some(SECRET), None] {
+            let link = parse_payload("summer-campaign-instagramstory01", secret).unwrap();
+            assert_eq!(link.source, "summer-campaign-instagramstory01");
+            assert_eq!(link.customer_id, None);
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_payload() {
+        assert_eq!(parse_payload("", Some(SECRET)), None);
+        assert_eq!(parse_payload("has spaces", Some(SECRET)), None);
+        assert_eq!(parse_payload(&"a".repeat(65), Some(SECRET)), None);
+    }
+
+    #[test]
+    fn test_sign_payload_validates_parts() {
+        assert!(sign_payload("web-site", "42", SECRET).is_err());
+        assert!(sign_payload("website", "", SECRET).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 // Utilities module
 
+pub mod deep_link;
 pub mod jwt;
 
-pub use jwt::{generate_token, verify_token, Claims};
\ No newline at end of file
+pub use jwt::{generate_token, verify_token, Claims};
@@ -1,6 +1,11 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
+use crate::services::queue::QueueEntry;
+
 /// WebSocket event types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -52,6 +57,9 @@ pub enum WebSocketEvent {
         conversation_id: Uuid,
         telegram_user_id: i64,
         telegram_user_name: String,
+        /// The customer's custom field values keyed by field key
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        custom_fields: BTreeMap<String, Value>,
     },
 
     /// Conversation status changed
@@ -68,11 +76,81 @@ pub enum WebSocketEvent {
         user_name: String,
     },
 
+    /// Conversation transferred to another operator (sent to the operators involved)
+    ConversationTransferred {
+        conversation_id: Uuid,
+        from_user_id: Option<Uuid>,
+        to_user_id: Uuid,
+        transferred_by: Uuid,
+        transferred_by_name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        note: Option<String>,
+    },
+
     /// Conversation closed
     ConversationClosed {
         conversation_id: Uuid,
     },
 
+    /// Conversation snoozed until `snoozed_until`
+    ConversationSnoozed {
+        conversation_id: Uuid,
+        snoozed_until: DateTime<Utc>,
+        snoozed_by: Uuid,
+    },
+
+    /// Snoozed conversation woke up ("scheduled", "customer_reply" or "manual")
+    ConversationWoken {
+        conversation_id: Uuid,
+        status: String,
+        user_id: Option<Uuid>,
+        reason: String,
+    },
+
+    /// Priority or category changed by an operator (`changed_by`) or a
+    /// priority rule (`changed_by: None`)
+    ConversationPriorityChanged {
+        conversation_id: Uuid,
+        priority: String,
+        category: Option<String>,
+        changed_by: Option<Uuid>,
+    },
+
+    /// Messages of `source_conversation_id` moved into this conversation
+    ConversationMerged {
+        conversation_id: Uuid,
+        source_conversation_id: Uuid,
+        message_count: i64,
+        merged_by: Option<Uuid>,
+    },
+
+    /// Conversation moved to the trash
+    ConversationDeleted {
+        conversation_id: Uuid,
+        deleted_by: Uuid,
+    },
+
+    /// Conversation restored from the trash
+    ConversationRestored {
+        conversation_id: Uuid,
+    },
+
+    /// Closed conversation reopened by a new customer message
+    ConversationReopened {
+        conversation_id: Uuid,
+        status: String,
+        user_id: Option<Uuid>,
+    },
+
+    /// Operator presence in a conversation ("viewing", "typing" or "idle"),
+    /// sent to the other operators present
+    ConversationPresence {
+        conversation_id: Uuid,
+        user_id: Uuid,
+        user_name: String,
+        state: String,
+    },
+
     /// User typing indicator
     UserTyping {
         conversation_id: Uuid,
@@ -97,10 +175,84 @@ pub enum WebSocketEvent {
         user_id: Uuid,
     },
 
-    /// Message read
+    /// Message read; sent only to the reader, whose unread count it carries
     MessageRead {
         message_id: Uuid,
         conversation_id: Uuid,
+        unread_count: i64,
+    },
+
+    /// Internal note added to a conversation
+    NoteAdded {
+        conversation_id: Uuid,
+        message_id: Uuid,
+        content: String,
+        user_id: Uuid,
+        user_name: String,
+        mentioned_user_ids: Vec<Uuid>,
+    },
+
+    /// Internal note edited
+    NoteEdited {
+        conversation_id: Uuid,
+        message_id: Uuid,
+        content: String,
+        user_id: Uuid,
+    },
+
+    /// Current user was mentioned in an internal note
+    NoteMention {
+        conversation_id: Uuid,
+        message_id: Uuid,
+        content: String,
+        author_id: Option<Uuid>,
+        author_name: String,
+    },
+
+    /// Conversation tags changed
+    ConversationTagsChanged {
+        conversation_id: Uuid,
+        tag_ids: Vec<Uuid>,
+        user_id: Uuid,
+    },
+
+    /// Telegram user tags changed
+    TelegramUserTagsChanged {
+        telegram_user_id: i64,
+        tag_ids: Vec<Uuid>,
+        user_id: Uuid,
+    },
+
+    /// Tag created, updated or deleted by an admin
+    TagChanged {
+        tag_id: Uuid,
+        /// "created", "updated" or "deleted"
+        action: String,
+    },
+
+    /// SLA deadline approaching
+    SlaWarning {
+        conversation_id: Uuid,
+        /// "first_response", "next_response" or "resolution"
+        timer: String,
+        due_at: DateTime<Utc>,
+        user_id: Option<Uuid>,
+    },
+
+    /// SLA deadline missed
+    SlaBreached {
+        conversation_id: Uuid,
+        /// "first_response", "next_response" or "resolution"
+        timer: String,
+        due_at: DateTime<Utc>,
+        user_id: Option<Uuid>,
+    },
+
+    /// Waiting queue changed
+    QueueUpdated {
+        entries: Vec<QueueEntry>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        average_first_response_seconds: Option<f64>,
     },
 
     /// Error event
@@ -112,6 +264,16 @@ pub enum WebSocketEvent {
     /// Bot status changed
     BotStatus {
         status: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bot_username: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        connected_since: Option<DateTime<Utc>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        last_error: Option<String>,
+        #[serde(default)]
+        restart_count: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        next_retry_at: Option<DateTime<Utc>>,
     },
 }
 
@@ -124,10 +286,25 @@ impl WebSocketEvent {
             | Self::ConversationCreated { conversation_id, .. }
             | Self::ConversationStatusChanged { conversation_id, .. }
             | Self::ConversationAssigned { conversation_id, .. }
+            | Self::ConversationTransferred { conversation_id, .. }
             | Self::ConversationClosed { conversation_id }
+            | Self::ConversationReopened { conversation_id, .. }
+            | Self::ConversationSnoozed { conversation_id, .. }
+            | Self::ConversationWoken { conversation_id, .. }
+            | Self::ConversationPriorityChanged { conversation_id, .. }
+            | Self::ConversationMerged { conversation_id, .. }
+            | Self::ConversationPresence { conversation_id, .. }
+            | Self::ConversationDeleted { conversation_id, .. }
+            | Self::ConversationRestored { conversation_id }
             | Self::UserTyping { conversation_id, .. }
             | Self::TelegramUserTyping { conversation_id, .. }
-            | Self::MessageRead { conversation_id, .. } => Some(*conversation_id),
+            | Self::MessageRead { conversation_id, .. }
+            | Self::NoteAdded { conversation_id, .. }
+            | Self::NoteEdited { conversation_id, .. }
+            | Self::NoteMention { conversation_id, .. }
+            | Self::ConversationTagsChanged { conversation_id, .. }
+            | Self::SlaWarning { conversation_id, .. }
+            | Self::SlaBreached { conversation_id, .. } => Some(*conversation_id),
             _ => None,
         }
     }
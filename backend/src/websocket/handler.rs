@@ -5,10 +5,13 @@ use axum::{
 };
 use std::collections::HashMap;
 use std::sync::Arc;
+use storehaus::prelude::*;
 use watchtower::prelude::*;
 
 use crate::config::AppConfig;
+use crate::services::{presence, routing};
 use crate::utils;
+use crate::websocket::WebSocketManager;
 
 /// WebSocket connection handler
 /// Authentication via Sec-WebSocket-Protocol header
@@ -17,6 +20,8 @@ pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(config): State<AppConfig>,
     State(transport): State<Arc<WebSocketServerTransport>>,
+    State(storehaus): State<Arc<StoreHaus>>,
+    State(ws_manager): State<Arc<WebSocketManager>>,
     request: Request,
 ) -> Response {
     tracing::info!("WebSocket connection attempt");
@@ -67,16 +72,17 @@ pub async fn websocket_handler(
         }
     };
 
-    let operator_id = match claims.user_id() {
+    let user_id = match claims.user_id() {
         Ok(id) => {
             tracing::info!("Extracted user_id: {}", id);
-            id.to_string()
+            id
         }
         Err(e) => {
             tracing::error!("Failed to extract user_id from claims: {}", e);
             return (StatusCode::UNAUTHORIZED, "Invalid token claims").into_response();
         }
     };
+    let operator_id = user_id.to_string();
     let operator_email = claims.email.clone();
 
     tracing::info!("WebSocket authentication successful for operator: {} ({})", operator_email, operator_id);
@@ -98,6 +104,7 @@ pub async fn websocket_handler(
             // Handle the connection (Watchtower will manage the full lifecycle)
             // This should block until the connection is closed
             let start = std::time::Instant::now();
+            ws_manager.connection_opened(user_id);
             transport.handle_connection(socket, Some(metadata)).await;
             let duration = start.elapsed();
 
@@ -106,6 +113,11 @@ pub async fn websocket_handler(
                 operator_id,
                 duration
             );
+
+            if ws_manager.connection_closed(user_id) {
+                presence::disconnect(&ws_manager, user_id).await;
+                routing::spawn_operator_disconnected(storehaus, ws_manager, user_id);
+            }
         });
 
     tracing::info!("Returning WebSocket upgrade response");
@@ -1,20 +1,32 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use storehaus::prelude::*;
+use tracing::debug;
 use uuid::Uuid;
 use watchtower::prelude::*;
 
+use crate::services::access;
 use crate::websocket::events::WebSocketEvent;
 
 /// WebSocket manager using Watchtower WebSocketServerTransport
 pub struct WebSocketManager {
     transport: Arc<WebSocketServerTransport>,
+    storehaus: Arc<StoreHaus>,
+    /// Open connections per operator (one per browser tab)
+    connections: Mutex<HashMap<Uuid, usize>>,
 }
 
 impl WebSocketManager {
-    /// Create new WebSocket manager
-    pub fn new(config: WebSocketServerConfig) -> Self {
+    /// Create new WebSocket manager. `storehaus` is used to find who may
+    /// see conversation events.
+    pub fn new(config: WebSocketServerConfig, storehaus: Arc<StoreHaus>) -> Self {
         let transport = Arc::new(WebSocketServerTransport::new(config));
 
-        Self { transport }
+        Self {
+            transport,
+            storehaus,
+            connections: Mutex::new(HashMap::new()),
+        }
     }
 
     /// Get transport for use in Axum router
@@ -22,8 +34,30 @@ impl WebSocketManager {
         self.transport.clone()
     }
 
-    /// Broadcast WebSocketEvent to all connected users
+    /// Broadcast WebSocketEvent to connected users. Conversation events go
+    /// only to the users who can read the conversation.
     pub async fn broadcast_event(&self, event: WebSocketEvent) -> Result<(), String> {
+        if let Some(conversation_id) = event.conversation_id() {
+            let mut recipients = access::conversation_readers(&self.storehaus, conversation_id)
+                .await
+                .map_err(|e| format!("Failed to load conversation readers: {}", e))?;
+
+            // The previous assignee may have lost access but has to drop the conversation
+            if let WebSocketEvent::ConversationTransferred { from_user_id: Some(from), .. } = &event {
+                if !recipients.contains(from) {
+                    recipients.push(*from);
+                }
+            }
+
+            for user_id in recipients {
+                // Readers without an open connection just miss the event
+                if let Err(e) = self.send_to_user(&user_id, event.clone()).await {
+                    debug!("Event not delivered to user {}: {}", user_id, e);
+                }
+            }
+            return Ok(());
+        }
+
         let json = serde_json::to_value(&event)
             .map_err(|e| format!("Failed to serialize WebSocketEvent: {}", e))?;
 
@@ -56,6 +90,31 @@ impl WebSocketManager {
             .await
     }
 
+    /// Record a newly opened connection of `user_id`
+    pub fn connection_opened(&self, user_id: Uuid) {
+        *self.connections.lock().unwrap().entry(user_id).or_default() += 1;
+    }
+
+    /// Record a closed connection of `user_id`. Returns whether it was their last one.
+    pub fn connection_closed(&self, user_id: Uuid) -> bool {
+        let mut connections = self.connections.lock().unwrap();
+        match connections.get_mut(&user_id) {
+            Some(count) if *count > 1 => {
+                *count -= 1;
+                false
+            }
+            _ => {
+                connections.remove(&user_id);
+                true
+            }
+        }
+    }
+
+    /// Whether `user_id` has an open connection
+    pub fn is_connected(&self, user_id: Uuid) -> bool {
+        self.connections.lock().unwrap().contains_key(&user_id)
+    }
+
     /// Get count of active connections
     pub async fn active_connections(&self) -> usize {
         self.transport.active_connections().await
@@ -70,12 +129,30 @@ fn event_type_from_ws_event(event: &WebSocketEvent) -> &'static str {
         WebSocketEvent::ConversationCreated { .. } => "conversation.created",
         WebSocketEvent::ConversationStatusChanged { .. } => "conversation.status_changed",
         WebSocketEvent::ConversationAssigned { .. } => "conversation.assigned",
+        WebSocketEvent::ConversationTransferred { .. } => "conversation.transferred",
         WebSocketEvent::ConversationClosed { .. } => "conversation.closed",
+        WebSocketEvent::ConversationReopened { .. } => "conversation.reopened",
+        WebSocketEvent::ConversationSnoozed { .. } => "conversation.snoozed",
+        WebSocketEvent::ConversationWoken { .. } => "conversation.woken",
+        WebSocketEvent::ConversationPriorityChanged { .. } => "conversation.priority_changed",
+        WebSocketEvent::ConversationMerged { .. } => "conversation.merged",
+        WebSocketEvent::ConversationPresence { .. } => "conversation.presence",
+        WebSocketEvent::ConversationDeleted { .. } => "conversation.deleted",
+        WebSocketEvent::ConversationRestored { .. } => "conversation.restored",
         WebSocketEvent::UserTyping { .. } => "user.typing",
         WebSocketEvent::TelegramUserTyping { .. } => "telegram_user.typing",
         WebSocketEvent::UserOnline { .. } => "user.online",
         WebSocketEvent::UserOffline { .. } => "user.offline",
         WebSocketEvent::MessageRead { .. } => "message.read",
+        WebSocketEvent::NoteAdded { .. } => "note.added",
+        WebSocketEvent::NoteEdited { .. } => "note.edited",
+        WebSocketEvent::NoteMention { .. } => "note.mention",
+        WebSocketEvent::ConversationTagsChanged { .. } => "conversation.tags_changed",
+        WebSocketEvent::TelegramUserTagsChanged { .. } => "telegram_user.tags_changed",
+        WebSocketEvent::TagChanged { .. } => "tag.changed",
+        WebSocketEvent::SlaWarning { .. } => "sla.warning",
+        WebSocketEvent::SlaBreached { .. } => "sla.breached",
+        WebSocketEvent::QueueUpdated { .. } => "queue.updated",
         WebSocketEvent::Error { .. } => "error",
         WebSocketEvent::BotStatus { .. } => "bot.status",
     }
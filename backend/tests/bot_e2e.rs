@@ -0,0 +1,270 @@
+//! End-to-end tests for the Telegram bot against the mock Bot API server
+
+mod common;
+
+use axum::{extract::State, Extension, Json};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use storehaus::prelude::*;
+use tokio::sync::RwLock;
+
+use common::{unique_telegram_id, MockTelegram, TestContext, TestUser, BOT_USERNAME, TEST_TOKEN};
+use flashback_backend::{
+    api::handlers::messages::{create_note, send_message, CreateNoteRequest, SendMessageRequest},
+    errors::AppError,
+    models::{Conversation, Message, MessageKind, TelegramUser},
+    telegram::{
+        connect, handle_message, send_message_to_telegram_user, BotHealth, BotState, BotStatus,
+        ConnectError, SendMessageResult,
+    },
+};
+
+const WAIT: Duration = Duration::from_secs(5);
+
+/// Poll the database until the customer's conversation appears
+async fn wait_for_conversation(ctx: &TestContext, telegram_user_id: i64) -> Conversation {
+    let store = ctx
+        .storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .unwrap();
+
+    let deadline = tokio::time::Instant::now() + WAIT;
+    loop {
+        let query = QueryBuilder::new()
+            .filter(QueryFilter::eq("telegram_user_id", json!(telegram_user_id)));
+        if let Some(conv) = store.find_one(query).await.unwrap() {
+            return conv;
+        }
+        assert!(tokio::time::Instant::now() < deadline, "conversation was not created");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_connect_with_valid_token() {
+    let mock = MockTelegram::start().await;
+
+    let me = connect(&mock.bot()).await.expect("connect failed");
+
+    assert_eq!(me.username(), BOT_USERNAME);
+    assert_eq!(mock.calls("getMe").len(), 1);
+}
+
+#[tokio::test]
+async fn test_connect_with_invalid_token() {
+    let mock = MockTelegram::start().await;
+
+    let result = connect(&mock.bot_with_token("999:WRONG")).await;
+
+    assert!(matches!(result, Err(ConnectError::InvalidToken(_))));
+}
+
+#[tokio::test]
+async fn test_send_message_records_outgoing_call() {
+    let mock = MockTelegram::start().await;
+
+    let result = send_message_to_telegram_user(&mock.bot(), 42, "Hello from support").await;
+
+    assert!(matches!(result, SendMessageResult::Success(_)));
+    let calls = mock.calls("sendMessage");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].params["chat_id"], 42);
+    assert_eq!(calls[0].params["text"], "Hello from support");
+}
+
+#[tokio::test]
+async fn test_send_message_detects_blocked_user() {
+    let mock = MockTelegram::start().await;
+    mock.fail_next("sendMessage", 403, "Forbidden: bot was blocked by the user");
+
+    let result = send_message_to_telegram_user(&mock.bot(), 42, "Hello").await;
+
+    assert!(matches!(result, SendMessageResult::UserBlocked));
+}
+
+#[tokio::test]
+#[ignore = "requires PostgreSQL"]
+async fn test_incoming_message_creates_conversation() {
+    let mock = MockTelegram::start().await;
+    let ctx = TestContext::new(&mock).await;
+
+    ctx.bot_manager.start(TEST_TOKEN.to_string()).await.unwrap();
+    assert_eq!(ctx.bot_manager.status().await, BotStatus::Connected);
+
+    let customer = TestUser::new(unique_telegram_id(), "Alice");
+    mock.push_text(&customer, "My order has not arrived");
+
+    let conv = wait_for_conversation(&ctx, customer.id).await;
+
+    let message_store = ctx.storehaus.get_store::<GenericStore<Message>>("messages").unwrap();
+    let query = QueryBuilder::new().filter(QueryFilter::eq("conversation_id", json!(conv.id)));
+    let messages = message_store.find(query).await.unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].content, "My order has not arrived");
+    assert!(!messages[0].from_user);
+
+    let user_store = ctx.storehaus.get_store::<GenericStore<TelegramUser>>("telegram_users").unwrap();
+    let telegram_user = user_store.get_by_id(&customer.id).await.unwrap().unwrap();
+    assert_eq!(telegram_user.first_name, "Alice");
+
+    assert!(ctx.bot_manager.health().await.last_update_at.is_some());
+
+    ctx.bot_manager.stop().await.unwrap();
+    assert_eq!(ctx.bot_manager.status().await, BotStatus::Disconnected);
+}
+
+#[tokio::test]
+#[ignore = "requires PostgreSQL"]
+async fn test_start_command_sends_welcome() {
+    let mock = MockTelegram::start().await;
+    let ctx = TestContext::new(&mock).await;
+
+    let state = BotState {
+        storehaus: ctx.storehaus.clone(),
+        ws_manager: ctx.ws_manager.clone(),
+        health: Arc::new(RwLock::new(BotHealth::default())),
+        deep_link_secret: None,
+    };
+
+    let customer = TestUser::new(unique_telegram_id(), "Bob");
+    handle_message(mock.bot(), mock.text_message(&customer, "/start"), state)
+        .await
+        .unwrap();
+
+    let calls = mock.calls("sendMessage");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].params["chat_id"], customer.id);
+    assert!(calls[0].params["text"].as_str().unwrap().contains("Bob"));
+}
+
+#[tokio::test]
+#[ignore = "requires PostgreSQL"]
+async fn test_start_payload_records_source() {
+    let mock = MockTelegram::start().await;
+    let ctx = TestContext::new(&mock).await;
+
+    let state = BotState {
+        storehaus: ctx.storehaus.clone(),
+        ws_manager: ctx.ws_manager.clone(),
+        health: Arc::new(RwLock::new(BotHealth::default())),
+        deep_link_secret: None,
+    };
+
+    let customer = TestUser::new(unique_telegram_id(), "Dave");
+    handle_message(mock.bot(), mock.text_message(&customer, "/start website_pricing"), state.clone())
+        .await
+        .unwrap();
+    handle_message(mock.bot(), mock.text_message(&customer, "/start newsletter"), state)
+        .await
+        .unwrap();
+
+    let user_store = ctx.storehaus.get_store::<GenericStore<TelegramUser>>("telegram_users").unwrap();
+    let telegram_user = user_store.get_by_id(&customer.id).await.unwrap().unwrap();
+    assert_eq!(telegram_user.first_source.as_deref(), Some("website_pricing"));
+    assert_eq!(telegram_user.last_source.as_deref(), Some("newsletter"));
+    assert_eq!(telegram_user.external_customer_id, None);
+}
+
+#[tokio::test]
+#[ignore = "requires PostgreSQL"]
+async fn test_operator_reply_is_delivered() {
+    let mock = MockTelegram::start().await;
+    let ctx = TestContext::new(&mock).await;
+    ctx.bot_manager.start(TEST_TOKEN.to_string()).await.unwrap();
+
+    let customer = TestUser::new(unique_telegram_id(), "Carol");
+    mock.push_text(&customer, "Hi");
+    let conv = wait_for_conversation(&ctx, customer.id).await;
+
+    let Json(response) = send_message(
+        Extension(ctx.operator.clone()),
+        State(ctx.storehaus.clone()),
+        State(ctx.ws_manager.clone()),
+        State(ctx.bot_manager.clone()),
+        Json(SendMessageRequest {
+            conversation_id: conv.id,
+            content: "How can we help?".to_string(),
+            force: None,
+        }),
+    )
+    .await
+    .unwrap();
+
+    let call = mock
+        .wait_for_call("sendMessage", WAIT, |c| c.params["text"] == "How can we help?")
+        .await
+        .expect("reply was not sent to Telegram");
+    assert_eq!(call.params["chat_id"], customer.id);
+    assert!(response.telegram_message_id.is_some());
+
+    ctx.bot_manager.stop().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires PostgreSQL"]
+async fn test_unrelated_operator_cannot_reply() {
+    let mock = MockTelegram::start().await;
+    let ctx = TestContext::new(&mock).await;
+    ctx.bot_manager.start(TEST_TOKEN.to_string()).await.unwrap();
+
+    let customer = TestUser::new(unique_telegram_id(), "Dave");
+    mock.push_text(&customer, "Hello?");
+    let mut conv = wait_for_conversation(&ctx, customer.id).await;
+
+    let store = ctx
+        .storehaus
+        .get_store::<GenericStore<Conversation>>("conversations")
+        .unwrap();
+    conv.user_id = Some(ctx.operator.user_id);
+    store.update(&conv.id, conv.clone(), None).await.unwrap();
+
+    let outsider = ctx.create_operator("Outsider").await;
+    let result = send_message(
+        Extension(outsider),
+        State(ctx.storehaus.clone()),
+        State(ctx.ws_manager.clone()),
+        State(ctx.bot_manager.clone()),
+        Json(SendMessageRequest {
+            conversation_id: conv.id,
+            content: "Not my conversation".to_string(),
+            force: None,
+        }),
+    )
+    .await;
+
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+    assert!(mock.calls("sendMessage").iter().all(|c| c.params["text"] != "Not my conversation"));
+
+    ctx.bot_manager.stop().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires PostgreSQL"]
+async fn test_internal_note_is_not_sent_to_telegram() {
+    let mock = MockTelegram::start().await;
+    let ctx = TestContext::new(&mock).await;
+    ctx.bot_manager.start(TEST_TOKEN.to_string()).await.unwrap();
+
+    let customer = TestUser::new(unique_telegram_id(), "Erin");
+    mock.push_text(&customer, "Where is my refund?");
+    let conv = wait_for_conversation(&ctx, customer.id).await;
+
+    let Json(note) = create_note(
+        Extension(ctx.operator.clone()),
+        axum::extract::Path(conv.id),
+        State(ctx.storehaus.clone()),
+        State(ctx.ws_manager.clone()),
+        State(ctx.bot_manager.clone()),
+        Json(CreateNoteRequest {
+            content: "Customer is VIP, refund approved".to_string(),
+        }),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(note.kind, MessageKind::Note);
+    assert!(mock.calls("sendMessage").is_empty());
+
+    ctx.bot_manager.stop().await.unwrap();
+}
@@ -0,0 +1,414 @@
+//! Test harness: in-process fake Telegram Bot API
+//!
+//! `MockTelegram` serves the Bot API methods used by the backend on a random
+//! local port. Point a `Bot` at it with `set_api_url` (or use
+//! `BotManager::with_api_url`), script incoming updates with `push_text`, and
+//! assert on outgoing requests with `calls` / `wait_for_call`.
+//!
+//! Tests that need a database use `TestContext`, which connects with the
+//! same configuration as the server (`DB_*` / `EXTERNAL_DB_*` variables or
+//! `storehaus.toml`). They are marked `#[ignore]`; run them against a local
+//! Postgres with `cargo test -- --ignored`.
+
+#![allow(dead_code)]
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::post,
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use storehaus::prelude::*;
+use teloxide::Bot;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use watchtower::prelude::WebSocketServerConfig;
+
+use flashback_backend::{
+    api::middleware::AuthUser, db::initialize_database, models::User, telegram::BotManager,
+    websocket::WebSocketManager,
+};
+
+/// Token accepted by the mock server
+pub const TEST_TOKEN: &str = "123456:TEST-TOKEN";
+
+/// Bot user returned by `getMe`
+pub const BOT_ID: i64 = 123456;
+pub const BOT_USERNAME: &str = "flashback_test_bot";
+
+/// How long `getUpdates` waits for new updates before returning empty
+const POLL_WAIT: Duration = Duration::from_millis(500);
+
+/// Recorded Bot API request
+#[derive(Debug, Clone)]
+pub struct ApiCall {
+    /// Method name as sent by the client (e.g. "SendMessage")
+    pub method: String,
+    /// Request parameters (JSON body, `Null` if empty)
+    pub params: Value,
+}
+
+impl ApiCall {
+    pub fn is(&self, method: &str) -> bool {
+        self.method.eq_ignore_ascii_case(method)
+    }
+}
+
+/// Telegram user sending scripted updates
+#[derive(Debug, Clone)]
+pub struct TestUser {
+    pub id: i64,
+    pub first_name: String,
+    pub username: Option<String>,
+    pub language_code: Option<String>,
+}
+
+impl TestUser {
+    pub fn new(id: i64, first_name: &str) -> Self {
+        Self {
+            id,
+            first_name: first_name.to_string(),
+            username: None,
+            language_code: Some("en".to_string()),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "is_bot": false,
+            "first_name": self.first_name,
+            "username": self.username,
+            "language_code": self.language_code,
+        })
+    }
+
+    fn chat_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "type": "private",
+            "first_name": self.first_name,
+            "username": self.username,
+        })
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    /// Updates not yet acknowledged by the client
+    updates: Mutex<VecDeque<Value>>,
+    /// Wakes up pending `getUpdates` calls
+    updates_ready: Notify,
+    /// Every request received, in order
+    calls: Mutex<Vec<ApiCall>>,
+    /// Wakes up `wait_for_call`
+    call_received: Notify,
+    /// Scripted errors per lowercased method name
+    failures: Mutex<HashMap<String, VecDeque<(u16, String)>>>,
+    next_update_id: AtomicI64,
+    next_message_id: AtomicI64,
+}
+
+/// In-process fake Telegram Bot API server
+pub struct MockTelegram {
+    pub url: reqwest::Url,
+    state: Arc<MockState>,
+    handle: JoinHandle<()>,
+}
+
+impl MockTelegram {
+    /// Start the server on a random local port
+    pub async fn start() -> Self {
+        let state = Arc::new(MockState {
+            next_update_id: AtomicI64::new(1),
+            next_message_id: AtomicI64::new(1),
+            ..Default::default()
+        });
+
+        let app = Router::new()
+            .route("/:bot_token/:method", post(handle_request))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind mock Telegram server");
+        let addr = listener.local_addr().expect("Mock server has no address");
+
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("Mock Telegram server failed");
+        });
+
+        let url = reqwest::Url::parse(&format!("http://{}", addr)).expect("Invalid mock URL");
+
+        Self { url, state, handle }
+    }
+
+    /// Bot pointed at this server with the valid test token
+    pub fn bot(&self) -> Bot {
+        self.bot_with_token(TEST_TOKEN)
+    }
+
+    /// Bot pointed at this server with an arbitrary token
+    pub fn bot_with_token(&self, token: &str) -> Bot {
+        Bot::new(token).set_api_url(self.url.clone())
+    }
+
+    /// Queue a text message from `from`, returning the update ID
+    pub fn push_text(&self, from: &TestUser, text: &str) -> i64 {
+        let update_id = self.state.next_update_id.fetch_add(1, Ordering::SeqCst);
+        let message = self.message_json(from, text);
+
+        self.state
+            .updates
+            .lock()
+            .unwrap()
+            .push_back(json!({ "update_id": update_id, "message": message }));
+        self.state.updates_ready.notify_one();
+
+        update_id
+    }
+
+    /// Build a teloxide message to pass directly to `handle_message`
+    pub fn text_message(&self, from: &TestUser, text: &str) -> teloxide::types::Message {
+        serde_json::from_value(self.message_json(from, text)).expect("Invalid test message")
+    }
+
+    /// Make the next call to `method` fail with the given error
+    pub fn fail_next(&self, method: &str, error_code: u16, description: &str) {
+        self.state
+            .failures
+            .lock()
+            .unwrap()
+            .entry(method.to_ascii_lowercase())
+            .or_default()
+            .push_back((error_code, description.to_string()));
+    }
+
+    /// All recorded calls to `method`
+    pub fn calls(&self, method: &str) -> Vec<ApiCall> {
+        self.state
+            .calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c.is(method))
+            .cloned()
+            .collect()
+    }
+
+    /// Wait until a call to `method` matching `predicate` is received
+    pub async fn wait_for_call<F>(&self, method: &str, timeout: Duration, predicate: F) -> Option<ApiCall>
+    where
+        F: Fn(&ApiCall) -> bool,
+    {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            let notified = self.state.call_received.notified();
+
+            if let Some(call) = self.calls(method).into_iter().find(|c| predicate(c)) {
+                return Some(call);
+            }
+
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return None;
+            }
+        }
+    }
+
+    fn message_json(&self, from: &TestUser, text: &str) -> Value {
+        json!({
+            "message_id": self.state.next_message_id.fetch_add(1, Ordering::SeqCst),
+            "date": chrono::Utc::now().timestamp(),
+            "chat": from.chat_json(),
+            "from": from.to_json(),
+            "text": text,
+        })
+    }
+}
+
+impl Drop for MockTelegram {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Handle `POST /bot<token>/<method>`
+async fn handle_request(
+    State(state): State<Arc<MockState>>,
+    Path((bot_token, method)): Path<(String, String)>,
+    body: Bytes,
+) -> (StatusCode, Json<Value>) {
+    let params: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    state.calls.lock().unwrap().push(ApiCall {
+        method: method.clone(),
+        params: params.clone(),
+    });
+    state.call_received.notify_waiters();
+
+    if bot_token.strip_prefix("bot") != Some(TEST_TOKEN) {
+        return error(401, "Unauthorized");
+    }
+
+    let method = method.to_ascii_lowercase();
+
+    let failure = state
+        .failures
+        .lock()
+        .unwrap()
+        .get_mut(&method)
+        .and_then(|queue| queue.pop_front());
+    if let Some((code, description)) = failure {
+        return error(code, &description);
+    }
+
+    match method.as_str() {
+        "getme" => ok(json!({
+            "id": BOT_ID,
+            "is_bot": true,
+            "first_name": "FlashBack Test",
+            "username": BOT_USERNAME,
+            "can_join_groups": false,
+            "can_read_all_group_messages": false,
+            "supports_inline_queries": false,
+            "can_connect_to_business": false,
+            "has_main_web_app": false,
+        })),
+        "getupdates" => ok(get_updates(&state, &params).await),
+        "sendmessage" => {
+            let chat_id = params["chat_id"].as_i64().unwrap_or_default();
+            ok(json!({
+                "message_id": state.next_message_id.fetch_add(1, Ordering::SeqCst),
+                "date": chrono::Utc::now().timestamp(),
+                "chat": { "id": chat_id, "type": "private", "first_name": "Customer" },
+                "from": {
+                    "id": BOT_ID,
+                    "is_bot": true,
+                    "first_name": "FlashBack Test",
+                    "username": BOT_USERNAME,
+                },
+                "text": params["text"],
+            }))
+        }
+        "getfile" => ok(json!({
+            "file_id": params["file_id"],
+            "file_unique_id": "unique",
+            "file_size": 1024,
+            "file_path": "photos/file_0.jpg",
+        })),
+        // No chat photos: handlers fall back to getUserProfilePhotos
+        "getchat" => error(400, "Bad Request: chat not found"),
+        "getuserprofilephotos" => ok(json!({ "total_count": 0, "photos": [] })),
+        _ => ok(json!(true)),
+    }
+}
+
+/// Return unacknowledged updates, waiting briefly if there are none
+async fn get_updates(state: &MockState, params: &Value) -> Value {
+    let offset = params["offset"].as_i64().unwrap_or(0);
+
+    let pending = {
+        let mut updates = state.updates.lock().unwrap();
+        updates.retain(|u| u["update_id"].as_i64().unwrap_or_default() >= offset);
+        !updates.is_empty()
+    };
+
+    if !pending {
+        let _ = tokio::time::timeout(POLL_WAIT, state.updates_ready.notified()).await;
+    }
+
+    let updates = state.updates.lock().unwrap();
+    Value::Array(
+        updates
+            .iter()
+            .filter(|u| u["update_id"].as_i64().unwrap_or_default() >= offset)
+            .cloned()
+            .collect(),
+    )
+}
+
+fn ok(result: Value) -> (StatusCode, Json<Value>) {
+    (StatusCode::OK, Json(json!({ "ok": true, "result": result })))
+}
+
+fn error(code: u16, description: &str) -> (StatusCode, Json<Value>) {
+    let status = StatusCode::from_u16(code).unwrap_or(StatusCode::BAD_REQUEST);
+    (
+        status,
+        Json(json!({ "ok": false, "error_code": code, "description": description })),
+    )
+}
+
+/// Backend services wired to a mock Telegram server and a real database
+pub struct TestContext {
+    pub storehaus: Arc<StoreHaus>,
+    pub ws_manager: Arc<WebSocketManager>,
+    pub bot_manager: Arc<BotManager>,
+    /// Active operator created for this context
+    pub operator: AuthUser,
+}
+
+impl TestContext {
+    /// Connect to the test database and create a bot manager using `mock`
+    pub async fn new(mock: &MockTelegram) -> Self {
+        let storehaus = Arc::new(
+            initialize_database()
+                .await
+                .expect("Failed to connect to test database"),
+        );
+        let ws_manager = Arc::new(WebSocketManager::new(WebSocketServerConfig::default(), storehaus.clone()));
+        let bot_manager = Arc::new(
+            BotManager::new(storehaus.clone(), ws_manager.clone())
+                .with_api_url(Some(mock.url.clone())),
+        );
+        let operator = create_operator(&storehaus, "Operator").await;
+
+        Self {
+            storehaus,
+            ws_manager,
+            bot_manager,
+            operator,
+        }
+    }
+
+    /// Create another active operator
+    pub async fn create_operator(&self, name: &str) -> AuthUser {
+        create_operator(&self.storehaus, name).await
+    }
+}
+
+/// Insert an active operator with a unique email
+async fn create_operator(storehaus: &StoreHaus, name: &str) -> AuthUser {
+    let store = storehaus
+        .get_store::<GenericStore<User>>("users")
+        .expect("Users store is not registered");
+
+    let email = format!("operator-{}@example.com", uuid::Uuid::new_v4());
+    // Tests call handlers directly and never log in, so the hash is never checked
+    let user = User::new(
+        uuid::Uuid::new_v4(),
+        email.clone(),
+        name.to_string(),
+        "not-a-password-hash".to_string(),
+        true,
+        false,
+        true,
+        None,
+        None,
+    );
+    let user = store.create(user, None).await.expect("Failed to create operator");
+
+    AuthUser { user_id: user.id, email }
+}
+
+/// Telegram user ID that does not collide with other test runs
+pub fn unique_telegram_id() -> i64 {
+    1_000_000_000 + (uuid::Uuid::new_v4().as_u128() % 1_000_000_000) as i64
+}
@@ -1,5 +1,5 @@
 import apiClient from './client';
-import type { Conversation, ConversationListQuery } from '@/types';
+import type { CloseConversationRequest, Conversation, ConversationListQuery, UnreadResponse } from '@/types';
 
 interface ConversationListResponse {
   conversations: Conversation[];
@@ -13,6 +13,12 @@ export const conversationsApi = {
     return data;
   },
 
+  // Get the caller's unread counts across their inbox
+  getUnread: async () => {
+    const { data } = await apiClient.get<UnreadResponse>('/conversations/unread');
+    return data;
+  },
+
   // Get single conversation by ID
   getById: async (id: string) => {
     const { data } = await apiClient.get<Conversation>(`/conversations/${id}`);
@@ -35,9 +41,9 @@ export const conversationsApi = {
     return data;
   },
 
-  // Close conversation
-  close: async (id: string) => {
-    const { data } = await apiClient.patch<Conversation>(`/conversations/${id}/close`);
+  // Close conversation, optionally with a disposition code and resolution summary
+  close: async (id: string, wrapUp?: CloseConversationRequest) => {
+    const { data } = await apiClient.patch<Conversation>(`/conversations/${id}/close`, wrapUp ?? {});
     return data;
   },
 
@@ -1,5 +1,5 @@
 import apiClient from './client';
-import type { Message, MessageEdit, MessageListQuery, SearchMessagesQuery, SendMessageRequest } from '@/types';
+import type { Message, MessageEdit, MessageListQuery, SearchMessagesQuery, SearchMessagesResponse, SendMessageRequest } from '@/types';
 
 interface MessageListResponse {
   messages: Message[];
@@ -42,7 +42,7 @@ export const messagesApi = {
 
   // Search messages
   search: async (params: SearchMessagesQuery) => {
-    const { data } = await apiClient.get<Message[]>('/messages/search', { params });
+    const { data } = await apiClient.get<SearchMessagesResponse>('/messages/search', { params });
     return data;
   },
 };
@@ -6,15 +6,13 @@ import { conversationsApi } from '@/api/conversations';
  */
 export function useUnreadCount() {
   const { data } = useQuery({
-    queryKey: ['conversations', { status: undefined }],
-    queryFn: () => conversationsApi.getAll({}),
+    queryKey: ['conversations', 'unread'],
+    queryFn: () => conversationsApi.getUnread(),
     refetchInterval: 30000, // Refetch every 30 seconds
   });
 
-  const unreadCount = data?.conversations?.reduce(
-    (total: number, conv) => total + (conv.unread_count || 0),
-    0
-  ) || 0;
+  // The conversation list is paged, so totals come from the unread endpoint
+  const unreadCount = (data?.assigned.messages ?? 0) + (data?.queue.messages ?? 0);
 
   return { unreadCount };
 }
@@ -15,11 +15,32 @@ export interface Conversation {
   telegram_user: TelegramUser;
   user_id?: string;
   status: 'waiting' | 'active' | 'closed';
+  priority: ConversationPriority;
+  category?: string | null;
   last_message_at?: string;
   unread_count: number;
+  closed_at?: string | null;
+  closed_by?: string | null;
+  disposition?: string | null;
+  resolution_summary?: string | null;
   created_at: string;
 }
 
+export interface CloseConversationRequest {
+  disposition?: string;
+  resolution_summary?: string;
+}
+
+export interface DispositionCode {
+  id: string;
+  code: string;
+  label: string;
+  position: number;
+  is_active: boolean;
+}
+
+export type ConversationPriority = 'low' | 'normal' | 'high' | 'urgent';
+
 export interface Message {
   id: string;
   conversation_id: string;
@@ -114,20 +135,76 @@ export interface ConversationListQuery {
   status?: 'waiting' | 'active' | 'closed';
   user_id?: string;
   search?: string;
+  priority?: ConversationPriority;
+  category?: string;
+  // Defaults to 'priority': highest priority first, then longest waiting
+  sort?: 'priority' | 'last_message' | 'time_to_breach';
+  // Default 50, at most 200
   limit?: number;
   offset?: number;
 }
 
+export interface UnreadBadgeCount {
+  conversations: number;
+  messages: number;
+}
+
+// The caller's unread counts: open conversations assigned to them and the shared queue
+export interface UnreadResponse {
+  conversations: { conversation_id: string; unread_count: number }[];
+  assigned: UnreadBadgeCount;
+  queue: UnreadBadgeCount;
+}
+
 export interface MessageListQuery {
   conversation_id: string;
   limit?: number;
   offset?: number;
+  // Message ID or RFC 3339 timestamp
+  before?: string;
+  after?: string;
+  // Message ID to center the page on
+  around?: string;
 }
 
 export interface SearchMessagesQuery {
   query: string;
   conversation_id?: string;
+  from?: string;
+  to?: string;
+  sender?: 'customer' | 'operator' | 'note';
+  operator_id?: string;
+  media_type?: string;
+  tag_id?: string;
+  status?: string;
   limit?: number;
+  cursor?: string;
+}
+
+export interface MessageSearchResult {
+  message: Message;
+  rank: number;
+  snippet: {
+    text: string;
+    // [start, end) character offsets into text
+    highlights: [number, number][];
+  };
+  conversation: {
+    id: string;
+    status: string;
+    user_id?: string | null;
+    customer: {
+      id: number;
+      first_name: string;
+      last_name?: string | null;
+      username?: string | null;
+    };
+  };
+}
+
+export interface SearchMessagesResponse {
+  results: MessageSearchResult[];
+  next_cursor?: string | null;
 }
 
 export interface CreateTemplateRequest {
@@ -7,6 +7,7 @@ export type WebSocketEvent =
   | ConversationStatusChangedEvent
   | ConversationAssignedEvent
   | ConversationClosedEvent
+  | ConversationPriorityChangedEvent
   | UserTypingEvent
   | TelegramUserTypingEvent
   | UserOnlineEvent
@@ -49,6 +50,7 @@ export interface ConversationCreatedEvent {
   conversation_id: string;
   telegram_user_id: number;
   telegram_user_name: string;
+  custom_fields?: Record<string, unknown>;
 }
 
 export interface ConversationStatusChangedEvent {
@@ -70,6 +72,15 @@ export interface ConversationClosedEvent {
   conversation_id: string;
 }
 
+export interface ConversationPriorityChangedEvent {
+  type: 'conversation_priority_changed';
+  conversation_id: string;
+  priority: 'low' | 'normal' | 'high' | 'urgent';
+  category?: string | null;
+  // null when set by a priority rule
+  changed_by?: string | null;
+}
+
 export interface UserTypingEvent {
   type: 'user_typing';
   conversation_id: string;
@@ -98,6 +109,8 @@ export interface MessageReadEvent {
   type: 'message_read';
   message_id: string;
   conversation_id: string;
+  // the reader's remaining unread count in the conversation
+  unread_count: number;
 }
 
 export interface ErrorEvent {